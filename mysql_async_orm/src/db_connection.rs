@@ -1,23 +1,283 @@
-use std::{future::Future, pin::Pin};
+use std::{
+    borrow::Cow,
+    fmt,
+    future::Future,
+    pin::Pin,
+    sync::{Arc, OnceLock},
+    time::Duration,
+};
 
 use mysql_async::{
-    prelude::{AsQuery, FromRow, Queryable, StatementLike},
+    prelude::{AsQuery, FromRow, StatementLike},
     TxOpts,
 };
 
 #[derive(Clone)]
 pub struct DbConnectionPool {
-	pub pool: mysql_async::Pool,
+    pub pool: mysql_async::Pool,
+    hooks: QueryHooks,
+    utc_offset: Option<i32>,
 }
 
 pub struct DbConnection {
-	pub conn: mysql_async::Conn,
+    pub conn: mysql_async::Conn,
+    auto_transaction: bool,
+    hooks: QueryHooks,
+    utc_offset: Option<i32>,
 }
 pub struct DbTransaction<'a> {
-	pub conn: mysql_async::Transaction<'a>,
+    pub conn: mysql_async::Transaction<'a>,
+    hooks: QueryHooks,
+    utc_offset: Option<i32>,
+}
+
+#[derive(Debug)]
+pub enum OrmError {
+    NotFound { table: &'static str, pk: String },
+    PkMustBeSome,
+    PkMustBeNone,
+    RowDecode {
+        model: &'static str,
+        table: &'static str,
+        field: &'static str,
+        column: String,
+        index: usize,
+        sql_type: String,
+    },
+    /// A field of a model being written could not be encoded, e.g. a `DbJson` value serde cannot serialize.
+    Encode {
+        model: &'static str,
+        table: &'static str,
+        field: &'static str,
+        message: String,
+    },
+    MissingInsertId,
+    /// A `DBTimestamp` was read on a connection whose session time zone is not known.
+    MissingTimeZone {
+        model: &'static str,
+        table: &'static str,
+        field: &'static str,
+    },
+    Migration(crate::migrations::MigrationError),
+    Date(crate::db_date::DateError),
+    Driver(mysql_async::Error),
+}
+
+pub type DbError = OrmError;
+
+impl fmt::Display for OrmError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OrmError::NotFound { table, pk } => write!(f, "Not found: {} with pk {}", table, pk),
+            OrmError::PkMustBeSome => write!(f, "Pk must be Some"),
+            OrmError::PkMustBeNone => write!(f, "Pk must be None"),
+            OrmError::RowDecode { model, table, field, column, index, sql_type } => write!(
+                f,
+                "Cannot decode {}.{} from column {} (index {}, {}) of {}",
+                model, field, column, index, sql_type, table
+            ),
+            OrmError::Encode { model, table, field, message } => write!(f, "Cannot encode {}.{} for {}: {}", model, field, table, message),
+            OrmError::MissingInsertId => write!(f, "The server did not return an insert id"),
+            OrmError::MissingTimeZone { model, table, field } => write!(
+                f,
+                "Cannot decode {}.{} of {}: DBTimestamp needs the session time zone, set with DbConnectionPool::with_time_zone or DbConnection::set_time_zone",
+                model, field, table
+            ),
+            OrmError::Migration(error) => write!(f, "{}", error),
+            OrmError::Date(error) => write!(f, "{}", error),
+            OrmError::Driver(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl std::error::Error for OrmError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            OrmError::Migration(error) => Some(error),
+            OrmError::Date(error) => Some(error),
+            OrmError::Driver(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<mysql_async::Error> for OrmError {
+    fn from(error: mysql_async::Error) -> Self {
+        OrmError::Driver(error)
+    }
+}
+
+impl From<crate::migrations::MigrationError> for OrmError {
+    fn from(error: crate::migrations::MigrationError) -> Self {
+        OrmError::Migration(error)
+    }
+}
+
+impl From<crate::db_date::DateError> for OrmError {
+    fn from(error: crate::db_date::DateError) -> Self {
+        OrmError::Date(error)
+    }
+}
+
+impl From<mysql_async::DriverError> for OrmError {
+    fn from(error: mysql_async::DriverError) -> Self {
+        OrmError::Driver(error.into())
+    }
+}
+
+/// A statement executed through `QueryableConn`, as reported to query hooks.
+#[derive(Debug)]
+pub struct QueryEvent<'a> {
+    pub method: &'static str,
+    /// Empty unless a hook is registered or the `tracing` feature is enabled.
+    pub sql: &'a str,
+    pub params: usize,
+    pub duration: Duration,
+    /// Not available for statements returning rows (see `returns_rows`), for
+    /// `query_iter`/`exec_iter` or when the statement failed.
+    pub affected_rows: Option<u64>,
+    pub error: Option<&'a DbError>,
+}
+
+/// Whether `sql` starts with a keyword of a statement returning rows, e.g. `SELECT`, whose
+/// affected rows are not reported.
+pub fn returns_rows(sql: &str) -> bool {
+    let keyword = sql.trim_start_matches(|c: char| c.is_whitespace() || c == '(').split(|c: char| !c.is_ascii_alphabetic()).next().unwrap_or_default();
+    ["SELECT", "SHOW", "EXPLAIN", "DESCRIBE", "DESC", "WITH", "VALUES", "TABLE"].iter().any(|k| keyword.eq_ignore_ascii_case(k))
+}
+
+/// Receives every statement executed through `QueryableConn`, e.g. to log it or to detect
+/// slow queries. Add it to a pool or a connection with `add_query_hook`.
+pub trait QueryHook: Send + Sync {
+    fn on_query(&self, event: &QueryEvent<'_>);
+    /// A row that could not be decoded and was skipped by a lenient query, see `Query::lenient`.
+    fn on_skipped_row(&self, _error: &DbError) {}
+}
+
+impl<F: Fn(&QueryEvent<'_>) + Send + Sync> QueryHook for F {
+    fn on_query(&self, event: &QueryEvent<'_>) {
+        self(event)
+    }
+}
+
+/// The hooks of a pool or a connection. Connections start with the hooks of their pool,
+/// transactions use the hooks of their connection.
+#[derive(Clone, Default)]
+pub struct QueryHooks {
+    hooks: Arc<Vec<Arc<dyn QueryHook>>>,
+}
+
+impl QueryHooks {
+    pub fn add<H: QueryHook + 'static>(&mut self, hook: H) {
+        Arc::make_mut(&mut self.hooks).push(Arc::new(hook));
+    }
+
+    pub fn clear(&mut self) {
+        self.hooks = Arc::default();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.hooks.is_empty()
+    }
+
+    pub fn on_query(&self, event: &QueryEvent<'_>) {
+        for hook in self.hooks.iter() {
+            hook.on_query(event);
+        }
+    }
+
+    pub fn on_skipped_row(&self, error: &DbError) {
+        for hook in self.hooks.iter() {
+            hook.on_skipped_row(error);
+        }
+    }
+}
+
+impl fmt::Debug for QueryHooks {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "QueryHooks({})", self.hooks.len())
+    }
+}
+
+/// A statement accepted by `QueryableConn`, whose SQL can be reported to hooks and spans.
+///
+/// `QueryableConn` used to accept any `StatementLike`; other statement types need an impl of
+/// this trait now.
+pub trait SqlStatement: StatementLike {
+    fn sql_text(&self) -> Cow<'_, str>;
+}
+
+impl SqlStatement for &'_ str {
+    fn sql_text(&self) -> Cow<'_, str> {
+        Cow::Borrowed(self)
+    }
+}
+
+impl SqlStatement for String {
+    fn sql_text(&self) -> Cow<'_, str> {
+        Cow::Borrowed(self)
+    }
+}
+
+impl SqlStatement for Cow<'_, str> {
+    fn sql_text(&self) -> Cow<'_, str> {
+        Cow::Borrowed(self)
+    }
 }
 
-pub type DbError = mysql_async::Error;
+impl SqlStatement for Box<str> {
+    fn sql_text(&self) -> Cow<'_, str> {
+        Cow::Borrowed(self)
+    }
+}
+
+impl SqlStatement for Arc<str> {
+    fn sql_text(&self) -> Cow<'_, str> {
+        Cow::Borrowed(self)
+    }
+}
+
+impl SqlStatement for &'_ [u8] {
+    fn sql_text(&self) -> Cow<'_, str> {
+        String::from_utf8_lossy(self)
+    }
+}
+
+impl SqlStatement for Vec<u8> {
+    fn sql_text(&self) -> Cow<'_, str> {
+        String::from_utf8_lossy(self)
+    }
+}
+
+impl SqlStatement for Cow<'_, [u8]> {
+    fn sql_text(&self) -> Cow<'_, str> {
+        String::from_utf8_lossy(self)
+    }
+}
+
+impl SqlStatement for Box<[u8]> {
+    fn sql_text(&self) -> Cow<'_, str> {
+        String::from_utf8_lossy(self)
+    }
+}
+
+impl SqlStatement for Arc<[u8]> {
+    fn sql_text(&self) -> Cow<'_, str> {
+        String::from_utf8_lossy(self)
+    }
+}
+
+impl SqlStatement for mysql_async::Statement {
+    fn sql_text(&self) -> Cow<'_, str> {
+        Cow::Owned(format!("<prepared statement {}>", self.id()))
+    }
+}
+
+impl<T: SqlStatement + Clone> SqlStatement for &'_ T {
+    fn sql_text(&self) -> Cow<'_, str> {
+        (**self).sql_text()
+    }
+}
 
 impl DbConnectionPool {
     pub fn new<O>(opts: O) -> Self
@@ -27,39 +287,120 @@ impl DbConnectionPool {
     {
         DbConnectionPool {
             pool: mysql_async::Pool::new(opts),
+            hooks: QueryHooks::default(),
+            utc_offset: None,
         }
     }
+    /// Sets the session `time_zone` of every new connection to a fixed offset from UTC, in
+    /// minutes, and makes it the `db_date::session_utc_offset` used by `DBTimestamp` on them.
+    pub fn with_time_zone<O>(opts: O, utc_offset: i32) -> Result<Self, DbError>
+    where
+        mysql_async::Opts: TryFrom<O>,
+        <mysql_async::Opts as TryFrom<O>>::Error: std::error::Error,
+    {
+        crate::db_date::check_utc_offset(utc_offset)?;
+        let opts = mysql_async::Opts::try_from(opts).map_err(|error| mysql_async::Error::Other(error.to_string().into()))?;
+        Ok(DbConnectionPool {
+            pool: mysql_async::Pool::new::<mysql_async::Opts>(time_zone_opts(opts, utc_offset)),
+            hooks: QueryHooks::default(),
+            utc_offset: Some(utc_offset),
+        })
+    }
     pub fn from_url<T: AsRef<str>>(url: T) -> Result<Self, DbError> {
         Ok(DbConnectionPool {
             pool: mysql_async::Pool::from_url(url)?,
+            hooks: QueryHooks::default(),
+            utc_offset: None,
         })
     }
+    /// Adds a hook to the connections taken from this pool (or its clones) from now on.
+    pub fn add_query_hook<H: QueryHook + 'static>(&mut self, hook: H) {
+        self.hooks.add(hook);
+    }
+    pub fn query_hooks(&self) -> &QueryHooks {
+        &self.hooks
+    }
     pub async fn get_conn(&self) -> Result<DbConnection, DbError> {
-        Ok(DbConnection::new(self.pool.get_conn().await?))
+        let mut conn = DbConnection::new(self.pool.get_conn().await?);
+        conn.hooks = self.hooks.clone();
+        conn.utc_offset = self.utc_offset;
+        Ok(conn)
     }
     pub async fn disconnect(self) -> Result<(), DbError> {
-        self.pool.disconnect().await
+        Ok(self.pool.disconnect().await?)
     }
 }
 
+/// Appends `SET time_zone` to the setup commands, which, unlike the init commands, also run
+/// after the pool resets a connection returned to it.
+fn time_zone_opts(opts: mysql_async::Opts, utc_offset: i32) -> mysql_async::Opts {
+    let mut setup = opts.setup().to_vec();
+    setup.push(format!("SET time_zone = '{}'", crate::db_date::format_utc_offset(utc_offset)));
+    mysql_async::OptsBuilder::from_opts(opts).setup(setup).into()
+}
+
 impl<'a> DbTransaction<'a> {
     pub async fn commit(self) -> Result<(), DbError> {
-        self.conn.commit().await
+        Ok(self.conn.commit().await?)
     }
 
     pub async fn rollback(self) -> Result<(), DbError> {
-        self.conn.rollback().await
+        Ok(self.conn.rollback().await?)
+    }
+
+    /// Commits on `Ok` and rolls back on `Err`, returning `result` unless the commit fails.
+    pub async fn finish<T>(self, result: Result<T, DbError>) -> Result<T, DbError> {
+        match result {
+            Ok(value) => {
+                self.commit().await?;
+                Ok(value)
+            }
+            Err(error) => {
+                let _ = self.rollback().await;
+                Err(error)
+            }
+        }
     }
 }
 
 impl DbConnection {
     pub fn new(conn: mysql_async::Conn) -> Self {
-        DbConnection { conn }
+        DbConnection {
+            conn,
+            auto_transaction: true,
+            hooks: QueryHooks::default(),
+            utc_offset: None,
+        }
+    }
+
+    /// Adds a hook to this connection only.
+    pub fn add_query_hook<H: QueryHook + 'static>(&mut self, hook: H) {
+        self.hooks.add(hook);
+    }
+
+    /// Like `DbConnectionPool::with_time_zone`, for this connection only.
+    pub async fn set_time_zone(&mut self, utc_offset: i32) -> Result<(), DbError> {
+        crate::db_date::check_utc_offset(utc_offset)?;
+        self.query_drop(format!("SET time_zone = '{}'", crate::db_date::format_utc_offset(utc_offset))).await?;
+        self.utc_offset = Some(utc_offset);
+        Ok(())
+    }
+
+    /// Whether generated `exec_insert`/`exec_update`/`exec_delete` wrap their statements in a
+    /// transaction. Disable it when the transaction is managed manually on this connection.
+    pub fn set_auto_transaction(&mut self, enabled: bool) {
+        self.auto_transaction = enabled;
+    }
+
+    pub fn auto_transaction(&self) -> bool {
+        self.auto_transaction
     }
 
     pub async fn start_transaction(&mut self) -> Result<DbTransaction<'_>, DbError> {
         Ok(DbTransaction {
             conn: self.conn.start_transaction(TxOpts::default()).await?,
+            hooks: self.hooks.clone(),
+            utc_offset: self.utc_offset,
         })
     }
 
@@ -67,29 +408,29 @@ impl DbConnection {
         &mut self,
         query: Q,
     ) -> Result<Vec<R>, DbError> {
-        self.conn.query(query).await
+        instrumented::query(&mut self.conn, &self.hooks, self.utc_offset, query).await
     }
 
     pub async fn query_first<Q: AsQuery + Send + Sync, R: FromRow + Send + 'static>(
         &mut self,
         query: Q,
     ) -> Result<Option<R>, DbError> {
-        self.conn.query_first(query).await
+        instrumented::query_first(&mut self.conn, &self.hooks, self.utc_offset, query).await
     }
 
     pub async fn query_iter<'a, Q: AsQuery + Send + Sync + 'a>(
         &'a mut self,
         query: Q,
     ) -> Result<mysql_async::QueryResult<'a, 'static, mysql_async::TextProtocol>, DbError> {
-        self.conn.query_iter(query).await
+        instrumented::query_iter(&mut self.conn, &self.hooks, self.utc_offset, query).await
     }
 
     pub async fn query_drop<Q: AsQuery + Send + Sync>(&mut self, query: Q) -> Result<(), DbError> {
-        self.conn.query_drop(query).await
+        instrumented::query_drop(&mut self.conn, &self.hooks, self.utc_offset, query).await
     }
 
     pub async fn exec<
-        S: StatementLike,
+        S: SqlStatement,
         P: Into<mysql_async::Params> + Send,
         R: FromRow + Send + 'static,
     >(
@@ -97,7 +438,7 @@ impl DbConnection {
         stmt: S,
         params: P,
     ) -> Result<Vec<R>, DbError> {
-        self.conn.exec(stmt, params).await
+        instrumented::exec(&mut self.conn, &self.hooks, self.utc_offset, stmt, params).await
     }
 
     pub fn exec_map<'a: 'b, 'b, T, S, P, U, F>(
@@ -107,13 +448,13 @@ impl DbConnection {
         f: F,
     ) -> BoxFuture<'b, Vec<U>>
     where
-        S: StatementLike + 'b,
+        S: SqlStatement + 'b,
         P: Into<mysql_async::Params> + Send + 'b,
         T: FromRow + Send + 'static,
         F: FnMut(T) -> U + Send + 'a,
         U: Send + 'a,
     {
-        self.conn.exec_map(stmt, params, f)
+        instrumented::exec_map(&mut self.conn, &self.hooks, self.utc_offset, stmt, params, f)
     }
 
     pub fn exec_iter<'a: 's, 's, Q, P>(
@@ -122,14 +463,14 @@ impl DbConnection {
         params: P,
     ) -> BoxFuture<'s, mysql_async::QueryResult<'a, 'static, mysql_async::BinaryProtocol>>
     where
-        Q: StatementLike + 'a,
+        Q: SqlStatement + 'a,
         P: Into<mysql_async::Params>,
     {
-        self.conn.exec_iter(stmt, params)
+        instrumented::exec_iter(&mut self.conn, &self.hooks, self.utc_offset, stmt, params)
     }
 
     pub async fn exec_first<
-        S: StatementLike,
+        S: SqlStatement,
         P: Into<mysql_async::Params> + Send,
         R: FromRow + Send + 'static,
     >(
@@ -137,25 +478,25 @@ impl DbConnection {
         stmt: S,
         params: P,
     ) -> Result<Option<R>, DbError> {
-        self.conn.exec_first(stmt, params).await
+        instrumented::exec_first(&mut self.conn, &self.hooks, self.utc_offset, stmt, params).await
     }
 
-    pub async fn exec_drop<S: StatementLike, P: Into<mysql_async::Params> + Send>(
+    pub async fn exec_drop<S: SqlStatement, P: Into<mysql_async::Params> + Send>(
         &mut self,
         stmt: S,
         params: P,
     ) -> Result<(), DbError> {
-        self.conn.exec_drop(stmt, params).await
+        instrumented::exec_drop(&mut self.conn, &self.hooks, self.utc_offset, stmt, params).await
     }
 
     pub async fn exec_batch<S, P, I>(&mut self, stmt: S, params: I) -> Result<(), DbError>
     where
-        S: StatementLike,
+        S: SqlStatement,
         I: IntoIterator<Item = P> + Send,
         I::IntoIter: Send,
         P: Into<mysql_async::Params> + Send,
     {
-        self.conn.exec_batch(stmt, params).await
+        instrumented::exec_batch(&mut self.conn, &self.hooks, self.utc_offset, stmt, params).await
     }
 
     pub fn last_insert_id(&self) -> Option<u64> {
@@ -172,29 +513,29 @@ impl DbTransaction<'_> {
         &mut self,
         query: Q,
     ) -> Result<Vec<R>, DbError> {
-        self.conn.query(query).await
+        instrumented::query(&mut self.conn, &self.hooks, self.utc_offset, query).await
     }
 
     pub async fn query_first<Q: AsQuery + Send + Sync, R: FromRow + Send + 'static>(
         &mut self,
         query: Q,
     ) -> Result<Option<R>, DbError> {
-        self.conn.query_first(query).await
+        instrumented::query_first(&mut self.conn, &self.hooks, self.utc_offset, query).await
     }
 
     pub async fn query_iter<'a, Q: AsQuery + Send + Sync + 'a>(
         &'a mut self,
         query: Q,
     ) -> Result<mysql_async::QueryResult<'a, 'static, mysql_async::TextProtocol>, DbError> {
-        self.conn.query_iter(query).await
+        instrumented::query_iter(&mut self.conn, &self.hooks, self.utc_offset, query).await
     }
 
     pub async fn query_drop<Q: AsQuery + Send + Sync>(&mut self, query: Q) -> Result<(), DbError> {
-        self.conn.query_drop(query).await
+        instrumented::query_drop(&mut self.conn, &self.hooks, self.utc_offset, query).await
     }
 
     pub async fn exec<
-        S: StatementLike,
+        S: SqlStatement,
         P: Into<mysql_async::Params> + Send,
         R: FromRow + Send + 'static,
     >(
@@ -202,7 +543,7 @@ impl DbTransaction<'_> {
         stmt: S,
         params: P,
     ) -> Result<Vec<R>, DbError> {
-        self.conn.exec(stmt, params).await
+        instrumented::exec(&mut self.conn, &self.hooks, self.utc_offset, stmt, params).await
     }
 
     pub fn exec_map<'a: 'b, 'b, T, S, P, U, F>(
@@ -212,13 +553,13 @@ impl DbTransaction<'_> {
         f: F,
     ) -> BoxFuture<'b, Vec<U>>
     where
-        S: StatementLike + 'b,
+        S: SqlStatement + 'b,
         P: Into<mysql_async::Params> + Send + 'b,
         T: FromRow + Send + 'static,
         F: FnMut(T) -> U + Send + 'a,
         U: Send + 'a,
     {
-        self.conn.exec_map(stmt, params, f)
+        instrumented::exec_map(&mut self.conn, &self.hooks, self.utc_offset, stmt, params, f)
     }
 
     pub fn exec_iter<'a: 's, 's, Q, P>(
@@ -227,14 +568,14 @@ impl DbTransaction<'_> {
         params: P,
     ) -> BoxFuture<'s, mysql_async::QueryResult<'a, 'static, mysql_async::BinaryProtocol>>
     where
-        Q: StatementLike + 'a,
+        Q: SqlStatement + 'a,
         P: Into<mysql_async::Params>,
     {
-        self.conn.exec_iter(stmt, params)
+        instrumented::exec_iter(&mut self.conn, &self.hooks, self.utc_offset, stmt, params)
     }
 
     pub async fn exec_first<
-        S: StatementLike,
+        S: SqlStatement,
         P: Into<mysql_async::Params> + Send,
         R: FromRow + Send + 'static,
     >(
@@ -242,25 +583,25 @@ impl DbTransaction<'_> {
         stmt: S,
         params: P,
     ) -> Result<Option<R>, DbError> {
-        self.conn.exec_first(stmt, params).await
+        instrumented::exec_first(&mut self.conn, &self.hooks, self.utc_offset, stmt, params).await
     }
 
-    pub async fn exec_drop<S: StatementLike, P: Into<mysql_async::Params> + Send>(
+    pub async fn exec_drop<S: SqlStatement, P: Into<mysql_async::Params> + Send>(
         &mut self,
         stmt: S,
         params: P,
     ) -> Result<(), DbError> {
-        self.conn.exec_drop(stmt, params).await
+        instrumented::exec_drop(&mut self.conn, &self.hooks, self.utc_offset, stmt, params).await
     }
 
     pub async fn exec_batch<S, P, I>(&mut self, stmt: S, params: I) -> Result<(), DbError>
     where
-        S: StatementLike,
+        S: SqlStatement,
         I: IntoIterator<Item = P> + Send,
         I::IntoIter: Send,
         P: Into<mysql_async::Params> + Send,
     {
-        self.conn.exec_batch(stmt, params).await
+        instrumented::exec_batch(&mut self.conn, &self.hooks, self.utc_offset, stmt, params).await
     }
 
     pub fn last_insert_id(&self) -> Option<u64> {
@@ -272,27 +613,27 @@ impl DbTransaction<'_> {
     }
 }
 
-type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = Result<T, DbError>> + Send + 'a>>;
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = Result<T, DbError>> + Send + 'a>>;
 
 pub trait QueryableConn {
     fn query<'a, Q, R>(&'a mut self, query: Q) -> BoxFuture<'a, Vec<R>>
     where
         Q: AsQuery + Send + Sync + 'a,
         R: FromRow + Send + 'static;
-    fn query_first<'a, Q, R>(&'a mut self, query: Q) -> BoxFuture<Option<R>>
+    fn query_first<'a, Q, R>(&'a mut self, query: Q) -> BoxFuture<'a, Option<R>>
     where
         Q: AsQuery + Send + Sync + 'a,
         R: FromRow + Send + 'static;
     fn query_iter<'a, Q: AsQuery + Send + Sync + 'a>(
         &'a mut self,
         query: Q,
-    ) -> BoxFuture<mysql_async::QueryResult<'a, 'static, mysql_async::TextProtocol>>;
+    ) -> BoxFuture<'a, mysql_async::QueryResult<'a, 'static, mysql_async::TextProtocol>>;
     fn query_drop<'a, Q>(&'a mut self, query: Q) -> BoxFuture<'a, ()>
     where
         Q: AsQuery + Send + Sync + 'a;
     fn exec<'a, S, P, R>(&'a mut self, stmt: S, params: P) -> BoxFuture<'a, Vec<R>>
     where
-        S: StatementLike + 'a,
+        S: SqlStatement + 'a,
         P: Into<mysql_async::Params> + Send + 'a,
         R: FromRow + Send + 'static;
     fn exec_map<'a: 'b, 'b, T, S, P, U, F>(
@@ -302,7 +643,7 @@ pub trait QueryableConn {
         f: F,
     ) -> BoxFuture<'b, Vec<U>>
     where
-        S: StatementLike + 'b,
+        S: SqlStatement + 'b,
         P: Into<mysql_async::Params> + Send + 'b,
         T: FromRow + Send + 'static,
         F: FnMut(T) -> U + Send + 'a,
@@ -313,25 +654,39 @@ pub trait QueryableConn {
         params: P,
     ) -> BoxFuture<'s, mysql_async::QueryResult<'a, 'static, mysql_async::BinaryProtocol>>
     where
-        Q: StatementLike + 'a,
+        Q: SqlStatement + 'a,
         P: Into<mysql_async::Params>;
     fn exec_first<'a, S, P, R>(&'a mut self, stmt: S, params: P) -> BoxFuture<'a, Option<R>>
     where
-        S: StatementLike + 'a,
+        S: SqlStatement + 'a,
         P: Into<mysql_async::Params> + Send + 'a,
         R: FromRow + Send + 'static;
     fn exec_drop<'a, S, P>(&'a mut self, stmt: S, params: P) -> BoxFuture<'a, ()>
     where
-        S: StatementLike + 'a,
+        S: SqlStatement + 'a,
         P: Into<mysql_async::Params> + Send + 'a;
     fn exec_batch<'a, S, P, I>(&'a mut self, stmt: S, params: I) -> BoxFuture<'a, ()>
     where
-        S: StatementLike + 'a,
+        S: SqlStatement + 'a,
         I: IntoIterator<Item = P> + Send + 'a,
         I::IntoIter: Send,
         P: Into<mysql_async::Params> + Send;
     fn last_insert_id(&self) -> Option<u64>;
     fn affected_rows(&self) -> u64;
+    /// No hooks unless overridden.
+    fn query_hooks(&self) -> &QueryHooks {
+        static NO_HOOKS: OnceLock<QueryHooks> = OnceLock::new();
+        NO_HOOKS.get_or_init(QueryHooks::default)
+    }
+    /// The offset `DBTimestamp`s read through this connection are decoded with, see `db_date::session_utc_offset`.
+    fn session_utc_offset(&self) -> Option<i32> {
+        None
+    }
+    /// Starts the transaction wrapping a graph write. Returns `None` when the connection is
+    /// already a transaction or auto transactions are disabled, and by default.
+    fn begin_atomic<'a>(&'a mut self) -> BoxFuture<'a, Option<DbTransaction<'a>>> {
+        Box::pin(async { Ok(None) })
+    }
 }
 
 impl QueryableConn for DbConnection {
@@ -340,38 +695,38 @@ impl QueryableConn for DbConnection {
         Q: AsQuery + Send + Sync + 'a,
         R: FromRow + Send + 'static,
     {
-        self.conn.query(query)
+        instrumented::query(&mut self.conn, &self.hooks, self.utc_offset, query)
     }
 
-    fn query_first<'a, Q, R>(&'a mut self, query: Q) -> BoxFuture<Option<R>>
+    fn query_first<'a, Q, R>(&'a mut self, query: Q) -> BoxFuture<'a, Option<R>>
     where
         Q: AsQuery + Send + Sync + 'a,
         R: FromRow + Send + 'static,
     {
-        self.conn.query_first(query)
+        instrumented::query_first(&mut self.conn, &self.hooks, self.utc_offset, query)
     }
 
     fn query_iter<'a, Q: AsQuery + Send + Sync + 'a>(
         &'a mut self,
         query: Q,
-    ) -> BoxFuture<mysql_async::QueryResult<'a, 'static, mysql_async::TextProtocol>> {
-        self.conn.query_iter(query)
+    ) -> BoxFuture<'a, mysql_async::QueryResult<'a, 'static, mysql_async::TextProtocol>> {
+        instrumented::query_iter(&mut self.conn, &self.hooks, self.utc_offset, query)
     }
 
     fn query_drop<'a, Q>(&'a mut self, query: Q) -> BoxFuture<'a, ()>
     where
         Q: AsQuery + Send + Sync + 'a,
     {
-        self.conn.query_drop(query)
+        instrumented::query_drop(&mut self.conn, &self.hooks, self.utc_offset, query)
     }
 
     fn exec<'a, S, P, R>(&'a mut self, stmt: S, params: P) -> BoxFuture<'a, Vec<R>>
     where
-        S: StatementLike + 'a,
+        S: SqlStatement + 'a,
         P: Into<mysql_async::Params> + Send + 'a,
         R: FromRow + Send + 'static,
     {
-        self.conn.exec(stmt, params)
+        instrumented::exec(&mut self.conn, &self.hooks, self.utc_offset, stmt, params)
     }
 
     fn exec_map<'a: 'b, 'b, T, S, P, U, F>(
@@ -381,13 +736,13 @@ impl QueryableConn for DbConnection {
         f: F,
     ) -> BoxFuture<'b, Vec<U>>
     where
-        S: StatementLike + 'b,
+        S: SqlStatement + 'b,
         P: Into<mysql_async::Params> + Send + 'b,
         T: FromRow + Send + 'static,
         F: FnMut(T) -> U + Send + 'a,
         U: Send + 'a,
     {
-        self.conn.exec_map(stmt, params, f)
+        instrumented::exec_map(&mut self.conn, &self.hooks, self.utc_offset, stmt, params, f)
     }
 
     fn exec_iter<'a: 's, 's, Q, P>(
@@ -396,37 +751,37 @@ impl QueryableConn for DbConnection {
         params: P,
     ) -> BoxFuture<'s, mysql_async::QueryResult<'a, 'static, mysql_async::BinaryProtocol>>
     where
-        Q: StatementLike + 'a,
+        Q: SqlStatement + 'a,
         P: Into<mysql_async::Params>,
     {
-        self.conn.exec_iter(stmt, params)
+        instrumented::exec_iter(&mut self.conn, &self.hooks, self.utc_offset, stmt, params)
     }
 
     fn exec_first<'a, S, P, R>(&'a mut self, stmt: S, params: P) -> BoxFuture<'a, Option<R>>
     where
-        S: StatementLike + 'a,
+        S: SqlStatement + 'a,
         P: Into<mysql_async::Params> + Send + 'a,
         R: FromRow + Send + 'static,
     {
-        self.conn.exec_first(stmt, params)
+        instrumented::exec_first(&mut self.conn, &self.hooks, self.utc_offset, stmt, params)
     }
 
     fn exec_drop<'a, S, P>(&'a mut self, stmt: S, params: P) -> BoxFuture<'a, ()>
     where
-        S: StatementLike + 'a,
+        S: SqlStatement + 'a,
         P: Into<mysql_async::Params> + Send + 'a,
     {
-        self.conn.exec_drop(stmt, params)
+        instrumented::exec_drop(&mut self.conn, &self.hooks, self.utc_offset, stmt, params)
     }
 
     fn exec_batch<'a, S, P, I>(&'a mut self, stmt: S, params: I) -> BoxFuture<'a, ()>
     where
-        S: StatementLike + 'a,
+        S: SqlStatement + 'a,
         I: IntoIterator<Item = P> + Send + 'a,
         I::IntoIter: Send,
         P: Into<mysql_async::Params> + Send,
     {
-        self.conn.exec_batch(stmt, params)
+        instrumented::exec_batch(&mut self.conn, &self.hooks, self.utc_offset, stmt, params)
     }
 
     fn last_insert_id(&self) -> Option<u64> {
@@ -436,6 +791,24 @@ impl QueryableConn for DbConnection {
     fn affected_rows(&self) -> u64 {
         self.conn.affected_rows()
     }
+
+    fn query_hooks(&self) -> &QueryHooks {
+        &self.hooks
+    }
+
+    fn session_utc_offset(&self) -> Option<i32> {
+        self.utc_offset
+    }
+
+    fn begin_atomic<'a>(&'a mut self) -> BoxFuture<'a, Option<DbTransaction<'a>>> {
+        Box::pin(async move {
+            if self.auto_transaction {
+                Ok(Some(self.start_transaction().await?))
+            } else {
+                Ok(None)
+            }
+        })
+    }
 }
 
 impl QueryableConn for DbTransaction<'_> {
@@ -444,38 +817,38 @@ impl QueryableConn for DbTransaction<'_> {
         Q: AsQuery + Send + Sync + 'a,
         R: FromRow + Send + 'static,
     {
-        self.conn.query(query)
+        instrumented::query(&mut self.conn, &self.hooks, self.utc_offset, query)
     }
 
-    fn query_first<'a, Q, R>(&'a mut self, query: Q) -> BoxFuture<Option<R>>
+    fn query_first<'a, Q, R>(&'a mut self, query: Q) -> BoxFuture<'a, Option<R>>
     where
         Q: AsQuery + Send + Sync + 'a,
         R: FromRow + Send + 'static,
     {
-        self.conn.query_first(query)
+        instrumented::query_first(&mut self.conn, &self.hooks, self.utc_offset, query)
     }
 
     fn query_iter<'a, Q: AsQuery + Send + Sync + 'a>(
         &'a mut self,
         query: Q,
-    ) -> BoxFuture<mysql_async::QueryResult<'a, 'static, mysql_async::TextProtocol>> {
-        self.conn.query_iter(query)
+    ) -> BoxFuture<'a, mysql_async::QueryResult<'a, 'static, mysql_async::TextProtocol>> {
+        instrumented::query_iter(&mut self.conn, &self.hooks, self.utc_offset, query)
     }
 
     fn query_drop<'a, Q>(&'a mut self, query: Q) -> BoxFuture<'a, ()>
     where
         Q: AsQuery + Send + Sync + 'a,
     {
-        self.conn.query_drop(query)
+        instrumented::query_drop(&mut self.conn, &self.hooks, self.utc_offset, query)
     }
 
     fn exec<'a, S, P, R>(&'a mut self, stmt: S, params: P) -> BoxFuture<'a, Vec<R>>
     where
-        S: StatementLike + 'a,
+        S: SqlStatement + 'a,
         P: Into<mysql_async::Params> + Send + 'a,
         R: FromRow + Send + 'static,
     {
-        self.conn.exec(stmt, params)
+        instrumented::exec(&mut self.conn, &self.hooks, self.utc_offset, stmt, params)
     }
 
     fn exec_map<'a: 'b, 'b, T, S, P, U, F>(
@@ -485,52 +858,52 @@ impl QueryableConn for DbTransaction<'_> {
         f: F,
     ) -> BoxFuture<'b, Vec<U>>
     where
-        S: StatementLike + 'b,
+        S: SqlStatement + 'b,
         P: Into<mysql_async::Params> + Send + 'b,
         T: FromRow + Send + 'static,
         F: FnMut(T) -> U + Send + 'a,
         U: Send + 'a,
     {
-        self.conn.exec_map(stmt, params, f)
+        instrumented::exec_map(&mut self.conn, &self.hooks, self.utc_offset, stmt, params, f)
     }
-	
-	fn exec_iter<'a: 's, 's, Q, P>(
+
+    fn exec_iter<'a: 's, 's, Q, P>(
         &'a mut self,
         stmt: Q,
         params: P,
     ) -> BoxFuture<'s, mysql_async::QueryResult<'a, 'static, mysql_async::BinaryProtocol>>
     where
-        Q: StatementLike + 'a,
+        Q: SqlStatement + 'a,
         P: Into<mysql_async::Params>,
     {
-        self.conn.exec_iter(stmt, params)
+        instrumented::exec_iter(&mut self.conn, &self.hooks, self.utc_offset, stmt, params)
     }
 
     fn exec_first<'a, S, P, R>(&'a mut self, stmt: S, params: P) -> BoxFuture<'a, Option<R>>
     where
-        S: StatementLike + 'a,
+        S: SqlStatement + 'a,
         P: Into<mysql_async::Params> + Send + 'a,
         R: FromRow + Send + 'static,
     {
-        self.conn.exec_first(stmt, params)
+        instrumented::exec_first(&mut self.conn, &self.hooks, self.utc_offset, stmt, params)
     }
 
     fn exec_drop<'a, S, P>(&'a mut self, stmt: S, params: P) -> BoxFuture<'a, ()>
     where
-        S: StatementLike + 'a,
+        S: SqlStatement + 'a,
         P: Into<mysql_async::Params> + Send + 'a,
     {
-        self.conn.exec_drop(stmt, params)
+        instrumented::exec_drop(&mut self.conn, &self.hooks, self.utc_offset, stmt, params)
     }
 
     fn exec_batch<'a, S, P, I>(&'a mut self, stmt: S, params: I) -> BoxFuture<'a, ()>
     where
-        S: StatementLike + 'a,
+        S: SqlStatement + 'a,
         I: IntoIterator<Item = P> + Send + 'a,
         I::IntoIter: Send,
         P: Into<mysql_async::Params> + Send,
     {
-        self.conn.exec_batch(stmt, params)
+        instrumented::exec_batch(&mut self.conn, &self.hooks, self.utc_offset, stmt, params)
     }
 
     fn last_insert_id(&self) -> Option<u64> {
@@ -540,4 +913,308 @@ impl QueryableConn for DbTransaction<'_> {
     fn affected_rows(&self) -> u64 {
         self.conn.affected_rows()
     }
+
+    fn query_hooks(&self) -> &QueryHooks {
+        &self.hooks
+    }
+
+    fn session_utc_offset(&self) -> Option<i32> {
+        self.utc_offset
+    }
+
+    fn begin_atomic<'a>(&'a mut self) -> BoxFuture<'a, Option<DbTransaction<'a>>> {
+        Box::pin(async { Ok(None) })
+    }
+}
+
+/// Wraps every driver call with the query hooks and, with the `tracing` feature, a span.
+mod instrumented {
+    use std::{borrow::Cow, future::Future, time::Instant};
+
+    use mysql_async::{
+        prelude::{AsQuery, FromRow, Queryable},
+        BinaryProtocol, Params, QueryResult, TextProtocol,
+    };
+
+    use super::{BoxFuture, DbError, OrmError, QueryEvent, QueryHooks, SqlStatement};
+
+    pub(super) trait Session: Queryable {
+        fn affected_rows(&self) -> u64;
+    }
+
+    impl Session for mysql_async::Conn {
+        fn affected_rows(&self) -> u64 {
+            mysql_async::Conn::affected_rows(self)
+        }
+    }
+
+    impl Session for mysql_async::Transaction<'_> {
+        fn affected_rows(&self) -> u64 {
+            mysql_async::Conn::affected_rows(self)
+        }
+    }
+
+    struct Call {
+        method: &'static str,
+        sql: String,
+        params: usize,
+        start: Instant,
+        hooks: QueryHooks,
+        utc_offset: Option<i32>,
+        #[cfg(feature = "tracing")]
+        span: tracing::Span,
+    }
+
+    impl Call {
+        fn new<'s>(method: &'static str, hooks: &QueryHooks, utc_offset: Option<i32>, sql: impl FnOnce() -> Cow<'s, str>, params: usize) -> Self {
+            let sql = if cfg!(feature = "tracing") || !hooks.is_empty() {
+                sql().into_owned()
+            } else {
+                String::new()
+            };
+            Call {
+                method,
+                #[cfg(feature = "tracing")]
+                span: tracing::debug_span!(
+                    "mysql_async_orm::query",
+                    method,
+                    sql = %sql,
+                    params,
+                    duration_us = tracing::field::Empty,
+                    affected_rows = tracing::field::Empty,
+                    error = tracing::field::Empty,
+                ),
+                sql,
+                params,
+                start: Instant::now(),
+                hooks: hooks.clone(),
+                utc_offset,
+            }
+        }
+
+        fn run<'a, T, F>(self, call: F) -> BoxFuture<'a, T>
+        where
+            F: Future<Output = (Result<T, mysql_async::Error>, Option<u64>)> + Send + 'a,
+        {
+            #[cfg(feature = "tracing")]
+            let span = self.span.clone();
+            let utc_offset = self.utc_offset;
+            let future = async move {
+                let (result, affected_rows) = call.await;
+                self.finish(result, affected_rows)
+            };
+            #[cfg(feature = "tracing")]
+            let future = tracing::Instrument::instrument(future, span);
+            // The driver decodes the rows while polled, so `DBTimestamp` sees the session offset.
+            let mut future = Box::pin(future);
+            Box::pin(std::future::poll_fn(move |cx| {
+                crate::db_date::with_session_utc_offset(utc_offset, || future.as_mut().poll(cx))
+            }))
+        }
+
+        fn finish<T>(self, result: Result<T, mysql_async::Error>, affected_rows: Option<u64>) -> Result<T, DbError> {
+            let result = result.map_err(OrmError::from);
+            let event = QueryEvent {
+                method: self.method,
+                sql: &self.sql,
+                params: self.params,
+                duration: self.start.elapsed(),
+                affected_rows: affected_rows.filter(|_| result.is_ok() && !super::returns_rows(&self.sql)),
+                error: result.as_ref().err(),
+            };
+            #[cfg(feature = "tracing")]
+            {
+                self.span.record("duration_us", event.duration.as_micros() as u64);
+                if let Some(affected_rows) = event.affected_rows {
+                    self.span.record("affected_rows", affected_rows);
+                }
+                if let Some(error) = event.error {
+                    self.span.record("error", tracing::field::display(error));
+                    tracing::error!(parent: &self.span, %error, "query failed");
+                }
+            }
+            self.hooks.on_query(&event);
+            result
+        }
+    }
+
+    fn query_text<Q: AsQuery>(query: &Q) -> Cow<'_, str> {
+        match query.as_query() {
+            Cow::Borrowed(bytes) => String::from_utf8_lossy(bytes),
+            Cow::Owned(bytes) => Cow::Owned(String::from_utf8_lossy(&bytes).into_owned()),
+        }
+    }
+
+    fn params_len(params: &Params) -> usize {
+        match params {
+            Params::Empty => 0,
+            Params::Named(params) => params.len(),
+            Params::Positional(params) => params.len(),
+        }
+    }
+
+    pub(super) fn query<'a, C, Q, R>(conn: &'a mut C, hooks: &QueryHooks, utc_offset: Option<i32>, query: Q) -> BoxFuture<'a, Vec<R>>
+    where
+        C: Session,
+        Q: AsQuery + Send + Sync + 'a,
+        R: FromRow + Send + 'static,
+    {
+        Call::new("query", hooks, utc_offset, || query_text(&query), 0).run(async move {
+            let result = conn.query(query).await;
+            (result, Some(conn.affected_rows()))
+        })
+    }
+
+    pub(super) fn query_first<'a, C, Q, R>(conn: &'a mut C, hooks: &QueryHooks, utc_offset: Option<i32>, query: Q) -> BoxFuture<'a, Option<R>>
+    where
+        C: Session,
+        Q: AsQuery + Send + Sync + 'a,
+        R: FromRow + Send + 'static,
+    {
+        Call::new("query_first", hooks, utc_offset, || query_text(&query), 0).run(async move {
+            let result = conn.query_first(query).await;
+            (result, Some(conn.affected_rows()))
+        })
+    }
+
+    pub(super) fn query_iter<'a, C, Q>(conn: &'a mut C, hooks: &QueryHooks, utc_offset: Option<i32>, query: Q) -> BoxFuture<'a, QueryResult<'a, 'static, TextProtocol>>
+    where
+        C: Session,
+        Q: AsQuery + Send + Sync + 'a,
+    {
+        Call::new("query_iter", hooks, utc_offset, || query_text(&query), 0).run(async move { (conn.query_iter(query).await, None) })
+    }
+
+    pub(super) fn query_drop<'a, C, Q>(conn: &'a mut C, hooks: &QueryHooks, utc_offset: Option<i32>, query: Q) -> BoxFuture<'a, ()>
+    where
+        C: Session,
+        Q: AsQuery + Send + Sync + 'a,
+    {
+        Call::new("query_drop", hooks, utc_offset, || query_text(&query), 0).run(async move {
+            let result = conn.query_drop(query).await;
+            (result, Some(conn.affected_rows()))
+        })
+    }
+
+    pub(super) fn exec<'a, C, S, P, R>(conn: &'a mut C, hooks: &QueryHooks, utc_offset: Option<i32>, stmt: S, params: P) -> BoxFuture<'a, Vec<R>>
+    where
+        C: Session,
+        S: SqlStatement + 'a,
+        P: Into<Params> + Send + 'a,
+        R: FromRow + Send + 'static,
+    {
+        let params = params.into();
+        Call::new("exec", hooks, utc_offset, || stmt.sql_text(), params_len(&params)).run(async move {
+            let result = conn.exec(stmt, params).await;
+            (result, Some(conn.affected_rows()))
+        })
+    }
+
+    pub(super) fn exec_map<'a: 'b, 'b, C, T, S, P, U, F>(conn: &'a mut C, hooks: &QueryHooks, utc_offset: Option<i32>, stmt: S, params: P, f: F) -> BoxFuture<'b, Vec<U>>
+    where
+        C: Session,
+        S: SqlStatement + 'b,
+        P: Into<Params> + Send + 'b,
+        T: FromRow + Send + 'static,
+        F: FnMut(T) -> U + Send + 'a,
+        U: Send + 'a,
+    {
+        let params = params.into();
+        Call::new("exec_map", hooks, utc_offset, || stmt.sql_text(), params_len(&params)).run(async move {
+            let result = conn.exec_map(stmt, params, f).await;
+            (result, Some(conn.affected_rows()))
+        })
+    }
+
+    pub(super) fn exec_iter<'a: 's, 's, C, S, P>(
+        conn: &'a mut C,
+        hooks: &QueryHooks,
+        utc_offset: Option<i32>,
+        stmt: S,
+        params: P,
+    ) -> BoxFuture<'s, QueryResult<'a, 'static, BinaryProtocol>>
+    where
+        C: Session,
+        S: SqlStatement + 'a,
+        P: Into<Params>,
+    {
+        let params = params.into();
+        Call::new("exec_iter", hooks, utc_offset, || stmt.sql_text(), params_len(&params)).run(async move { (conn.exec_iter(stmt, params).await, None) })
+    }
+
+    pub(super) fn exec_first<'a, C, S, P, R>(conn: &'a mut C, hooks: &QueryHooks, utc_offset: Option<i32>, stmt: S, params: P) -> BoxFuture<'a, Option<R>>
+    where
+        C: Session,
+        S: SqlStatement + 'a,
+        P: Into<Params> + Send + 'a,
+        R: FromRow + Send + 'static,
+    {
+        let params = params.into();
+        Call::new("exec_first", hooks, utc_offset, || stmt.sql_text(), params_len(&params)).run(async move {
+            let result = conn.exec_first(stmt, params).await;
+            (result, Some(conn.affected_rows()))
+        })
+    }
+
+    pub(super) fn exec_drop<'a, C, S, P>(conn: &'a mut C, hooks: &QueryHooks, utc_offset: Option<i32>, stmt: S, params: P) -> BoxFuture<'a, ()>
+    where
+        C: Session,
+        S: SqlStatement + 'a,
+        P: Into<Params> + Send + 'a,
+    {
+        let params = params.into();
+        Call::new("exec_drop", hooks, utc_offset, || stmt.sql_text(), params_len(&params)).run(async move {
+            let result = conn.exec_drop(stmt, params).await;
+            (result, Some(conn.affected_rows()))
+        })
+    }
+
+    /// Reports the total number of parameters over all the executions of the batch.
+    pub(super) fn exec_batch<'a, C, S, P, I>(conn: &'a mut C, hooks: &QueryHooks, utc_offset: Option<i32>, stmt: S, params: I) -> BoxFuture<'a, ()>
+    where
+        C: Session,
+        S: SqlStatement + 'a,
+        I: IntoIterator<Item = P> + Send + 'a,
+        I::IntoIter: Send,
+        P: Into<Params> + Send,
+    {
+        let params: Vec<Params> = params.into_iter().map(Into::into).collect();
+        let count = params.iter().map(params_len).sum();
+        Call::new("exec_batch", hooks, utc_offset, || stmt.sql_text(), count).run(async move {
+            let result = conn.exec_batch(stmt, params).await;
+            (result, Some(conn.affected_rows()))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn time_zone_is_a_setup_command() {
+        let opts = mysql_async::OptsBuilder::default()
+            .init(vec!["SET NAMES utf8mb4"])
+            .setup(vec!["SET sql_mode = 'TRADITIONAL'"]);
+        let opts = time_zone_opts(opts.into(), -150);
+        assert_eq!(opts.init(), ["SET NAMES utf8mb4"]);
+        assert_eq!(opts.setup(), ["SET sql_mode = 'TRADITIONAL'", "SET time_zone = '-02:30'"]);
+    }
+
+    #[test]
+    fn rows_are_returned_by_reads() {
+        assert!(returns_rows("SELECT 1"));
+        assert!(returns_rows("  (select id FROM a) UNION (SELECT id FROM b)"));
+        assert!(returns_rows("WITH x AS (SELECT 1) SELECT * FROM x"));
+        assert!(returns_rows("show tables"));
+        assert!(!returns_rows("UPDATE a SET b = 1"));
+        assert!(!returns_rows("INSERT INTO a SELECT * FROM b"));
+        assert!(!returns_rows("SELECTED"));
+    }
+
+    #[test]
+    fn with_time_zone_rejects_invalid_offsets() {
+        assert!(matches!(DbConnectionPool::with_time_zone("mysql://localhost/db", 15 * 60), Err(OrmError::Date(_))));
+        assert!(matches!(DbConnectionPool::with_time_zone("not a url", 0), Err(OrmError::Driver(_))));
+    }
 }
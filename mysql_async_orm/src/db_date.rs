@@ -1,79 +1,1207 @@
-use mysql_async::prelude::FromValue;
+use std::{
+	cell::Cell,
+	fmt,
+	ops::{Add, Neg, Sub},
+	str::FromStr,
+	time::{Duration, SystemTime, UNIX_EPOCH},
+};
 
-#[derive(Debug, Clone)]
-pub struct DBTime(i16, u8, u8);
-#[derive(Debug, Clone)]
-pub struct DBDate(i32, u8, u8);
-#[derive(Debug, Clone)]
-pub struct DBDateTime(i32, u8, u8, u8, u8, u8);
+use mysql_async::{prelude::FromValue, FromValueError, Value};
 
-impl FromValue for DBTime {
-	type Intermediate = DBTime;
+const MICROS_PER_SECOND: i64 = 1_000_000;
+const MICROS_PER_DAY: i64 = 86_400 * MICROS_PER_SECOND;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DateError {
+	InvalidDate { year: u16, month: u8, day: u8 },
+	InvalidTime { hours: u32, minutes: u8, seconds: u8, micros: u32 },
+	/// The result of an arithmetic operation or conversion is outside the MySQL range.
+	OutOfRange,
+	Parse(String),
+}
+
+impl fmt::Display for DateError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			DateError::InvalidDate { year, month, day } => write!(f, "Invalid date {:04}-{:02}-{:02}", year, month, day),
+			DateError::InvalidTime { hours, minutes, seconds, micros } => {
+				write!(f, "Invalid time {:02}:{:02}:{:02}.{:06}", hours, minutes, seconds, micros)
+			}
+			DateError::OutOfRange => write!(f, "Value out of the MySQL range"),
+			DateError::Parse(s) => write!(f, "Cannot parse {:?}", s),
+		}
+	}
 }
 
-impl TryFrom<mysql_async::Value> for DBTime {
-	type Error = mysql_async::FromValueError;
-	
-	fn try_from(value: mysql_async::Value) -> Result<Self, Self::Error> {
-		if let mysql_async::Value::Time(is_negative, d, h, m, s, _) = value {
-			let h = if is_negative { -(h as i16) } else { h as i16 };
-			Ok(DBTime(d as i16 * 24 + h, m, s))
+impl std::error::Error for DateError {}
+
+/// A MySQL `DATE`, from 0000-01-01 to 9999-12-31.
+///
+/// MySQL zero dates are represented by `DBDate::ZERO` (`0000-00-00`), which sorts before any
+/// other date and is only produced by decoding or parsing it explicitly; constructors and
+/// arithmetic never return it, and arithmetic on it returns `None`. Dates with only some
+/// zero parts (`2024-00-00`) are rejected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct DBDate {
+	year: u16,
+	month: u8,
+	day: u8,
+}
+
+impl DBDate {
+	pub const ZERO: DBDate = DBDate { year: 0, month: 0, day: 0 };
+	pub const MIN: DBDate = DBDate { year: 0, month: 1, day: 1 };
+	pub const MAX: DBDate = DBDate { year: 9999, month: 12, day: 31 };
+
+	pub fn new(year: u16, month: u8, day: u8) -> Result<Self, DateError> {
+		if year <= 9999 && (1..=12).contains(&month) && day >= 1 && day <= days_in_month(year, month) {
+			Ok(DBDate { year, month, day })
 		} else {
-			Err(mysql_async::FromValueError(value))
+			Err(DateError::InvalidDate { year, month, day })
 		}
 	}
+
+	pub fn year(&self) -> u16 {
+		self.year
+	}
+
+	pub fn month(&self) -> u8 {
+		self.month
+	}
+
+	pub fn day(&self) -> u8 {
+		self.day
+	}
+
+	pub fn is_zero(&self) -> bool {
+		*self == DBDate::ZERO
+	}
+
+	pub fn and_hms(self, hour: u8, minute: u8, second: u8) -> Result<DBDateTime, DateError> {
+		DBDateTime::new(self, hour, minute, second, 0)
+	}
+
+	pub fn and_hms_micro(self, hour: u8, minute: u8, second: u8, micro: u32) -> Result<DBDateTime, DateError> {
+		DBDateTime::new(self, hour, minute, second, micro)
+	}
+
+	pub fn checked_add_days(self, days: i64) -> Option<Self> {
+		DBDate::from_day_number(self.day_number()?.checked_add(days)?)
+	}
+
+	pub fn checked_sub_days(self, days: i64) -> Option<Self> {
+		DBDate::from_day_number(self.day_number()?.checked_sub(days)?)
+	}
+
+	/// Days from `earlier` to `self`, negative if `earlier` is later.
+	pub fn days_since(self, earlier: DBDate) -> Option<i64> {
+		Some(self.day_number()? - earlier.day_number()?)
+	}
+
+	/// Days since 1970-01-01.
+	fn day_number(self) -> Option<i64> {
+		(!self.is_zero()).then(|| days_from_civil(self.year as i64, self.month as i64, self.day as i64))
+	}
+
+	fn from_day_number(days: i64) -> Option<Self> {
+		let (year, month, day) = civil_from_days(days);
+		let year = u16::try_from(year).ok()?;
+		DBDate::new(year, month, day).ok()
+	}
+}
+
+impl fmt::Display for DBDate {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+	}
 }
 
-impl From<DBTime> for mysql_async::Value {
-	fn from(v: DBTime) -> Self {
-		let DBTime(h, m, s) = v;
-		let is_negative = h.is_negative();
-		let h = h.abs() as u32;
-		mysql_async::Value::Time(is_negative, h, m, s, 0, 0)
+/// `YYYY-MM-DD`, or `0000-00-00` for `DBDate::ZERO`.
+impl FromStr for DBDate {
+	type Err = DateError;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let parse_error = || DateError::Parse(s.to_string());
+		let mut parts = s.split('-');
+		let (Some(year), Some(month), Some(day), None) = (parts.next(), parts.next(), parts.next(), parts.next()) else {
+			return Err(parse_error());
+		};
+		if year.len() != 4 || month.len() != 2 || day.len() != 2 {
+			return Err(parse_error());
+		}
+		let year = parse_digits(year).ok_or_else(parse_error)?;
+		let month = parse_digits(month).ok_or_else(parse_error)?;
+		let day = parse_digits(day).ok_or_else(parse_error)?;
+		date_from_parts(year, month, day)
 	}
 }
 
-impl TryFrom<mysql_async::Value> for DBDate {
-	type Error = mysql_async::FromValueError;
-	
-	fn try_from(value: mysql_async::Value) -> Result<Self, Self::Error> {
-		if let mysql_async::Value::Date(y, m, d, _, _, _, _) = value {
-			Ok(DBDate(y as i32, m, d))
+/// A MySQL `TIME`: a signed duration from -838:59:59 to 838:59:59, with microseconds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct DBTime {
+	micros: i64,
+}
+
+impl DBTime {
+	pub const ZERO: DBTime = DBTime { micros: 0 };
+	pub const MAX: DBTime = DBTime { micros: (838 * 3600 + 59 * 60 + 59) * MICROS_PER_SECOND };
+	pub const MIN: DBTime = DBTime { micros: -DBTime::MAX.micros };
+
+	/// A non-negative time; negate it for negative ones.
+	pub fn new(hours: u16, minutes: u8, seconds: u8, micros: u32) -> Result<Self, DateError> {
+		let invalid = || DateError::InvalidTime { hours: hours as u32, minutes, seconds, micros };
+		if minutes >= 60 || seconds >= 60 || micros >= 1_000_000 {
+			return Err(invalid());
+		}
+		let total = ((hours as i64 * 60 + minutes as i64) * 60 + seconds as i64) * MICROS_PER_SECOND + micros as i64;
+		DBTime::from_micros(total).map_err(|_| invalid())
+	}
+
+	pub fn from_micros(micros: i64) -> Result<Self, DateError> {
+		if (DBTime::MIN.micros..=DBTime::MAX.micros).contains(&micros) {
+			Ok(DBTime { micros })
 		} else {
-			Err(mysql_async::FromValueError(value))
+			Err(DateError::OutOfRange)
 		}
 	}
+
+	pub fn as_micros(&self) -> i64 {
+		self.micros
+	}
+
+	pub fn is_negative(&self) -> bool {
+		self.micros < 0
+	}
+
+	/// Hours of the absolute value, up to 838.
+	pub fn hours(&self) -> u16 {
+		(self.micros.unsigned_abs() / 3_600_000_000) as u16
+	}
+
+	pub fn minutes(&self) -> u8 {
+		(self.micros.unsigned_abs() / 60_000_000 % 60) as u8
+	}
+
+	pub fn seconds(&self) -> u8 {
+		(self.micros.unsigned_abs() / 1_000_000 % 60) as u8
+	}
+
+	/// Fractional part in microseconds.
+	pub fn micros(&self) -> u32 {
+		(self.micros.unsigned_abs() % 1_000_000) as u32
+	}
+
+	pub fn abs_duration(&self) -> Duration {
+		Duration::from_micros(self.micros.unsigned_abs())
+	}
+
+	pub fn checked_add(self, duration: Duration) -> Option<Self> {
+		DBTime::from_micros(self.micros.checked_add(i64::try_from(duration.as_micros()).ok()?)?).ok()
+	}
+
+	pub fn checked_sub(self, duration: Duration) -> Option<Self> {
+		DBTime::from_micros(self.micros.checked_sub(i64::try_from(duration.as_micros()).ok()?)?).ok()
+	}
 }
 
-impl From<DBDate> for mysql_async::Value {
-	fn from(v: DBDate) -> Self {
-		let DBDate(y, m, d) = v;
-		let y = if y.is_negative() { 0 } else { y };
-		mysql_async::Value::Date(y as u16, m , d, 0, 0, 0, 0)
+impl TryFrom<Duration> for DBTime {
+	type Error = DateError;
+
+	fn try_from(duration: Duration) -> Result<Self, Self::Error> {
+		DBTime::from_micros(i64::try_from(duration.as_micros()).map_err(|_| DateError::OutOfRange)?)
 	}
 }
 
+impl Neg for DBTime {
+	type Output = DBTime;
+
+	fn neg(self) -> DBTime {
+		DBTime { micros: -self.micros }
+	}
+}
+
+/// Panics if the result is outside the `TIME` range, see `checked_add`.
+impl Add<Duration> for DBTime {
+	type Output = DBTime;
+
+	fn add(self, duration: Duration) -> DBTime {
+		self.checked_add(duration).expect("overflow when adding duration to DBTime")
+	}
+}
+
+/// Panics if the result is outside the `TIME` range, see `checked_sub`.
+impl Sub<Duration> for DBTime {
+	type Output = DBTime;
+
+	fn sub(self, duration: Duration) -> DBTime {
+		self.checked_sub(duration).expect("overflow when subtracting duration from DBTime")
+	}
+}
+
+impl fmt::Display for DBTime {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		let sign = if self.is_negative() { "-" } else { "" };
+		write!(f, "{}{:02}:{:02}:{:02}", sign, self.hours(), self.minutes(), self.seconds())?;
+		write_micros(f, self.micros())
+	}
+}
+
+/// `[-]HH:MM:SS[.ffffff]`, with up to three hour digits.
+impl FromStr for DBTime {
+	type Err = DateError;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let (negative, hours, minutes, seconds, micros) = parse_time(s).ok_or_else(|| DateError::Parse(s.to_string()))?;
+		let hours = u16::try_from(hours).map_err(|_| DateError::OutOfRange)?;
+		let time = DBTime::new(hours, minutes, seconds, micros)?;
+		Ok(if negative { -time } else { time })
+	}
+}
+
+/// A MySQL `DATETIME` with microseconds. `DBDateTime::ZERO` follows the `DBDate::ZERO` policy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct DBDateTime {
+	date: DBDate,
+	hour: u8,
+	minute: u8,
+	second: u8,
+	micro: u32,
+}
+
+impl DBDateTime {
+	pub const ZERO: DBDateTime = DBDateTime { date: DBDate::ZERO, hour: 0, minute: 0, second: 0, micro: 0 };
+	pub const MIN: DBDateTime = DBDateTime { date: DBDate::MIN, hour: 0, minute: 0, second: 0, micro: 0 };
+	pub const MAX: DBDateTime = DBDateTime { date: DBDate::MAX, hour: 23, minute: 59, second: 59, micro: 999_999 };
+
+	pub fn new(date: DBDate, hour: u8, minute: u8, second: u8, micro: u32) -> Result<Self, DateError> {
+		if date.is_zero() {
+			return Err(DateError::InvalidDate { year: 0, month: 0, day: 0 });
+		}
+		if hour >= 24 || minute >= 60 || second >= 60 || micro >= 1_000_000 {
+			return Err(DateError::InvalidTime { hours: hour as u32, minutes: minute, seconds: second, micros: micro });
+		}
+		Ok(DBDateTime { date, hour, minute, second, micro })
+	}
+
+	pub fn from_ymd_hms(year: u16, month: u8, day: u8, hour: u8, minute: u8, second: u8) -> Result<Self, DateError> {
+		DBDateTime::new(DBDate::new(year, month, day)?, hour, minute, second, 0)
+	}
+
+	pub fn date(&self) -> DBDate {
+		self.date
+	}
+
+	pub fn year(&self) -> u16 {
+		self.date.year
+	}
+
+	pub fn month(&self) -> u8 {
+		self.date.month
+	}
+
+	pub fn day(&self) -> u8 {
+		self.date.day
+	}
+
+	pub fn hour(&self) -> u8 {
+		self.hour
+	}
+
+	pub fn minute(&self) -> u8 {
+		self.minute
+	}
+
+	pub fn second(&self) -> u8 {
+		self.second
+	}
+
+	pub fn micro(&self) -> u32 {
+		self.micro
+	}
+
+	pub fn is_zero(&self) -> bool {
+		*self == DBDateTime::ZERO
+	}
+
+	/// The time of day as a `DBTime`.
+	pub fn time(&self) -> DBTime {
+		DBTime { micros: self.micros_of_day() }
+	}
+
+	pub fn checked_add(self, duration: Duration) -> Option<Self> {
+		DBDateTime::from_micros(self.as_micros()?.checked_add(i64::try_from(duration.as_micros()).ok()?)?)
+	}
+
+	pub fn checked_sub(self, duration: Duration) -> Option<Self> {
+		DBDateTime::from_micros(self.as_micros()?.checked_sub(i64::try_from(duration.as_micros()).ok()?)?)
+	}
+
+	/// `None` if `earlier` is after `self` or either is zero.
+	pub fn duration_since(self, earlier: DBDateTime) -> Option<Duration> {
+		let micros = self.as_micros()?.checked_sub(earlier.as_micros()?)?;
+		Some(Duration::from_micros(u64::try_from(micros).ok()?))
+	}
+
+	fn micros_of_day(&self) -> i64 {
+		((self.hour as i64 * 60 + self.minute as i64) * 60 + self.second as i64) * MICROS_PER_SECOND + self.micro as i64
+	}
+
+	/// Microseconds since 1970-01-01T00:00:00.
+	fn as_micros(self) -> Option<i64> {
+		Some(self.date.day_number()? * MICROS_PER_DAY + self.micros_of_day())
+	}
+
+	fn from_micros(micros: i64) -> Option<Self> {
+		let date = DBDate::from_day_number(micros.div_euclid(MICROS_PER_DAY))?;
+		let micros = micros.rem_euclid(MICROS_PER_DAY);
+		let seconds = micros / MICROS_PER_SECOND;
+		Some(DBDateTime {
+			date,
+			hour: (seconds / 3600) as u8,
+			minute: (seconds / 60 % 60) as u8,
+			second: (seconds % 60) as u8,
+			micro: (micros % MICROS_PER_SECOND) as u32,
+		})
+	}
+}
+
+/// Panics if the result is outside the `DATETIME` range or `self` is zero, see `checked_add`.
+impl Add<Duration> for DBDateTime {
+	type Output = DBDateTime;
+
+	fn add(self, duration: Duration) -> DBDateTime {
+		self.checked_add(duration).expect("overflow when adding duration to DBDateTime")
+	}
+}
+
+/// Panics if the result is outside the `DATETIME` range or `self` is zero, see `checked_sub`.
+impl Sub<Duration> for DBDateTime {
+	type Output = DBDateTime;
+
+	fn sub(self, duration: Duration) -> DBDateTime {
+		self.checked_sub(duration).expect("overflow when subtracting duration from DBDateTime")
+	}
+}
+
+impl fmt::Display for DBDateTime {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "{}T{:02}:{:02}:{:02}", self.date, self.hour, self.minute, self.second)?;
+		write_micros(f, self.micro)
+	}
+}
+
+/// `YYYY-MM-DDTHH:MM:SS[.ffffff]`; a space is accepted instead of the `T`, as MySQL prints it.
+impl FromStr for DBDateTime {
+	type Err = DateError;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let (date, time) = s.split_once(['T', ' ']).ok_or_else(|| DateError::Parse(s.to_string()))?;
+		let date: DBDate = date.parse()?;
+		let (negative, hour, minute, second, micro) = parse_time(time).ok_or_else(|| DateError::Parse(s.to_string()))?;
+		if negative {
+			return Err(DateError::Parse(s.to_string()));
+		}
+		let hour = u8::try_from(hour).map_err(|_| DateError::InvalidTime { hours: hour, minutes: minute, seconds: second, micros: micro })?;
+		if date.is_zero() && (hour, minute, second, micro) == (0, 0, 0, 0) {
+			return Ok(DBDateTime::ZERO);
+		}
+		DBDateTime::new(date, hour, minute, second, micro)
+	}
+}
+
+thread_local! {
+	static SESSION_UTC_OFFSET: Cell<Option<i32>> = const { Cell::new(None) };
+	static MISSING_TIME_ZONE: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Offset from UTC of the session `time_zone`, in minutes, of the connection whose rows are
+/// being decoded. `None` unless the connection was pinned with `DbConnectionPool::with_time_zone`
+/// or `DbConnection::set_time_zone`.
+pub fn session_utc_offset() -> Option<i32> {
+	SESSION_UTC_OFFSET.get()
+}
+
+/// Runs `f` with `utc_offset` as the `session_utc_offset`, e.g. to decode rows read directly
+/// with `mysql_async`.
+pub fn with_session_utc_offset<T>(utc_offset: Option<i32>, f: impl FnOnce() -> T) -> T {
+	struct Restore(Option<i32>);
+	impl Drop for Restore {
+		fn drop(&mut self) {
+			SESSION_UTC_OFFSET.set(self.0);
+		}
+	}
+	let _restore = Restore(SESSION_UTC_OFFSET.replace(utc_offset));
+	f()
+}
+
+/// Whether a `DBTimestamp` failed to decode for lack of a `session_utc_offset` since the last
+/// call, so the ORM can report `DbError::MissingTimeZone` rather than a row decode error.
+pub(crate) fn take_missing_time_zone() -> bool {
+	MISSING_TIME_ZONE.replace(false)
+}
+
+/// MySQL accepts `time_zone` offsets from -13:59 to +14:00.
+pub(crate) fn check_utc_offset(minutes: i32) -> Result<(), DateError> {
+	match (-839..=840).contains(&minutes) {
+		true => Ok(()),
+		false => Err(DateError::OutOfRange),
+	}
+}
+
+/// `+HH:MM`, the format of MySQL `time_zone` offsets.
+pub(crate) fn format_utc_offset(minutes: i32) -> String {
+	let sign = if minutes < 0 { '-' } else { '+' };
+	format!("{}{:02}:{:02}", sign, minutes.abs() / 60, minutes.abs() % 60)
+}
+
+/// A MySQL `TIMESTAMP`: an instant from 1970-01-01T00:00:01Z to 2038-01-19T03:14:07.999999Z,
+/// stored in UTC.
+///
+/// MySQL sends `TIMESTAMP` values in the session `time_zone`, which is converted with the
+/// `session_utc_offset` of the connection: loading them fails with `DbError::MissingTimeZone`
+/// unless the time zone was pinned with `DbConnectionPool::with_time_zone` or
+/// `DbConnection::set_time_zone`. Values are written
+/// in UTC with an explicit `+00:00` offset, which needs MySQL 8.0.19 or later. Named time zones
+/// with daylight saving time cannot be converted, and zero timestamps fail to decode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct DBTimestamp {
+	micros: i64,
+}
+
+impl DBTimestamp {
+	pub const MIN: DBTimestamp = DBTimestamp { micros: MICROS_PER_SECOND };
+	pub const MAX: DBTimestamp = DBTimestamp { micros: i32::MAX as i64 * MICROS_PER_SECOND + 999_999 };
+
+	/// Microseconds since 1970-01-01T00:00:00Z.
+	pub fn from_unix_micros(micros: i64) -> Result<Self, DateError> {
+		if (DBTimestamp::MIN.micros..=DBTimestamp::MAX.micros).contains(&micros) {
+			Ok(DBTimestamp { micros })
+		} else {
+			Err(DateError::OutOfRange)
+		}
+	}
+
+	pub fn from_unix_seconds(seconds: i64) -> Result<Self, DateError> {
+		DBTimestamp::from_unix_micros(seconds.checked_mul(MICROS_PER_SECOND).ok_or(DateError::OutOfRange)?)
+	}
+
+	/// The current instant, truncated to microseconds.
+	pub fn now() -> Result<Self, DateError> {
+		let since_epoch = SystemTime::now().duration_since(UNIX_EPOCH).map_err(|_| DateError::OutOfRange)?;
+		DBTimestamp::from_unix_micros(i64::try_from(since_epoch.as_micros()).map_err(|_| DateError::OutOfRange)?)
+	}
+
+	pub fn unix_micros(&self) -> i64 {
+		self.micros
+	}
+
+	pub fn unix_seconds(&self) -> i64 {
+		self.micros.div_euclid(MICROS_PER_SECOND)
+	}
+
+	pub fn from_utc(datetime: DBDateTime) -> Result<Self, DateError> {
+		DBTimestamp::from_local(datetime, 0)
+	}
+
+	/// `datetime` read on a clock `utc_offset` minutes ahead of UTC, within ±14 hours.
+	pub fn from_local(datetime: DBDateTime, utc_offset: i32) -> Result<Self, DateError> {
+		check_utc_offset(utc_offset)?;
+		let micros = datetime.as_micros().ok_or(DateError::InvalidDate { year: 0, month: 0, day: 0 })?;
+		DBTimestamp::from_unix_micros(micros - utc_offset as i64 * 60 * MICROS_PER_SECOND)
+	}
+
+	pub fn to_utc(&self) -> DBDateTime {
+		self.shifted(0)
+	}
+
+	/// The date and time on a clock `utc_offset` minutes ahead of UTC, within ±14 hours.
+	pub fn to_local(&self, utc_offset: i32) -> Result<DBDateTime, DateError> {
+		check_utc_offset(utc_offset)?;
+		Ok(self.shifted(utc_offset))
+	}
+
+	/// A `TIMESTAMP` shifted by at most a day is always within the `DATETIME` range.
+	fn shifted(&self, utc_offset: i32) -> DBDateTime {
+		DBDateTime::from_micros(self.micros + utc_offset as i64 * 60 * MICROS_PER_SECOND).expect("TIMESTAMP range within DATETIME range")
+	}
+
+	pub fn checked_add(self, duration: Duration) -> Option<Self> {
+		DBTimestamp::from_unix_micros(self.micros.checked_add(i64::try_from(duration.as_micros()).ok()?)?).ok()
+	}
+
+	pub fn checked_sub(self, duration: Duration) -> Option<Self> {
+		DBTimestamp::from_unix_micros(self.micros.checked_sub(i64::try_from(duration.as_micros()).ok()?)?).ok()
+	}
+
+	/// `None` if `earlier` is after `self`.
+	pub fn duration_since(self, earlier: DBTimestamp) -> Option<Duration> {
+		Some(Duration::from_micros(u64::try_from(self.micros - earlier.micros).ok()?))
+	}
+}
+
+/// Panics if the result is outside the `TIMESTAMP` range, see `checked_add`.
+impl Add<Duration> for DBTimestamp {
+	type Output = DBTimestamp;
+
+	fn add(self, duration: Duration) -> DBTimestamp {
+		self.checked_add(duration).expect("overflow when adding duration to DBTimestamp")
+	}
+}
+
+/// Panics if the result is outside the `TIMESTAMP` range, see `checked_sub`.
+impl Sub<Duration> for DBTimestamp {
+	type Output = DBTimestamp;
+
+	fn sub(self, duration: Duration) -> DBTimestamp {
+		self.checked_sub(duration).expect("overflow when subtracting duration from DBTimestamp")
+	}
+}
+
+/// RFC 3339 in UTC, `YYYY-MM-DDTHH:MM:SS[.ffffff]Z`.
+impl fmt::Display for DBTimestamp {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "{}Z", self.to_utc())
+	}
+}
+
+/// RFC 3339, `YYYY-MM-DDTHH:MM:SS[.ffffff]` followed by `Z` or a `±HH:MM` offset.
+impl FromStr for DBTimestamp {
+	type Err = DateError;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let parse_error = || DateError::Parse(s.to_string());
+		let (datetime, utc_offset) = match s.strip_suffix(['Z', 'z']) {
+			Some(datetime) => (datetime, 0),
+			None => {
+				let split = s.len().checked_sub(6).filter(|&i| s.is_char_boundary(i)).ok_or_else(parse_error)?;
+				let (datetime, offset) = s.split_at(split);
+				let negative = match offset.as_bytes()[0] {
+					b'+' => false,
+					b'-' => true,
+					_ => return Err(parse_error()),
+				};
+				let (hours, minutes) = offset[1..].split_once(':').ok_or_else(parse_error)?;
+				let hours: i32 = parse_digits(hours).ok_or_else(parse_error)?;
+				let minutes: i32 = parse_digits(minutes).filter(|&minutes| minutes < 60).ok_or_else(parse_error)?;
+				let utc_offset = hours * 60 + minutes;
+				(datetime, if negative { -utc_offset } else { utc_offset })
+			}
+		};
+		DBTimestamp::from_local(datetime.parse()?, utc_offset)
+	}
+}
+
+fn days_in_month(year: u16, month: u8) -> u8 {
+	match month {
+		2 if year.is_multiple_of(4) && (!year.is_multiple_of(100) || year.is_multiple_of(400)) => 29,
+		2 => 28,
+		4 | 6 | 9 | 11 => 30,
+		_ => 31,
+	}
+}
+
+fn date_from_parts(year: u16, month: u8, day: u8) -> Result<DBDate, DateError> {
+	if (year, month, day) == (0, 0, 0) {
+		Ok(DBDate::ZERO)
+	} else {
+		DBDate::new(year, month, day)
+	}
+}
+
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+	let year = if month <= 2 { year - 1 } else { year };
+	let era = year.div_euclid(400);
+	let year_of_era = year - era * 400;
+	let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+	let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+	era * 146_097 + day_of_era - 719_468
+}
+
+fn civil_from_days(days: i64) -> (i64, u8, u8) {
+	let days = days + 719_468;
+	let era = days.div_euclid(146_097);
+	let day_of_era = days - era * 146_097;
+	let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+	let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+	let shifted_month = (5 * day_of_year + 2) / 153;
+	let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+	let month = if shifted_month < 10 { shifted_month + 3 } else { shifted_month - 9 };
+	let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+	(year, month as u8, day as u8)
+}
+
+fn parse_digits<T: FromStr>(s: &str) -> Option<T> {
+	if s.is_empty() || !s.bytes().all(|b| b.is_ascii_digit()) {
+		return None;
+	}
+	s.parse().ok()
+}
+
+/// `[-]H+:MM:SS[.f{1,6}]` into (negative, hours, minutes, seconds, micros); ranges are checked by the caller.
+fn parse_time(s: &str) -> Option<(bool, u32, u8, u8, u32)> {
+	let (negative, s) = match s.strip_prefix('-') {
+		Some(s) => (true, s),
+		None => (false, s),
+	};
+	let (s, fraction) = match s.split_once('.') {
+		Some((s, fraction)) if (1..=6).contains(&fraction.len()) => (s, Some(fraction)),
+		Some(_) => return None,
+		None => (s, None),
+	};
+	let mut parts = s.split(':');
+	let (Some(hours), Some(minutes), Some(seconds), None) = (parts.next(), parts.next(), parts.next(), parts.next()) else {
+		return None;
+	};
+	if hours.len() < 2 || minutes.len() != 2 || seconds.len() != 2 {
+		return None;
+	}
+	let micros = match fraction {
+		Some(fraction) => parse_digits::<u32>(fraction)? * 10u32.pow(6 - fraction.len() as u32),
+		None => 0,
+	};
+	Some((negative, parse_digits(hours)?, parse_digits(minutes)?, parse_digits(seconds)?, micros))
+}
+
+fn write_micros(f: &mut fmt::Formatter<'_>, micros: u32) -> fmt::Result {
+	if micros != 0 {
+		write!(f, ".{:06}", micros)?;
+	}
+	Ok(())
+}
+
+/// Values of the text protocol arrive as bytes, those of prepared statements as `Value::Date`/`Value::Time`.
+fn parse_bytes<T: FromStr>(value: Value) -> Result<T, FromValueError> {
+	match &value {
+		Value::Bytes(bytes) => match std::str::from_utf8(bytes).ok().and_then(|s| s.parse().ok()) {
+			Some(res) => Ok(res),
+			None => Err(FromValueError(value)),
+		},
+		_ => Err(FromValueError(value)),
+	}
+}
+
+impl FromValue for DBDate {
+	type Intermediate = DBDate;
+}
+
+impl TryFrom<Value> for DBDate {
+	type Error = FromValueError;
+
+	fn try_from(value: Value) -> Result<Self, Self::Error> {
+		match value {
+			Value::Date(year, month, day, 0, 0, 0, 0) => date_from_parts(year, month, day).map_err(|_| FromValueError(value)),
+			value => parse_bytes(value),
+		}
+	}
+}
+
+impl From<DBDate> for Value {
+	fn from(date: DBDate) -> Self {
+		Value::Date(date.year, date.month, date.day, 0, 0, 0, 0)
+	}
+}
+
+impl FromValue for DBTime {
+	type Intermediate = DBTime;
+}
+
+impl TryFrom<Value> for DBTime {
+	type Error = FromValueError;
+
+	fn try_from(value: Value) -> Result<Self, Self::Error> {
+		match value {
+			Value::Time(negative, days, hours, minutes, seconds, micros) => {
+				let total = (((days as i64 * 24 + hours as i64) * 60 + minutes as i64) * 60 + seconds as i64) * MICROS_PER_SECOND + micros as i64;
+				DBTime::from_micros(if negative { -total } else { total }).map_err(|_| FromValueError(value))
+			}
+			value => parse_bytes(value),
+		}
+	}
+}
+
+impl From<DBTime> for Value {
+	fn from(time: DBTime) -> Self {
+		let hours = time.hours();
+		Value::Time(time.is_negative(), (hours / 24) as u32, (hours % 24) as u8, time.minutes(), time.seconds(), time.micros())
+	}
+}
 
 impl FromValue for DBDateTime {
 	type Intermediate = DBDateTime;
 }
 
-impl TryFrom<mysql_async::Value> for DBDateTime {
-	type Error = mysql_async::FromValueError;
-	
-	fn try_from(value: mysql_async::Value) -> Result<Self, Self::Error> {
-		if let mysql_async::Value::Date(y, m, d, h, min, s, _) = value {
-			Ok(DBDateTime(y as i32, m, d, h, min, s))
-		} else {
-			Err(mysql_async::FromValueError(value))
+impl TryFrom<Value> for DBDateTime {
+	type Error = FromValueError;
+
+	fn try_from(value: Value) -> Result<Self, Self::Error> {
+		match value {
+			Value::Date(0, 0, 0, 0, 0, 0, 0) => Ok(DBDateTime::ZERO),
+			Value::Date(year, month, day, hour, minute, second, micro) => DBDate::new(year, month, day)
+				.and_then(|date| DBDateTime::new(date, hour, minute, second, micro))
+				.map_err(|_| FromValueError(value)),
+			value => parse_bytes(value),
+		}
+	}
+}
+
+impl From<DBDateTime> for Value {
+	fn from(datetime: DBDateTime) -> Self {
+		let DBDateTime { date, hour, minute, second, micro } = datetime;
+		Value::Date(date.year, date.month, date.day, hour, minute, second, micro)
+	}
+}
+
+impl FromValue for DBTimestamp {
+	type Intermediate = DBTimestamp;
+}
+
+/// The value is a `DATETIME` in the session time zone, see `session_utc_offset`.
+impl TryFrom<Value> for DBTimestamp {
+	type Error = FromValueError;
+
+	fn try_from(value: Value) -> Result<Self, Self::Error> {
+		let Some(utc_offset) = session_utc_offset() else {
+			MISSING_TIME_ZONE.set(true);
+			return Err(FromValueError(value));
+		};
+		let datetime = DBDateTime::try_from(value.clone())?;
+		DBTimestamp::from_local(datetime, utc_offset).map_err(|_| FromValueError(value))
+	}
+}
+
+/// `YYYY-MM-DD HH:MM:SS.ffffff+00:00`, read by MySQL as UTC whatever the session time zone.
+impl From<DBTimestamp> for Value {
+	fn from(timestamp: DBTimestamp) -> Self {
+		let utc = timestamp.to_utc();
+		Value::from(format!(
+			"{} {:02}:{:02}:{:02}.{:06}+00:00",
+			utc.date(), utc.hour(), utc.minute(), utc.second(), utc.micro()
+		))
+	}
+}
+
+/// The ISO-8601 strings of `Display`/`FromStr`.
+#[cfg(feature = "serde")]
+mod serde_impl {
+	use std::{fmt::Display, str::FromStr};
+
+	use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
+
+	use super::{DBDate, DBDateTime, DBTime, DBTimestamp};
+
+	fn deserialize_str<'de, D: Deserializer<'de>, T: FromStr<Err: Display>>(deserializer: D) -> Result<T, D::Error> {
+		let s = String::deserialize(deserializer)?;
+		s.parse().map_err(D::Error::custom)
+	}
+
+	macro_rules! serde_as_string {
+		($($ty:ty),*) => {
+			$(
+				impl Serialize for $ty {
+					fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+						serializer.collect_str(self)
+					}
+				}
+
+				impl<'de> Deserialize<'de> for $ty {
+					fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+						deserialize_str(deserializer)
+					}
+				}
+			)*
+		};
+	}
+
+	serde_as_string!(DBDate, DBTime, DBDateTime, DBTimestamp);
+}
+
+/// Conversions with `chrono`. Zero dates and values outside the MySQL range fail.
+#[cfg(feature = "chrono")]
+mod chrono_impl {
+	use chrono::{DateTime, Datelike, NaiveDate, NaiveDateTime, NaiveTime, TimeDelta, Timelike, Utc};
+
+	use super::{DBDate, DBDateTime, DBTime, DBTimestamp, DateError, MICROS_PER_SECOND};
+
+	impl TryFrom<NaiveDate> for DBDate {
+		type Error = DateError;
+
+		fn try_from(date: NaiveDate) -> Result<Self, Self::Error> {
+			let year = u16::try_from(date.year()).map_err(|_| DateError::OutOfRange)?;
+			DBDate::new(year, date.month() as u8, date.day() as u8)
+		}
+	}
+
+	impl TryFrom<DBDate> for NaiveDate {
+		type Error = DateError;
+
+		fn try_from(date: DBDate) -> Result<Self, Self::Error> {
+			NaiveDate::from_ymd_opt(date.year as i32, date.month as u32, date.day as u32)
+				.ok_or(DateError::InvalidDate { year: date.year, month: date.month, day: date.day })
+		}
+	}
+
+	impl TryFrom<NaiveDateTime> for DBDateTime {
+		type Error = DateError;
+
+		fn try_from(datetime: NaiveDateTime) -> Result<Self, Self::Error> {
+			let time = datetime.time();
+			DBDateTime::new(datetime.date().try_into()?, time.hour() as u8, time.minute() as u8, time.second() as u8, time.nanosecond() / 1000)
+		}
+	}
+
+	impl TryFrom<DBDateTime> for NaiveDateTime {
+		type Error = DateError;
+
+		fn try_from(datetime: DBDateTime) -> Result<Self, Self::Error> {
+			let time = NaiveTime::from_hms_micro_opt(datetime.hour as u32, datetime.minute as u32, datetime.second as u32, datetime.micro)
+				.ok_or(DateError::OutOfRange)?;
+			Ok(NaiveDateTime::new(datetime.date.try_into()?, time))
+		}
+	}
+
+	/// A leap second is folded into the following second.
+	impl From<NaiveTime> for DBTime {
+		fn from(time: NaiveTime) -> Self {
+			DBTime { micros: time.num_seconds_from_midnight() as i64 * MICROS_PER_SECOND + (time.nanosecond() / 1000) as i64 }
+		}
+	}
+
+	/// Fails for negative times and times of 24 hours or more.
+	impl TryFrom<DBTime> for NaiveTime {
+		type Error = DateError;
+
+		fn try_from(time: DBTime) -> Result<Self, Self::Error> {
+			if time.is_negative() || time.hours() >= 24 {
+				return Err(DateError::OutOfRange);
+			}
+			NaiveTime::from_hms_micro_opt(time.hours() as u32, time.minutes() as u32, time.seconds() as u32, time.micros())
+				.ok_or(DateError::OutOfRange)
+		}
+	}
+
+	impl From<DBTime> for TimeDelta {
+		fn from(time: DBTime) -> Self {
+			TimeDelta::microseconds(time.micros)
+		}
+	}
+
+	impl TryFrom<TimeDelta> for DBTime {
+		type Error = DateError;
+
+		fn try_from(delta: TimeDelta) -> Result<Self, Self::Error> {
+			DBTime::from_micros(delta.num_microseconds().ok_or(DateError::OutOfRange)?)
+		}
+	}
+
+	impl TryFrom<DateTime<Utc>> for DBTimestamp {
+		type Error = DateError;
+
+		fn try_from(datetime: DateTime<Utc>) -> Result<Self, Self::Error> {
+			DBTimestamp::from_unix_micros(datetime.timestamp_micros())
+		}
+	}
+
+	impl From<DBTimestamp> for DateTime<Utc> {
+		fn from(timestamp: DBTimestamp) -> Self {
+			DateTime::from_timestamp_micros(timestamp.micros).expect("TIMESTAMP range within chrono range")
+		}
+	}
+}
+
+/// Conversions with `time`. Zero dates and values outside the MySQL range fail.
+#[cfg(feature = "time")]
+mod time_impl {
+	use time::{Date, Month, OffsetDateTime, PrimitiveDateTime, Time};
+
+	use super::{DBDate, DBDateTime, DBTime, DBTimestamp, DateError, MICROS_PER_SECOND};
+
+	impl TryFrom<Date> for DBDate {
+		type Error = DateError;
+
+		fn try_from(date: Date) -> Result<Self, Self::Error> {
+			let year = u16::try_from(date.year()).map_err(|_| DateError::OutOfRange)?;
+			DBDate::new(year, date.month().into(), date.day())
+		}
+	}
+
+	impl TryFrom<DBDate> for Date {
+		type Error = DateError;
+
+		fn try_from(date: DBDate) -> Result<Self, Self::Error> {
+			let invalid = DateError::InvalidDate { year: date.year, month: date.month, day: date.day };
+			let month = Month::try_from(date.month).map_err(|_| invalid.clone())?;
+			Date::from_calendar_date(date.year as i32, month, date.day).map_err(|_| invalid)
+		}
+	}
+
+	impl TryFrom<PrimitiveDateTime> for DBDateTime {
+		type Error = DateError;
+
+		fn try_from(datetime: PrimitiveDateTime) -> Result<Self, Self::Error> {
+			DBDateTime::new(datetime.date().try_into()?, datetime.hour(), datetime.minute(), datetime.second(), datetime.microsecond())
+		}
+	}
+
+	impl TryFrom<DBDateTime> for PrimitiveDateTime {
+		type Error = DateError;
+
+		fn try_from(datetime: DBDateTime) -> Result<Self, Self::Error> {
+			let time = Time::from_hms_micro(datetime.hour, datetime.minute, datetime.second, datetime.micro).map_err(|_| DateError::OutOfRange)?;
+			Ok(PrimitiveDateTime::new(datetime.date.try_into()?, time))
+		}
+	}
+
+	impl From<Time> for DBTime {
+		fn from(time: Time) -> Self {
+			let (hour, minute, second, micro) = time.as_hms_micro();
+			let seconds = (hour as i64 * 60 + minute as i64) * 60 + second as i64;
+			DBTime { micros: seconds * MICROS_PER_SECOND + micro as i64 }
+		}
+	}
+
+	/// Fails for negative times and times of 24 hours or more.
+	impl TryFrom<DBTime> for Time {
+		type Error = DateError;
+
+		fn try_from(time: DBTime) -> Result<Self, Self::Error> {
+			if time.is_negative() || time.hours() >= 24 {
+				return Err(DateError::OutOfRange);
+			}
+			Time::from_hms_micro(time.hours() as u8, time.minutes(), time.seconds(), time.micros()).map_err(|_| DateError::OutOfRange)
+		}
+	}
+
+	impl From<DBTime> for time::Duration {
+		fn from(time: DBTime) -> Self {
+			time::Duration::microseconds(time.micros)
+		}
+	}
+
+	impl TryFrom<time::Duration> for DBTime {
+		type Error = DateError;
+
+		fn try_from(duration: time::Duration) -> Result<Self, Self::Error> {
+			DBTime::from_micros(i64::try_from(duration.whole_microseconds()).map_err(|_| DateError::OutOfRange)?)
+		}
+	}
+
+	impl TryFrom<OffsetDateTime> for DBTimestamp {
+		type Error = DateError;
+
+		fn try_from(datetime: OffsetDateTime) -> Result<Self, Self::Error> {
+			let micros = datetime.unix_timestamp_nanos().div_euclid(1000);
+			DBTimestamp::from_unix_micros(i64::try_from(micros).map_err(|_| DateError::OutOfRange)?)
+		}
+	}
+
+	impl From<DBTimestamp> for OffsetDateTime {
+		fn from(timestamp: DBTimestamp) -> Self {
+			OffsetDateTime::from_unix_timestamp_nanos(timestamp.micros as i128 * 1000).expect("TIMESTAMP range within time range")
 		}
 	}
 }
 
-impl From<DBDateTime> for mysql_async::Value {
-	fn from(v: DBDateTime) -> Self {
-		let DBDateTime(y, m, d, h, min, s) = v;
-		let y = if y.is_negative() { 0 } else { y };
-		mysql_async::Value::Date(y as u16, m, d, h, min, s, 0)
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn bytes(s: &str) -> Value {
+		Value::Bytes(s.as_bytes().to_vec())
+	}
+
+	#[test]
+	fn date_round_trips_through_strings() {
+		for s in ["2024-02-29", "0000-01-01", "9999-12-31", "0000-00-00"] {
+			assert_eq!(s.parse::<DBDate>().unwrap().to_string(), s);
+		}
+		assert!("0000-00-00".parse::<DBDate>().unwrap().is_zero());
+	}
+
+	#[test]
+	fn invalid_dates_are_rejected() {
+		assert_eq!(DBDate::new(2024, 2, 30), Err(DateError::InvalidDate { year: 2024, month: 2, day: 30 }));
+		assert!(DBDate::new(2023, 2, 29).is_err());
+		assert!(DBDate::new(2024, 13, 1).is_err());
+		assert!(DBDate::new(2024, 0, 1).is_err());
+		assert!("2024-02-30".parse::<DBDate>().is_err());
+		assert!("2024-13-01".parse::<DBDate>().is_err());
+		assert!("2024-00-00".parse::<DBDate>().is_err());
+		assert!("2024-1-01".parse::<DBDate>().is_err());
+	}
+
+	#[test]
+	fn time_round_trips_through_strings() {
+		for s in ["00:00:00", "12:34:56.000100", "-00:00:01", "-838:59:59", "838:59:59", "100:00:00"] {
+			assert_eq!(s.parse::<DBTime>().unwrap().to_string(), s);
+		}
+		assert_eq!("12:00:00.5".parse::<DBTime>().unwrap().micros(), 500_000);
+	}
+
+	#[test]
+	fn time_accepts_negative_and_long_durations() {
+		let time: DBTime = "-100:30:00".parse().unwrap();
+		assert!(time.is_negative());
+		assert_eq!((time.hours(), time.minutes()), (100, 30));
+		assert_eq!(time.as_micros(), -(100 * 3600 + 30 * 60) * MICROS_PER_SECOND);
+		assert_eq!(-time, "100:30:00".parse().unwrap());
+		assert!("839:00:00".parse::<DBTime>().is_err());
+		assert!("12:60:00".parse::<DBTime>().is_err());
+		assert_eq!(DBTime::MAX.checked_add(Duration::from_secs(1)), None);
+	}
+
+	#[test]
+	fn datetime_round_trips_through_strings() {
+		let datetime: DBDateTime = "2024-12-31T23:59:59.999999".parse().unwrap();
+		assert_eq!(datetime.to_string(), "2024-12-31T23:59:59.999999");
+		assert_eq!("2024-12-31 23:59:59.999999".parse::<DBDateTime>().unwrap(), datetime);
+		assert_eq!("0000-00-00 00:00:00".parse::<DBDateTime>().unwrap(), DBDateTime::ZERO);
+		assert!("0000-00-00 00:00:01".parse::<DBDateTime>().is_err());
+		assert!("2024-02-30 00:00:00".parse::<DBDateTime>().is_err());
+		assert!("2024-01-01 24:00:00".parse::<DBDateTime>().is_err());
+	}
+
+	#[test]
+	fn timestamp_round_trips_through_strings() {
+		let timestamp: DBTimestamp = "2024-01-02T03:04:05.000006Z".parse().unwrap();
+		assert_eq!(timestamp.to_string(), "2024-01-02T03:04:05.000006Z");
+		assert_eq!("2024-01-02T04:34:05.000006+01:30".parse::<DBTimestamp>().unwrap(), timestamp);
+		assert!("1970-01-01T00:00:00Z".parse::<DBTimestamp>().is_err());
+	}
+
+	#[test]
+	fn zero_dates_sort_first_and_have_no_arithmetic() {
+		assert!(DBDate::ZERO < DBDate::MIN);
+		assert!(DBDateTime::ZERO < DBDateTime::MIN);
+		assert_eq!(DBDate::ZERO.checked_add_days(1), None);
+		assert_eq!(DBDateTime::ZERO.checked_add(Duration::from_secs(1)), None);
+	}
+
+	#[test]
+	fn date_from_value() {
+		assert_eq!(DBDate::try_from(Value::Date(2024, 2, 29, 0, 0, 0, 0)).unwrap(), DBDate::new(2024, 2, 29).unwrap());
+		assert_eq!(DBDate::try_from(Value::Date(0, 0, 0, 0, 0, 0, 0)).unwrap(), DBDate::ZERO);
+		assert!(DBDate::try_from(Value::Date(2024, 2, 30, 0, 0, 0, 0)).is_err());
+		assert_eq!(DBDate::try_from(bytes("2024-02-29")).unwrap(), DBDate::new(2024, 2, 29).unwrap());
+		assert!(DBDate::try_from(bytes("2024-13-01")).is_err());
+		assert!(DBDate::try_from(Value::NULL).is_err());
+		let date = DBDate::new(1999, 12, 31).unwrap();
+		assert_eq!(DBDate::try_from(Value::from(date)).unwrap(), date);
+	}
+
+	#[test]
+	fn time_from_value() {
+		let time: DBTime = "-50:00:01.000002".parse().unwrap();
+		assert_eq!(DBTime::try_from(Value::Time(true, 2, 2, 0, 1, 2)).unwrap(), time);
+		assert_eq!(DBTime::try_from(Value::from(time)).unwrap(), time);
+		assert_eq!(DBTime::try_from(bytes("-50:00:01.000002")).unwrap(), time);
+		assert!(DBTime::try_from(Value::Time(false, 35, 0, 0, 0, 0)).is_err());
+		assert!(DBTime::try_from(bytes("25:61:00")).is_err());
+	}
+
+	#[test]
+	fn datetime_from_value() {
+		let datetime = DBDateTime::new(DBDate::new(2024, 1, 2).unwrap(), 3, 4, 5, 6).unwrap();
+		assert_eq!(DBDateTime::try_from(Value::Date(2024, 1, 2, 3, 4, 5, 6)).unwrap(), datetime);
+		assert_eq!(DBDateTime::try_from(bytes("2024-01-02 03:04:05.000006")).unwrap(), datetime);
+		assert_eq!(DBDateTime::try_from(Value::from(datetime)).unwrap(), datetime);
+		assert_eq!(DBDateTime::try_from(Value::Date(0, 0, 0, 0, 0, 0, 0)).unwrap(), DBDateTime::ZERO);
+		assert_eq!(DBDateTime::try_from(bytes("0000-00-00 00:00:00")).unwrap(), DBDateTime::ZERO);
+		assert!(DBDateTime::try_from(Value::Date(2024, 2, 30, 0, 0, 0, 0)).is_err());
+	}
+
+	#[test]
+	fn timestamp_from_value_needs_the_session_offset() {
+		let timestamp: DBTimestamp = "2024-01-02T03:04:05Z".parse().unwrap();
+		assert!(DBTimestamp::try_from(Value::Date(2024, 1, 2, 3, 4, 5, 0)).is_err());
+		with_session_utc_offset(Some(60), || {
+			assert_eq!(DBTimestamp::try_from(Value::Date(2024, 1, 2, 4, 4, 5, 0)).unwrap(), timestamp);
+			assert_eq!(DBTimestamp::try_from(bytes("2024-01-02 04:04:05")).unwrap(), timestamp);
+			assert!(DBTimestamp::try_from(Value::Date(0, 0, 0, 0, 0, 0, 0)).is_err());
+		});
+		assert_eq!(session_utc_offset(), None);
+		assert_eq!(Value::from(timestamp), bytes("2024-01-02 03:04:05.000000+00:00"));
+	}
+
+	#[test]
+	fn timestamp_offsets_are_bounded() {
+		let timestamp: DBTimestamp = "2024-01-02T03:04:05Z".parse().unwrap();
+		assert_eq!(timestamp.to_local(-150).unwrap().to_string(), "2024-01-02T00:34:05");
+		assert_eq!(timestamp.to_local(840).unwrap().to_string(), "2024-01-02T17:04:05");
+		assert_eq!(timestamp.to_local(841), Err(DateError::OutOfRange));
+		assert_eq!(DBTimestamp::MAX.to_local(i32::MAX), Err(DateError::OutOfRange));
+		assert_eq!(DBTimestamp::from_local(timestamp.to_utc(), -900), Err(DateError::OutOfRange));
+		assert!("2024-01-02T03:04:05+15:00".parse::<DBTimestamp>().is_err());
+	}
+
+	#[test]
+	fn missing_offset_is_flagged() {
+		assert!(!take_missing_time_zone());
+		assert!(DBTimestamp::try_from(bytes("2024-01-02 04:04:05")).is_err());
+		assert!(take_missing_time_zone());
+		assert!(!take_missing_time_zone());
+	}
+
+	#[cfg(feature = "chrono")]
+	#[test]
+	fn chrono_round_trips() {
+		use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, TimeDelta, Utc};
+
+		let datetime: DBDateTime = "2024-02-29 13:45:07.250001".parse().unwrap();
+		let naive = NaiveDateTime::try_from(datetime).unwrap();
+		assert_eq!(naive.to_string(), "2024-02-29 13:45:07.250001");
+		assert_eq!(DBDateTime::try_from(naive).unwrap(), datetime);
+		assert_eq!(DBDate::try_from(NaiveDate::try_from(DBDate::MAX).unwrap()).unwrap(), DBDate::MAX);
+		let time: DBTime = "23:59:59.999999".parse().unwrap();
+		assert_eq!(DBTime::from(NaiveTime::try_from(time).unwrap()), time);
+		assert_eq!(DBTime::try_from(TimeDelta::from(DBTime::MIN)).unwrap(), DBTime::MIN);
+		for timestamp in [DBTimestamp::MIN, DBTimestamp::MAX] {
+			assert_eq!(DBTimestamp::try_from(DateTime::<Utc>::from(timestamp)).unwrap(), timestamp);
+		}
+	}
+
+	#[cfg(feature = "chrono")]
+	#[test]
+	fn chrono_values_outside_the_mysql_range_fail() {
+		use chrono::{DateTime, NaiveDate, NaiveTime, TimeDelta};
+
+		assert!(NaiveDate::try_from(DBDate::ZERO).is_err());
+		assert!(DBDate::try_from(NaiveDate::from_ymd_opt(-1, 1, 1).unwrap()).is_err());
+		assert!(DBDate::try_from(NaiveDate::from_ymd_opt(10000, 1, 1).unwrap()).is_err());
+		assert!(NaiveTime::try_from(DBTime::new(24, 0, 0, 0).unwrap()).is_err());
+		assert!(NaiveTime::try_from(DBTime::from_micros(-1).unwrap()).is_err());
+		assert!(DBTime::try_from(TimeDelta::hours(839)).is_err());
+		assert!(DBTimestamp::try_from(DateTime::from_timestamp(0, 0).unwrap()).is_err());
+		assert!(DBTimestamp::try_from(DateTime::from_timestamp(i32::MAX as i64 + 1, 0).unwrap()).is_err());
+	}
+
+	#[cfg(feature = "time")]
+	#[test]
+	fn time_round_trips() {
+		use time::{Date, OffsetDateTime, PrimitiveDateTime, Time};
+
+		let datetime: DBDateTime = "2024-02-29 13:45:07.250001".parse().unwrap();
+		let primitive = PrimitiveDateTime::try_from(datetime).unwrap();
+		assert_eq!((primitive.hour(), primitive.microsecond()), (13, 250_001));
+		assert_eq!(DBDateTime::try_from(primitive).unwrap(), datetime);
+		assert_eq!(DBDate::try_from(Date::try_from(DBDate::MIN).unwrap()).unwrap(), DBDate::MIN);
+		let time: DBTime = "23:59:59.999999".parse().unwrap();
+		assert_eq!(DBTime::from(Time::try_from(time).unwrap()), time);
+		assert_eq!(DBTime::try_from(time::Duration::from(DBTime::MAX)).unwrap(), DBTime::MAX);
+		for timestamp in [DBTimestamp::MIN, DBTimestamp::MAX] {
+			assert_eq!(DBTimestamp::try_from(OffsetDateTime::from(timestamp)).unwrap(), timestamp);
+		}
+	}
+
+	#[cfg(feature = "time")]
+	#[test]
+	fn time_values_outside_the_mysql_range_fail() {
+		use time::{Date, Month, OffsetDateTime, Time};
+
+		assert!(Date::try_from(DBDate::ZERO).is_err());
+		assert!(DBDate::try_from(Date::from_calendar_date(-1, Month::January, 1).unwrap()).is_err());
+		assert!(Time::try_from(DBTime::new(24, 0, 0, 0).unwrap()).is_err());
+		assert!(DBTime::try_from(time::Duration::hours(839)).is_err());
+		assert!(DBTimestamp::try_from(OffsetDateTime::UNIX_EPOCH).is_err());
+		assert!(DBTimestamp::try_from(OffsetDateTime::from_unix_timestamp(i32::MAX as i64 + 1).unwrap()).is_err());
 	}
 }
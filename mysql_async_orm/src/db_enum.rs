@@ -0,0 +1,269 @@
+use std::{fmt, hash::Hash, marker::PhantomData};
+
+use mysql_async::{prelude::FromValue, FromValueError, Value};
+use serde_json::json;
+
+use crate::{
+	db_query::{Column, Filter},
+	db_schema::{ColumnDdl, SqlType},
+	fe_export::TsType,
+	json_schema::JsonSchemaType,
+};
+
+/// A Rust enum stored as a MySQL `ENUM` or as an integer code, implemented by `#[derive(DbEnum)]`.
+pub trait DbEnum: Sized + 'static {
+	/// The member names in declaration order, which is the order of the MySQL `ENUM`/`SET` members.
+	const NAMES: &'static [&'static str];
+	/// Position of the variant in `NAMES`.
+	fn index(&self) -> usize;
+	fn from_index(index: usize) -> Option<Self>;
+
+	fn name(&self) -> &'static str {
+		Self::NAMES[self.index()]
+	}
+
+	fn from_name(name: &str) -> Option<Self> {
+		Self::from_index(Self::NAMES.iter().position(|n| *n == name)?)
+	}
+}
+
+/// A `DbEnum` with at most 64 variants, which can be the member type of a `DbSet`.
+/// `#[derive(DbEnum)]` implements it for those enums.
+#[diagnostic::on_unimplemented(
+	message = "`{Self}` cannot be a `DbSet` member",
+	note = "a `DbSet` member must derive `DbEnum` and have at most 64 variants"
+)]
+pub trait SetMember: DbEnum {}
+
+/// `ENUM('a','b')` or `SET('a','b')`.
+pub fn members_sql_type(keyword: &str, names: &[&str]) -> String {
+	let members: Vec<String> = names.iter().map(|name| format!("'{}'", name.replace('\'', "''"))).collect();
+	format!("{}({})", keyword, members.join(","))
+}
+
+/// `"a" | "b"`, the TypeScript union of the member names.
+pub fn names_ts_type(names: &[&str]) -> String {
+	names.iter().map(|name| json!(name).to_string()).collect::<Vec<_>>().join(" | ")
+}
+
+/// The text of an `ENUM` value, as both protocols send it.
+pub fn name_from_value(value: &Value) -> Option<&str> {
+	match value {
+		Value::Bytes(bytes) => std::str::from_utf8(bytes).ok(),
+		_ => None,
+	}
+}
+
+/// The integer of an integer coded enum, from either protocol.
+pub fn code_from_value(value: &Value) -> Option<i64> {
+	match value {
+		Value::Int(code) => Some(*code),
+		Value::UInt(code) => i64::try_from(*code).ok(),
+		Value::Bytes(bytes) => std::str::from_utf8(bytes).ok()?.parse().ok(),
+		_ => None,
+	}
+}
+
+/// A MySQL `SET` of the members of `E`.
+pub struct DbSet<E> {
+	bits: u64,
+	marker: PhantomData<fn() -> E>,
+}
+
+impl<E: SetMember> DbSet<E> {
+	pub fn new() -> Self {
+		DbSet::from_bits_unchecked(0)
+	}
+
+	/// The numeric value MySQL gives the set, bit `i` being member `i`. `None` for unknown members.
+	pub fn from_bits(bits: u64) -> Option<Self> {
+		match E::NAMES.len() {
+			len if len < 64 && bits >> len != 0 => None,
+			_ => Some(DbSet::from_bits_unchecked(bits)),
+		}
+	}
+
+	fn from_bits_unchecked(bits: u64) -> Self {
+		DbSet { bits, marker: PhantomData }
+	}
+
+	pub fn bits(&self) -> u64 {
+		self.bits
+	}
+
+	/// Returns whether the member was not already present.
+	pub fn insert(&mut self, member: E) -> bool {
+		let bit = Self::bit(&member);
+		let res = self.bits & bit == 0;
+		self.bits |= bit;
+		res
+	}
+
+	/// Returns whether the member was present.
+	pub fn remove(&mut self, member: &E) -> bool {
+		let bit = Self::bit(member);
+		let res = self.bits & bit != 0;
+		self.bits &= !bit;
+		res
+	}
+
+	pub fn contains(&self, member: &E) -> bool {
+		self.bits & Self::bit(member) != 0
+	}
+
+	pub fn len(&self) -> usize {
+		self.bits.count_ones() as usize
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.bits == 0
+	}
+
+	/// The members in declaration order.
+	pub fn iter(&self) -> impl Iterator<Item = E> + '_ {
+		(0..E::NAMES.len()).filter(|index| self.bits & 1 << index != 0).filter_map(E::from_index)
+	}
+
+	fn bit(member: &E) -> u64 {
+		1 << member.index()
+	}
+}
+
+impl<E: SetMember> Default for DbSet<E> {
+	fn default() -> Self {
+		DbSet::new()
+	}
+}
+
+impl<E> Clone for DbSet<E> {
+	fn clone(&self) -> Self {
+		*self
+	}
+}
+
+impl<E> Copy for DbSet<E> {}
+
+impl<E> PartialEq for DbSet<E> {
+	fn eq(&self, other: &Self) -> bool {
+		self.bits == other.bits
+	}
+}
+
+impl<E> Eq for DbSet<E> {}
+
+impl<E> Hash for DbSet<E> {
+	fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+		self.bits.hash(state);
+	}
+}
+
+impl<E: SetMember + fmt::Debug> fmt::Debug for DbSet<E> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_set().entries(self.iter()).finish()
+	}
+}
+
+/// The MySQL text of the set, e.g. `a,b`.
+impl<E: SetMember> fmt::Display for DbSet<E> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		let names: Vec<&str> = self.iter().map(|member| member.name()).collect();
+		f.write_str(&names.join(","))
+	}
+}
+
+impl<E: SetMember> FromIterator<E> for DbSet<E> {
+	fn from_iter<I: IntoIterator<Item = E>>(iter: I) -> Self {
+		let mut res = DbSet::new();
+		for member in iter {
+			res.insert(member);
+		}
+		res
+	}
+}
+
+impl<E: SetMember> FromValue for DbSet<E> {
+	type Intermediate = DbSet<E>;
+}
+
+/// Text (`a,b`) as sent by both protocols, or the numeric value of the set.
+impl<E: SetMember> TryFrom<Value> for DbSet<E> {
+	type Error = FromValueError;
+
+	fn try_from(value: Value) -> Result<Self, Self::Error> {
+		let res = match &value {
+			Value::Int(bits) => DbSet::from_bits(*bits as u64),
+			Value::UInt(bits) => DbSet::from_bits(*bits),
+			_ => name_from_value(&value).and_then(|text| {
+				text.split(',').filter(|name| !name.is_empty()).map(E::from_name).collect::<Option<DbSet<E>>>()
+			}),
+		};
+		res.ok_or(FromValueError(value))
+	}
+}
+
+impl<E: SetMember> From<DbSet<E>> for Value {
+	fn from(set: DbSet<E>) -> Self {
+		Value::Bytes(set.to_string().into_bytes())
+	}
+}
+
+impl<E: SetMember> SqlType for DbSet<E> {
+	fn sql_type(_: &ColumnDdl) -> String {
+		members_sql_type("SET", E::NAMES)
+	}
+}
+
+impl<E: SetMember> JsonSchemaType for DbSet<E> {
+	fn json_schema(_: &ColumnDdl) -> serde_json::Value {
+		json!({ "type": "array", "items": { "type": "string", "enum": E::NAMES }, "uniqueItems": true })
+	}
+}
+
+impl<E: SetMember> TsType for DbSet<E> {
+	fn ts_type() -> String {
+		format!("({})[]", names_ts_type(E::NAMES))
+	}
+}
+
+/// The column types accepting member filters, `DbSet<E>` and `Option<DbSet<E>>`.
+pub trait SetColumn {
+	type Member: SetMember;
+}
+
+impl<E: SetMember> SetColumn for DbSet<E> {
+	type Member = E;
+}
+
+impl<E: SetMember> SetColumn for Option<DbSet<E>> {
+	type Member = E;
+}
+
+impl<C: SetColumn> Column<C> {
+	/// `FIND_IN_SET(member, column)`.
+	pub fn contains(&self, member: C::Member) -> Filter {
+		Filter::raw(format!("FIND_IN_SET(?, {})", self.sql()), vec![Value::from(member.name())])
+	}
+}
+
+/// A sequence of member names.
+#[cfg(feature = "serde")]
+mod serde_impl {
+	use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
+
+	use super::{DbSet, SetMember};
+
+	impl<E: SetMember> Serialize for DbSet<E> {
+		fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+			serializer.collect_seq(self.iter().map(|member| member.name()))
+		}
+	}
+
+	impl<'de, E: SetMember> Deserialize<'de> for DbSet<E> {
+		fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+			let names = Vec::<String>::deserialize(deserializer)?;
+			names.iter()
+				.map(|name| E::from_name(name).ok_or_else(|| D::Error::custom(format!("unknown member {:?}", name))))
+				.collect()
+		}
+	}
+}
@@ -0,0 +1,220 @@
+use std::{
+	marker::PhantomData,
+	ops::{Deref, DerefMut},
+};
+
+use mysql_async::{prelude::FromValue, FromValueError, Value};
+use serde::{de::DeserializeOwned, Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::json;
+
+use crate::{
+	db_connection::DbError,
+	db_query::Filter,
+	db_schema::{ColumnDdl, SqlType},
+	fe_export::TsType,
+	json_schema::JsonSchemaType,
+};
+
+/// A MySQL `JSON` column holding a `T`, decoded and encoded with serde.
+///
+/// Writing a model fails with `DbError::Encode` if serializing `T` fails, which `serde_json`
+/// only does for maps with non-string keys or failing `Serialize` impls.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct DbJson<T>(pub T);
+
+impl<T> DbJson<T> {
+	pub fn into_inner(self) -> T {
+		self.0
+	}
+}
+
+impl<T> Deref for DbJson<T> {
+	type Target = T;
+
+	fn deref(&self) -> &T {
+		&self.0
+	}
+}
+
+impl<T> DerefMut for DbJson<T> {
+	fn deref_mut(&mut self) -> &mut T {
+		&mut self.0
+	}
+}
+
+fn to_json_value<V: Serialize + ?Sized>(value: &V) -> Result<Value, serde_json::Error> {
+	serde_json::to_string(value).map(Value::from)
+}
+
+impl<T: DeserializeOwned> FromValue for DbJson<T> {
+	type Intermediate = DbJson<T>;
+}
+
+impl<T: DeserializeOwned> TryFrom<Value> for DbJson<T> {
+	type Error = FromValueError;
+
+	fn try_from(value: Value) -> Result<Self, Self::Error> {
+		match &value {
+			Value::Bytes(bytes) => serde_json::from_slice(bytes).map(DbJson).map_err(|_| FromValueError(value)),
+			_ => Err(FromValueError(value)),
+		}
+	}
+}
+
+/// Serialized as the inner value, so a model serializes its JSON columns as nested JSON.
+impl<T: Serialize> Serialize for DbJson<T> {
+	fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		self.0.serialize(serializer)
+	}
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for DbJson<T> {
+	fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+		T::deserialize(deserializer).map(DbJson)
+	}
+}
+
+impl<T> SqlType for DbJson<T> {
+	fn sql_type(_: &ColumnDdl) -> String {
+		"JSON".to_string()
+	}
+}
+
+/// Any JSON value, the schema of `T` is not known.
+impl<T> JsonSchemaType for DbJson<T> {
+	fn json_schema(_: &ColumnDdl) -> serde_json::Value {
+		json!({})
+	}
+}
+
+impl<T> TsType for DbJson<T> {
+	fn ts_type() -> String {
+		"unknown".to_string()
+	}
+}
+
+/// The column types accepting JSON path filters, `DbJson<T>` and `Option<DbJson<T>>`.
+pub trait JsonColumn {
+	/// The JSON text written for the column, `NULL` for `None`.
+	fn try_to_value(&self) -> Result<Value, serde_json::Error>;
+}
+
+impl<T: Serialize> JsonColumn for DbJson<T> {
+	fn try_to_value(&self) -> Result<Value, serde_json::Error> {
+		to_json_value(&self.0)
+	}
+}
+
+impl<T: Serialize> JsonColumn for Option<DbJson<T>> {
+	fn try_to_value(&self) -> Result<Value, serde_json::Error> {
+		match self {
+			Some(json) => json.try_to_value(),
+			None => Ok(Value::NULL),
+		}
+	}
+}
+
+/// Encodes a `DbJson` field for the generated `prepare_insert` and `prepare_update`, reporting
+/// which field of which model cannot be serialized.
+pub fn encode_column<C: JsonColumn>(value: &C, model: &'static str, table: &'static str, field: &'static str) -> Result<Value, DbError> {
+	value.try_to_value().map_err(|error| DbError::Encode { model, table, field, message: error.to_string() })
+}
+
+/// Handle on a `DbJson` column, as returned by the generated `columns()` in place of a `Column`.
+/// The document is filtered through its paths, never compared as a whole, since MySQL would
+/// compare it with the bound text as a JSON string.
+pub struct JsonDocument<C> {
+	sql: &'static str,
+	column: PhantomData<fn() -> C>,
+}
+
+impl<C> Clone for JsonDocument<C> {
+	fn clone(&self) -> Self {
+		*self
+	}
+}
+
+impl<C> Copy for JsonDocument<C> {}
+
+impl<C: JsonColumn> JsonDocument<C> {
+	pub fn new(sql: &'static str) -> Self {
+		JsonDocument { sql, column: PhantomData }
+	}
+
+	pub fn sql(&self) -> &'static str {
+		self.sql
+	}
+
+	/// A value inside the document, e.g. `"$.tolleranza.max"` or `"$.misure[0]"`.
+	pub fn path<S: Into<String>>(&self, path: S) -> JsonPath {
+		JsonPath { column: self.sql, path: path.into() }
+	}
+
+	/// `JSON_CONTAINS(column, value)`: `value` is contained in the document.
+	pub fn json_contains<V: Serialize + ?Sized>(&self, value: &V) -> Result<Filter, serde_json::Error> {
+		Ok(Filter::raw(format!("JSON_CONTAINS({}, CAST(? AS JSON))", self.sql), vec![to_json_value(value)?]))
+	}
+
+	pub fn is_null(&self) -> Filter {
+		Filter::raw(format!("{} IS NULL", self.sql), Vec::new())
+	}
+
+	pub fn is_not_null(&self) -> Filter {
+		Filter::raw(format!("{} IS NOT NULL", self.sql), Vec::new())
+	}
+}
+
+/// A JSON path into a `DbJson` column, compared with JSON values using MySQL's JSON comparison
+/// rules, so numbers compare as numbers and strings as strings. The comparisons fail if
+/// `value` cannot be serialized as JSON.
+#[derive(Debug, Clone)]
+pub struct JsonPath {
+	column: &'static str,
+	path: String,
+}
+
+impl JsonPath {
+	pub fn eq<V: Serialize + ?Sized>(&self, value: &V) -> Result<Filter, serde_json::Error> {
+		self.compare("=", value)
+	}
+
+	pub fn ne<V: Serialize + ?Sized>(&self, value: &V) -> Result<Filter, serde_json::Error> {
+		self.compare("<>", value)
+	}
+
+	pub fn gt<V: Serialize + ?Sized>(&self, value: &V) -> Result<Filter, serde_json::Error> {
+		self.compare(">", value)
+	}
+
+	pub fn ge<V: Serialize + ?Sized>(&self, value: &V) -> Result<Filter, serde_json::Error> {
+		self.compare(">=", value)
+	}
+
+	pub fn lt<V: Serialize + ?Sized>(&self, value: &V) -> Result<Filter, serde_json::Error> {
+		self.compare("<", value)
+	}
+
+	pub fn le<V: Serialize + ?Sized>(&self, value: &V) -> Result<Filter, serde_json::Error> {
+		self.compare("<=", value)
+	}
+
+	/// `JSON_CONTAINS(column, value, path)`: `value` is contained in the value at the path.
+	pub fn contains<V: Serialize + ?Sized>(&self, value: &V) -> Result<Filter, serde_json::Error> {
+		Ok(Filter::raw(
+			format!("JSON_CONTAINS({}, CAST(? AS JSON), ?)", self.column),
+			vec![to_json_value(value)?, Value::from(self.path.as_str())],
+		))
+	}
+
+	/// The path is present, possibly with a JSON `null`.
+	pub fn exists(&self) -> Filter {
+		Filter::raw(format!("JSON_CONTAINS_PATH({}, 'one', ?)", self.column), vec![Value::from(self.path.as_str())])
+	}
+
+	fn compare<V: Serialize + ?Sized>(&self, op: &str, value: &V) -> Result<Filter, serde_json::Error> {
+		Ok(Filter::raw(
+			format!("JSON_EXTRACT({}, ?) {} CAST(? AS JSON)", self.column, op),
+			vec![Value::from(self.path.as_str()), to_json_value(value)?],
+		))
+	}
+}
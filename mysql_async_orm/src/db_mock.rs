@@ -0,0 +1,390 @@
+use std::{borrow::Cow, collections::VecDeque, sync::Arc, time::Duration};
+
+use mysql_async::{
+	consts::{ColumnFlags, ColumnType},
+	prelude::{AsQuery, FromRow},
+	Column, Params, Row, ServerError, Value,
+};
+use regex::Regex;
+
+use crate::db_connection::{returns_rows, BoxFuture, DbError, DbTransaction, QueryEvent, QueryHook, QueryHooks, QueryableConn, SqlStatement};
+
+/// A `QueryableConn` that answers from scripted expectations instead of a server.
+///
+/// Expectations are consumed in order: every statement must match the next one, otherwise
+/// it fails. `MockConn::recording()` accepts any statement instead. Statements are reported to
+/// the query hooks like on a connection, with a zero duration. `query_iter` and `exec_iter`,
+/// and so `Query::stream`, are not supported.
+#[derive(Debug, Default)]
+pub struct MockConn {
+	expectations: VecDeque<Expectation>,
+	executed: Vec<ExecutedStatement>,
+	recording: bool,
+	next_insert_id: u64,
+	last_insert_id: Option<u64>,
+	affected_rows: u64,
+	hooks: QueryHooks,
+	utc_offset: Option<i32>,
+}
+
+/// A statement received by a `MockConn`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExecutedStatement {
+	pub sql: String,
+	/// Named parameters are sorted by name.
+	pub params: Vec<Value>,
+}
+
+#[derive(Debug)]
+enum SqlMatcher {
+	Exact(String),
+	Regex(Regex),
+}
+
+impl SqlMatcher {
+	fn matches(&self, sql: &str) -> bool {
+		match self {
+			SqlMatcher::Exact(expected) => expected == sql,
+			SqlMatcher::Regex(regex) => regex.is_match(sql),
+		}
+	}
+}
+
+#[derive(Debug)]
+pub struct Expectation {
+	sql: SqlMatcher,
+	params: Option<Vec<Value>>,
+	rows: Vec<Row>,
+	affected_rows: u64,
+	last_insert_id: Option<u64>,
+	error: Option<ServerError>,
+}
+
+impl Expectation {
+	fn new(sql: SqlMatcher) -> Self {
+		Expectation {
+			sql,
+			params: None,
+			rows: Vec::new(),
+			affected_rows: 0,
+			last_insert_id: None,
+			error: None,
+		}
+	}
+
+	/// Also requires the statement to be executed with exactly these positional parameters.
+	pub fn with_params<I: IntoIterator<Item = V>, V: Into<Value>>(&mut self, params: I) -> &mut Self {
+		self.params = Some(params.into_iter().map(Into::into).collect());
+		self
+	}
+
+	/// Rows returned by the statement. The column types are taken from the first non-NULL value.
+	pub fn returning<R: IntoIterator<Item = Vec<Value>>>(&mut self, columns: &[&str], rows: R) -> &mut Self {
+		let rows: Vec<Vec<Value>> = rows.into_iter().collect();
+		let columns: Arc<[Column]> = columns.iter().enumerate().map(|(i, name)| {
+			let value = rows.iter().filter_map(|row| row.get(i)).find(|value| **value != Value::NULL);
+			column(name, value)
+		}).collect::<Vec<_>>().into();
+		self.rows = rows.into_iter().map(|values| mysql_common::row::new_row(values, columns.clone())).collect();
+		self
+	}
+
+	pub fn affected_rows(&mut self, affected_rows: u64) -> &mut Self {
+		self.affected_rows = affected_rows;
+		self
+	}
+
+	pub fn last_insert_id(&mut self, id: u64) -> &mut Self {
+		self.last_insert_id = Some(id);
+		self
+	}
+
+	/// Fails the statement with a server error, e.g. `1062` for a duplicate key.
+	pub fn fails(&mut self, code: u16, message: &str) -> &mut Self {
+		self.error = Some(ServerError {
+			code,
+			message: message.to_string(),
+			state: "HY000".to_string(),
+		});
+		self
+	}
+}
+
+fn column(name: &str, value: Option<&Value>) -> Column {
+	let (column_type, flags) = match value {
+		None | Some(Value::NULL) => (ColumnType::MYSQL_TYPE_NULL, ColumnFlags::empty()),
+		Some(Value::Bytes(_)) => (ColumnType::MYSQL_TYPE_VAR_STRING, ColumnFlags::empty()),
+		Some(Value::Int(_)) => (ColumnType::MYSQL_TYPE_LONGLONG, ColumnFlags::empty()),
+		Some(Value::UInt(_)) => (ColumnType::MYSQL_TYPE_LONGLONG, ColumnFlags::UNSIGNED_FLAG),
+		Some(Value::Float(_)) => (ColumnType::MYSQL_TYPE_FLOAT, ColumnFlags::empty()),
+		Some(Value::Double(_)) => (ColumnType::MYSQL_TYPE_DOUBLE, ColumnFlags::empty()),
+		Some(Value::Date(..)) => (ColumnType::MYSQL_TYPE_DATETIME, ColumnFlags::empty()),
+		Some(Value::Time(..)) => (ColumnType::MYSQL_TYPE_TIME, ColumnFlags::empty()),
+	};
+	Column::new(column_type).with_name(name.as_bytes()).with_flags(flags)
+}
+
+fn mock_error(message: String) -> DbError {
+	DbError::Driver(mysql_async::Error::Other(message.into()))
+}
+
+impl MockConn {
+	pub fn new() -> Self {
+		MockConn::default()
+	}
+
+	/// Accepts any statement not matched by an expectation, returning no rows and giving
+	/// `INSERT`/`REPLACE` statements increasing insert ids starting from 1.
+	pub fn recording() -> Self {
+		MockConn {
+			recording: true,
+			..MockConn::default()
+		}
+	}
+
+	/// Adds a hook to this mock; only `on_skipped_row` is reported.
+	pub fn add_query_hook<H: QueryHook + 'static>(&mut self, hook: H) {
+		self.hooks.add(hook);
+	}
+
+	/// Decodes `DBTimestamp`s as if the session `time_zone` was pinned to `utc_offset` minutes.
+	pub fn set_time_zone(&mut self, utc_offset: i32) {
+		self.utc_offset = Some(utc_offset);
+	}
+
+	pub fn expect<S: Into<String>>(&mut self, sql: S) -> &mut Expectation {
+		self.push_expectation(SqlMatcher::Exact(sql.into()))
+	}
+
+	/// Panics if `pattern` is not a valid regex.
+	pub fn expect_regex(&mut self, pattern: &str) -> &mut Expectation {
+		self.push_expectation(SqlMatcher::Regex(Regex::new(pattern).expect("invalid regex")))
+	}
+
+	pub fn executed(&self) -> &[ExecutedStatement] {
+		&self.executed
+	}
+
+	pub fn executed_sql(&self) -> Vec<&str> {
+		self.executed.iter().map(|statement| statement.sql.as_str()).collect()
+	}
+
+	/// Expectations not consumed yet.
+	pub fn pending(&self) -> usize {
+		self.expectations.len()
+	}
+
+	fn push_expectation(&mut self, sql: SqlMatcher) -> &mut Expectation {
+		self.expectations.push_back(Expectation::new(sql));
+		self.expectations.back_mut().unwrap()
+	}
+
+	fn run(&mut self, method: &'static str, sql: Cow<'_, str>, params: Params) -> Result<Vec<Row>, DbError> {
+		let params = match params {
+			Params::Empty => Vec::new(),
+			Params::Positional(params) => params,
+			Params::Named(params) => {
+				let mut params: Vec<_> = params.into_iter().collect();
+				params.sort_by(|(a, _), (b, _)| a.cmp(b));
+				params.into_iter().map(|(_, value)| value).collect()
+			}
+		};
+		let statement = ExecutedStatement {
+			sql: sql.into_owned(),
+			params,
+		};
+		let matches = self.expectations.front().map(|expectation| {
+			expectation.sql.matches(&statement.sql)
+				&& expectation.params.as_ref().is_none_or(|params| *params == statement.params)
+		});
+		let result = match matches {
+			Some(true) => {
+				let expectation = self.expectations.pop_front().unwrap();
+				match expectation.error {
+					Some(error) => Err(DbError::Driver(error.into())),
+					None => {
+						self.affected_rows = expectation.affected_rows;
+						if expectation.last_insert_id.is_some() {
+							self.last_insert_id = expectation.last_insert_id;
+						}
+						Ok(expectation.rows)
+					}
+				}
+			}
+			_ if self.recording => {
+				self.last_insert_id = if is_insert(&statement.sql) {
+					self.next_insert_id += 1;
+					Some(self.next_insert_id)
+				} else {
+					None
+				};
+				self.affected_rows = 0;
+				Ok(Vec::new())
+			}
+			Some(false) => Err(mock_error(format!(
+				"Unexpected statement {:?}, expected {:?}",
+				statement, self.expectations.front().unwrap()
+			))),
+			None => Err(mock_error(format!("Unexpected statement {:?}", statement))),
+		};
+		self.hooks.on_query(&QueryEvent {
+			method,
+			sql: &statement.sql,
+			params: statement.params.len(),
+			duration: Duration::ZERO,
+			affected_rows: Some(self.affected_rows).filter(|_| result.is_ok() && !returns_rows(&statement.sql)),
+			error: result.as_ref().err(),
+		});
+		self.executed.push(statement);
+		result
+	}
+
+	fn rows<R: FromRow>(&mut self, method: &'static str, sql: Cow<'_, str>, params: Params) -> Result<Vec<R>, DbError> {
+		let rows = self.run(method, sql, params)?;
+		crate::db_date::with_session_utc_offset(self.utc_offset, || rows
+			.into_iter()
+			.map(|row| mysql_async::from_row_opt(row).map_err(|error| mock_error(error.to_string())))
+			.collect())
+	}
+}
+
+fn is_insert(sql: &str) -> bool {
+	let keyword = sql.trim_start().split(|c: char| !c.is_ascii_alphabetic()).next().unwrap_or_default();
+	keyword.eq_ignore_ascii_case("insert") || keyword.eq_ignore_ascii_case("replace")
+}
+
+fn query_text<Q: AsQuery>(query: &Q) -> Cow<'_, str> {
+	match query.as_query() {
+		Cow::Borrowed(bytes) => String::from_utf8_lossy(bytes),
+		Cow::Owned(bytes) => Cow::Owned(String::from_utf8_lossy(&bytes).into_owned()),
+	}
+}
+
+impl QueryableConn for MockConn {
+	fn query<'a, Q, R>(&'a mut self, query: Q) -> BoxFuture<'a, Vec<R>>
+	where
+		Q: AsQuery + Send + Sync + 'a,
+		R: FromRow + Send + 'static,
+	{
+		let result = self.rows("query", query_text(&query), Params::Empty);
+		Box::pin(async move { result })
+	}
+
+	fn query_first<'a, Q, R>(&'a mut self, query: Q) -> BoxFuture<'a, Option<R>>
+	where
+		Q: AsQuery + Send + Sync + 'a,
+		R: FromRow + Send + 'static,
+	{
+		let result = self.rows("query_first", query_text(&query), Params::Empty).map(|rows| rows.into_iter().next());
+		Box::pin(async move { result })
+	}
+
+	/// Not supported: `QueryResult` cannot be built without a server.
+	fn query_iter<'a, Q: AsQuery + Send + Sync + 'a>(
+		&'a mut self,
+		query: Q,
+	) -> BoxFuture<'a, mysql_async::QueryResult<'a, 'static, mysql_async::TextProtocol>> {
+		let error = mock_error(format!("MockConn does not support query_iter ({})", query_text(&query)));
+		Box::pin(async move { Err(error) })
+	}
+
+	fn query_drop<'a, Q>(&'a mut self, query: Q) -> BoxFuture<'a, ()>
+	where
+		Q: AsQuery + Send + Sync + 'a,
+	{
+		let result = self.run("query_drop", query_text(&query), Params::Empty).map(|_| ());
+		Box::pin(async move { result })
+	}
+
+	fn exec<'a, S, P, R>(&'a mut self, stmt: S, params: P) -> BoxFuture<'a, Vec<R>>
+	where
+		S: SqlStatement + 'a,
+		P: Into<Params> + Send + 'a,
+		R: FromRow + Send + 'static,
+	{
+		let result = self.rows("exec", stmt.sql_text(), params.into());
+		Box::pin(async move { result })
+	}
+
+	fn exec_map<'a: 'b, 'b, T, S, P, U, F>(
+		&'a mut self,
+		stmt: S,
+		params: P,
+		f: F,
+	) -> BoxFuture<'b, Vec<U>>
+	where
+		S: SqlStatement + 'b,
+		P: Into<Params> + Send + 'b,
+		T: FromRow + Send + 'static,
+		F: FnMut(T) -> U + Send + 'a,
+		U: Send + 'a,
+	{
+		let result = self.rows("exec_map", stmt.sql_text(), params.into()).map(|rows| rows.into_iter().map(f).collect());
+		Box::pin(async move { result })
+	}
+
+	/// Not supported: `QueryResult` cannot be built without a server.
+	fn exec_iter<'a: 's, 's, Q, P>(
+		&'a mut self,
+		stmt: Q,
+		_params: P,
+	) -> BoxFuture<'s, mysql_async::QueryResult<'a, 'static, mysql_async::BinaryProtocol>>
+	where
+		Q: SqlStatement + 'a,
+		P: Into<Params>,
+	{
+		let error = mock_error(format!("MockConn does not support exec_iter ({})", stmt.sql_text()));
+		Box::pin(async move { Err(error) })
+	}
+
+	fn exec_first<'a, S, P, R>(&'a mut self, stmt: S, params: P) -> BoxFuture<'a, Option<R>>
+	where
+		S: SqlStatement + 'a,
+		P: Into<Params> + Send + 'a,
+		R: FromRow + Send + 'static,
+	{
+		let result = self.rows("exec_first", stmt.sql_text(), params.into()).map(|rows| rows.into_iter().next());
+		Box::pin(async move { result })
+	}
+
+	fn exec_drop<'a, S, P>(&'a mut self, stmt: S, params: P) -> BoxFuture<'a, ()>
+	where
+		S: SqlStatement + 'a,
+		P: Into<Params> + Send + 'a,
+	{
+		let result = self.run("exec_drop", stmt.sql_text(), params.into()).map(|_| ());
+		Box::pin(async move { result })
+	}
+
+	fn exec_batch<'a, S, P, I>(&'a mut self, stmt: S, params: I) -> BoxFuture<'a, ()>
+	where
+		S: SqlStatement + 'a,
+		I: IntoIterator<Item = P> + Send + 'a,
+		I::IntoIter: Send,
+		P: Into<Params> + Send,
+	{
+		let result = params.into_iter().try_for_each(|params| self.run("exec_batch", stmt.sql_text(), params.into()).map(|_| ()));
+		Box::pin(async move { result })
+	}
+
+	fn last_insert_id(&self) -> Option<u64> {
+		self.last_insert_id
+	}
+
+	fn affected_rows(&self) -> u64 {
+		self.affected_rows
+	}
+
+	fn query_hooks(&self) -> &QueryHooks {
+		&self.hooks
+	}
+
+	fn session_utc_offset(&self) -> Option<i32> {
+		self.utc_offset
+	}
+
+	/// Graph writes run directly on the mock, so their statements are recorded in order.
+	fn begin_atomic<'a>(&'a mut self) -> BoxFuture<'a, Option<DbTransaction<'a>>> {
+		Box::pin(async { Ok(None) })
+	}
+}
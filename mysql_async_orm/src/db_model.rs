@@ -1,17 +1,217 @@
+use mysql_async::{prelude::FromValue, FromValueError, Row, Value};
+
+use crate::db_connection::{DbError, QueryHooks, QueryableConn};
+
 pub trait DbModel where Self: Sized {
-	type DataCollector: DbModelDataCollector;
+	type DataCollector: DbModelDataCollector<Item = Self>;
 	type PrimaryKey;
-	fn prepare_insert(fk: Option<&str>, data: &Self, query: &mut String, this_id: usize);
-	fn prepare_update(fk: Option<&str>, new_data: &Self, old_data: &Self, query: &mut String, this_id: usize);
-	fn prepare_delete(fk: Option<&str>, data: &Self, query: &mut String, this_id: usize);
+	type Columns;
+	fn prepare_insert(fk: Option<(&str, &WriteParam)>, data: &Self, statements: &mut Vec<WriteStatement>, this_id: usize) -> Result<(), DbError>;
+	fn prepare_update(fk: Option<(&str, &WriteParam)>, new_data: &Self, old_data: &Self, statements: &mut Vec<WriteStatement>, this_id: usize) -> Result<(), DbError>;
+	fn prepare_delete(fk: Option<(&str, &WriteParam)>, data: &Self, statements: &mut Vec<WriteStatement>, this_id: usize);
 	fn get_pk(&self) -> Option<Self::PrimaryKey>;
+	fn columns() -> Self::Columns;
+	fn model_sql() -> &'static ModelSql;
 }
 
+/// Upper bound on the `?` placeholders of a single prepared statement.
+pub const MAX_PLACEHOLDERS: usize = 65535;
+
+pub type DataCollectorSql = (&'static str, String, Vec<usize>, Vec<(&'static str, String, String)>);
+
 pub trait DbModelDataCollector where Self: Sized {
 	type Item;
 	const SIZE: usize;
-	fn sql() -> (&'static str, String, Vec<usize>, Vec<(&'static str, String, String)>);
-	fn new(offset: usize) -> Self;
-	fn push_next(&mut self, next_row: &mut mysql_async::Row) -> Option<()>;
+	fn sql() -> DataCollectorSql;
+	fn new(offset: usize, mode: DecodeMode) -> Self;
+	/// In `DecodeMode::Lenient` the errors of the skipped rows are pushed to `skipped`.
+	fn push_next(&mut self, next_row: &mut mysql_async::Row, skipped: &mut Vec<DbError>) -> Result<(), DbError>;
+	/// Takes the items whose rows are complete, i.e. the primary key already changed.
+	fn take_completed(&mut self) -> Vec<Self::Item>;
 	fn build(self) -> Vec<Self::Item>;
-}
\ No newline at end of file
+}
+
+/// What a collector does with a root row that cannot be decoded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DecodeMode {
+	/// Fail the whole load with `DbError::RowDecode`.
+	#[default]
+	Strict,
+	/// Skip the root row (and its relations), collecting the error.
+	Lenient,
+}
+
+impl DecodeMode {
+	/// A missing session time zone is a configuration error and fails the load in both modes.
+	pub fn skip_or_fail(self, error: DbError, skipped: &mut Vec<DbError>) -> Result<(), DbError> {
+		match self {
+			DecodeMode::Strict => Err(error),
+			DecodeMode::Lenient if matches!(error, DbError::MissingTimeZone { .. }) => Err(error),
+			DecodeMode::Lenient => {
+				skipped.push(error);
+				Ok(())
+			}
+		}
+	}
+}
+
+/// Reports the rows skipped by a lenient load to the query hooks and, with the `tracing`
+/// feature, as warnings.
+pub fn report_skipped_rows(hooks: &QueryHooks, skipped: Vec<DbError>) {
+	for error in skipped {
+		#[cfg(feature = "tracing")]
+		tracing::warn!(%error, "skipped row");
+		hooks.on_skipped_row(&error);
+	}
+}
+
+/// Takes the value at `index`, reporting which field of which model failed to decode.
+pub fn take_column<T: FromValue>(row: &mut Row, index: usize, model: &'static str, table: &'static str, field: &'static str) -> Result<T, DbError> {
+	match row.take_opt::<T, usize>(index) {
+		Some(Ok(value)) => Ok(value),
+		_ => Err(row_decode_error(row, index, model, table, field)),
+	}
+}
+
+/// Like `take_column` for the primary key of a collector, also keeping the raw value in `raw` when it
+/// cannot be decoded, so that the other rows of the same root can be skipped silently.
+pub fn take_pk<T: FromValue>(row: &mut Row, index: usize, model: &'static str, table: &'static str, field: &'static str, raw: &mut Option<Value>) -> Result<T, DbError> {
+	match row.take_opt::<Value, usize>(index) {
+		Some(Ok(value)) => T::from_value_opt(value).map_err(|FromValueError(value)| {
+			*raw = Some(value);
+			row_decode_error(row, index, model, table, field)
+		}),
+		_ => Err(row_decode_error(row, index, model, table, field)),
+	}
+}
+
+/// Like `take_column`, decoding the raw value with the `from_value` of a `#[convert(with = ...)]` module.
+pub fn take_converted<T>(
+	row: &mut Row,
+	index: usize,
+	model: &'static str,
+	table: &'static str,
+	field: &'static str,
+	from_value: fn(Value) -> Result<T, FromValueError>,
+) -> Result<T, DbError> {
+	match row.take_opt::<Value, usize>(index) {
+		Some(Ok(value)) => from_value(value).map_err(|_| row_decode_error(row, index, model, table, field)),
+		_ => Err(row_decode_error(row, index, model, table, field)),
+	}
+}
+
+fn row_decode_error(row: &Row, index: usize, model: &'static str, table: &'static str, field: &'static str) -> DbError {
+	if crate::db_date::take_missing_time_zone() {
+		return DbError::MissingTimeZone { model, table, field };
+	}
+	let (column, sql_type) = match row.columns_ref().get(index) {
+		Some(column) => (column.name_str().into_owned(), format!("{:?}", column.column_type())),
+		None => (String::new(), "missing".to_string()),
+	};
+	DbError::RowDecode { model, table, field, column, index, sql_type }
+}
+
+/// The UNION ALL select of a model graph, split so that a WHERE clause on the root
+/// table can be appended to every branch.
+pub struct ModelSql {
+	table: &'static str,
+	pk: &'static str,
+	from: &'static str,
+	branches: Vec<String>,
+	order_by: String,
+}
+
+impl ModelSql {
+	pub fn new<C: DbModelDataCollector>(pk: &'static str, from: &'static str) -> Self {
+		let (table, _, order_by, sql) = C::sql();
+		ModelSql {
+			table,
+			pk,
+			from,
+			branches: sql.iter().map(|(table, select, from)| format!("SELECT {} FROM {} {}", select, table, from)).collect(),
+			order_by: order_by.iter().map(|i| (i + 1).to_string()).collect::<Vec<_>>().join(","),
+		}
+	}
+
+	pub fn table(&self) -> &'static str {
+		self.table
+	}
+
+	pub fn pk(&self) -> &'static str {
+		self.pk
+	}
+
+	/// The root table with its own joins, without any relation.
+	pub fn from(&self) -> &'static str {
+		self.from
+	}
+
+	pub fn branch_count(&self) -> usize {
+		self.branches.len()
+	}
+
+	pub fn select(&self, condition: Option<&str>) -> String {
+		let sql = self.branches.iter().map(|branch| match condition {
+			Some(condition) => format!("{} WHERE {}", branch, condition),
+			None => branch.clone(),
+		}).collect::<Vec<_>>().join(" UNION ALL ");
+		format!("{} ORDER BY {};", sql, self.order_by)
+	}
+
+	pub fn repeat_params(&self, params: &[Value]) -> Vec<Value> {
+		let mut res = Vec::with_capacity(params.len() * self.branches.len());
+		for _ in 0..self.branches.len() {
+			res.extend_from_slice(params);
+		}
+		res
+	}
+}
+
+pub fn vec_from_rows<M: DbModel>(mut rows: Vec<Row>, mode: DecodeMode, skipped: &mut Vec<DbError>) -> Result<Vec<M>, DbError> {
+	let mut collector = M::DataCollector::new(0, mode);
+	for row in rows.iter_mut() {
+		collector.push_next(row, skipped)?;
+	}
+	Ok(collector.build())
+}
+
+/// A parameter of a write statement: a plain value, or the id generated by the last INSERT
+/// executed for the given nesting level.
+#[derive(Debug, Clone, PartialEq)]
+pub enum WriteParam {
+	Value(Value),
+	InsertId(usize),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct WriteStatement {
+	pub sql: String,
+	pub params: Vec<WriteParam>,
+	/// Nesting level whose generated id is stored after executing this statement.
+	pub insert_id: Option<usize>,
+}
+
+/// Executes the statements in order as prepared statements on the same connection.
+/// Returns the id generated by the first INSERT, if any.
+pub async fn exec_write<C: QueryableConn>(connection: &mut C, statements: Vec<WriteStatement>) -> Result<Option<u64>, DbError> {
+	let mut insert_ids: Vec<Option<u64>> = Vec::new();
+	let mut first_insert_id = None;
+	for WriteStatement { sql, params, insert_id } in statements {
+		let params = params.into_iter().map(|param| match param {
+			WriteParam::Value(value) => Ok(value),
+			WriteParam::InsertId(id) => insert_ids.get(id).copied().flatten().map(Value::from).ok_or(DbError::MissingInsertId),
+		}).collect::<Result<Vec<Value>, DbError>>()?;
+		connection.exec_drop(&sql, params).await?;
+		if let Some(id) = insert_id {
+			let last_insert_id = connection.last_insert_id();
+			if insert_ids.len() <= id {
+				insert_ids.resize(id + 1, None);
+			}
+			insert_ids[id] = last_insert_id;
+			if first_insert_id.is_none() {
+				first_insert_id = last_insert_id;
+			}
+		}
+	}
+	Ok(first_insert_id)
+}
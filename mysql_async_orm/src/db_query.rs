@@ -0,0 +1,418 @@
+use std::{
+	collections::{HashMap, VecDeque},
+	hash::Hash,
+	marker::PhantomData,
+};
+
+use futures_util::{stream, Stream};
+use mysql_async::{prelude::FromValue, BinaryProtocol, QueryResult, Value};
+
+use crate::{
+	db_connection::{DbError, QueryableConn},
+	db_date,
+	db_model::{self, DbModel, DbModelDataCollector, DecodeMode},
+};
+
+/// A SQL condition together with the values bound to its `?` placeholders.
+#[derive(Debug, Clone)]
+pub struct Filter {
+	sql: String,
+	params: Vec<Value>,
+}
+
+impl Filter {
+	pub fn raw<S: Into<String>>(sql: S, params: Vec<Value>) -> Self {
+		Filter {
+			sql: sql.into(),
+			params,
+		}
+	}
+
+	pub fn and(self, other: Filter) -> Filter {
+		self.combine("AND", other)
+	}
+
+	pub fn or(self, other: Filter) -> Filter {
+		self.combine("OR", other)
+	}
+
+	pub fn sql(&self) -> &str {
+		&self.sql
+	}
+
+	pub fn params(&self) -> &[Value] {
+		&self.params
+	}
+
+	fn combine(mut self, op: &str, other: Filter) -> Filter {
+		self.params.extend(other.params);
+		Filter {
+			sql: format!("({}) {} ({})", self.sql, op, other.sql),
+			params: self.params,
+		}
+	}
+}
+
+impl std::ops::Not for Filter {
+	type Output = Filter;
+
+	fn not(self) -> Filter {
+		Filter {
+			sql: format!("NOT ({})", self.sql),
+			params: self.params,
+		}
+	}
+}
+
+/// Typed handle on a column of a `DbModel`, as returned by the generated `columns()`.
+pub struct Column<T> {
+	sql: &'static str,
+	encode: fn(T) -> Value,
+}
+
+impl<T> Clone for Column<T> {
+	fn clone(&self) -> Self {
+		*self
+	}
+}
+
+impl<T> Copy for Column<T> {}
+
+impl<T: Into<Value>> Column<T> {
+	pub fn new(sql: &'static str) -> Self {
+		Column {
+			sql,
+			encode: Into::into,
+		}
+	}
+}
+
+impl<T> Column<T> {
+	/// A column whose values are encoded with `encode`, as for `#[convert(with = ...)]` fields.
+	pub fn with_encoder(sql: &'static str, encode: fn(T) -> Value) -> Self {
+		Column { sql, encode }
+	}
+
+	pub fn sql(&self) -> &'static str {
+		self.sql
+	}
+
+	/// `column = ?`, or `column IS NULL` when the value encodes as NULL (e.g. `None`).
+	pub fn eq<V: Into<T>>(&self, value: V) -> Filter {
+		self.compare_nullable("=", "IS NULL", value)
+	}
+
+	/// `column <> ?`, or `column IS NOT NULL` when the value encodes as NULL (e.g. `None`).
+	pub fn ne<V: Into<T>>(&self, value: V) -> Filter {
+		self.compare_nullable("<>", "IS NOT NULL", value)
+	}
+
+	pub fn gt<V: Into<T>>(&self, value: V) -> Filter {
+		self.compare(">", value)
+	}
+
+	pub fn ge<V: Into<T>>(&self, value: V) -> Filter {
+		self.compare(">=", value)
+	}
+
+	pub fn lt<V: Into<T>>(&self, value: V) -> Filter {
+		self.compare("<", value)
+	}
+
+	pub fn le<V: Into<T>>(&self, value: V) -> Filter {
+		self.compare("<=", value)
+	}
+
+	pub fn like<S: Into<String>>(&self, pattern: S) -> Filter {
+		Filter {
+			sql: format!("{} LIKE ?", self.sql),
+			params: vec![Value::from(pattern.into())],
+		}
+	}
+
+	pub fn in_list<V: Into<T>, I: IntoIterator<Item = V>>(&self, values: I) -> Filter {
+		let params: Vec<Value> = values.into_iter().map(|v| (self.encode)(v.into())).collect();
+		if params.is_empty() {
+			return Filter::raw("FALSE", Vec::new());
+		}
+		let placeholders = vec!["?"; params.len()].join(",");
+		Filter {
+			sql: format!("{} IN ({})", self.sql, placeholders),
+			params,
+		}
+	}
+
+	pub fn is_null(&self) -> Filter {
+		Filter::raw(format!("{} IS NULL", self.sql), Vec::new())
+	}
+
+	pub fn is_not_null(&self) -> Filter {
+		Filter::raw(format!("{} IS NOT NULL", self.sql), Vec::new())
+	}
+
+	fn compare<V: Into<T>>(&self, op: &str, value: V) -> Filter {
+		Filter {
+			sql: format!("{} {} ?", self.sql, op),
+			params: vec![(self.encode)(value.into())],
+		}
+	}
+
+	fn compare_nullable<V: Into<T>>(&self, op: &str, null_op: &str, value: V) -> Filter {
+		match (self.encode)(value.into()) {
+			Value::NULL => Filter::raw(format!("{} {}", self.sql, null_op), Vec::new()),
+			value => Filter {
+				sql: format!("{} {} ?", self.sql, op),
+				params: vec![value],
+			},
+		}
+	}
+}
+
+/// One page of root models, each with its relations fully loaded.
+#[derive(Debug)]
+pub struct Page<M, C> {
+	pub items: Vec<M>,
+	pub total: Option<u64>,
+	pub next_cursor: Option<C>,
+}
+
+/// Position of the last root of a keyset page: the value of the ordering column and the pk breaking ties.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cursor<T, K> {
+	pub value: T,
+	pub pk: K,
+}
+
+/// Loads complete model graphs whose root row matches the given filters.
+pub struct Query<M> {
+	filter: Option<Filter>,
+	mode: DecodeMode,
+	_model: PhantomData<fn() -> M>,
+}
+
+impl<M: DbModel> Default for Query<M> {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl<M: DbModel> Query<M> {
+	pub fn new() -> Self {
+		Query {
+			filter: None,
+			mode: DecodeMode::Strict,
+			_model: PhantomData,
+		}
+	}
+
+	/// Skips root rows that fail to decode instead of failing the whole query, reporting them
+	/// to `QueryHook::on_skipped_row` of the connection.
+	pub fn lenient(mut self) -> Self {
+		self.mode = DecodeMode::Lenient;
+		self
+	}
+
+	pub fn filter(mut self, filter: Filter) -> Self {
+		self.filter = Some(match self.filter.take() {
+			Some(current) => current.and(filter),
+			None => filter,
+		});
+		self
+	}
+
+	pub async fn fetch<C: QueryableConn>(self, connection: &mut C) -> Result<Vec<M>, DbError> {
+		let model_sql = M::model_sql();
+		let (sql, params) = match &self.filter {
+			Some(filter) => (model_sql.select(Some(filter.sql())), model_sql.repeat_params(filter.params())),
+			None => (model_sql.select(None), Vec::new()),
+		};
+		let rows = connection.exec(sql, params).await?;
+		let mut skipped = Vec::new();
+		let res = db_date::with_session_utc_offset(connection.session_utc_offset(), || db_model::vec_from_rows(rows, self.mode, &mut skipped));
+		db_model::report_skipped_rows(connection.query_hooks(), skipped);
+		res
+	}
+
+	/// Streams the matching models, holding in memory only the root currently being assembled.
+	pub fn stream<'a, C: QueryableConn>(self, connection: &'a mut C) -> impl Stream<Item = Result<M, DbError>> + 'a
+	where
+		M: 'a,
+	{
+		let model_sql = M::model_sql();
+		let (sql, params) = match &self.filter {
+			Some(filter) => (model_sql.select(Some(filter.sql())), model_sql.repeat_params(filter.params())),
+			None => (model_sql.select(None), Vec::new()),
+		};
+		let mode = self.mode;
+		let hooks = connection.query_hooks().clone();
+		let utc_offset = connection.session_utc_offset();
+		stream::unfold(StreamState::<C, M>::Pending(connection, sql, params), move |mut state| {
+			let hooks = hooks.clone();
+			async move {
+				loop {
+					state = match state {
+						StreamState::Pending(connection, sql, params) => match connection.exec_iter(sql, params).await {
+							Ok(result) => StreamState::Running(result, M::DataCollector::new(0, mode), VecDeque::new()),
+							Err(error) => return Some((Err(error), StreamState::Done)),
+						},
+						StreamState::Running(mut result, mut collector, mut ready) => {
+							if let Some(item) = ready.pop_front() {
+								return Some((Ok(item), StreamState::Running(result, collector, ready)));
+							}
+							match result.next().await {
+								Ok(Some(mut row)) => {
+									let mut skipped = Vec::new();
+									let pushed = db_date::with_session_utc_offset(utc_offset, || collector.push_next(&mut row, &mut skipped));
+									db_model::report_skipped_rows(&hooks, skipped);
+									if let Err(error) = pushed {
+										return Some((Err(error), StreamState::Done));
+									}
+									ready.extend(collector.take_completed());
+									StreamState::Running(result, collector, ready)
+								}
+								Ok(None) => StreamState::Finishing(collector.build().into()),
+								Err(error) => return Some((Err(error.into()), StreamState::Done)),
+							}
+						}
+						StreamState::Finishing(mut ready) => {
+							return ready.pop_front().map(|item| (Ok(item), StreamState::Finishing(ready)));
+						}
+						StreamState::Done => return None,
+					}
+				}
+			}
+		})
+	}
+
+	/// Offset pagination over the root primary keys, ordered by pk.
+	///
+	/// The count and the page are read by separate statements, so concurrent writes can make `total`
+	/// disagree with `items`; run it in a `START TRANSACTION WITH CONSISTENT SNAPSHOT` if that matters.
+	pub async fn page<C: QueryableConn>(self, offset: u64, limit: u64, connection: &mut C) -> Result<Page<M, u64>, DbError> {
+		let model_sql = M::model_sql();
+		let (condition, params) = self.condition();
+		let count_sql = format!("SELECT COUNT(DISTINCT {}) FROM {}{}", model_sql.pk(), model_sql.from(), condition);
+		let total: u64 = connection.exec_first(count_sql, params.clone()).await?.unwrap_or(0);
+		let pks_sql = format!(
+			"SELECT DISTINCT {pk} FROM {}{} ORDER BY {pk} LIMIT ? OFFSET ?",
+			model_sql.from(), condition, pk = model_sql.pk()
+		);
+		let mut params = params;
+		params.push(Value::from(limit));
+		params.push(Value::from(offset));
+		let pks: Vec<(Value,)> = connection.exec(pks_sql, params).await?;
+		let next_offset = offset + pks.len() as u64;
+		let items = Self::fetch_pks(pks.into_iter().map(|(pk,)| pk).collect(), self.mode, connection).await?;
+		Ok(Page {
+			items,
+			total: Some(total),
+			next_cursor: if next_offset < total { Some(next_offset) } else { None },
+		})
+	}
+
+	/// Keyset pagination ordered by `column` and then by pk. `column` should not contain NULLs.
+	pub async fn page_after<T, C>(
+		self,
+		column: Column<T>,
+		after: Option<Cursor<T, M::PrimaryKey>>,
+		limit: u64,
+		connection: &mut C,
+	) -> Result<Page<M, Cursor<T, M::PrimaryKey>>, DbError>
+	where
+		T: FromValue + Clone,
+		M::PrimaryKey: FromValue + Into<Value> + Clone + Eq + Hash,
+		C: QueryableConn,
+	{
+		let model_sql = M::model_sql();
+		let query = if let Some(Cursor { value, pk }) = after {
+			let pk_gt = Filter::raw(format!("{} > ?", model_sql.pk()), vec![pk.into()]);
+			self.filter(column.gt(value.clone()).or(column.eq(value).and(pk_gt)))
+		} else {
+			self
+		};
+		let mode = query.mode;
+		let (condition, mut params) = query.condition();
+		let pks_sql = format!(
+			"SELECT DISTINCT {col}, {pk} FROM {}{} ORDER BY {col}, {pk} LIMIT ?",
+			model_sql.from(), condition, col = column.sql(), pk = model_sql.pk()
+		);
+		params.push(Value::from(limit.saturating_add(1)));
+		let rows: Vec<(Value, Value)> = connection.exec(pks_sql, params).await?;
+		let decode_error = |field: &'static str, column: &str, index: usize, value: &Value| match db_date::take_missing_time_zone() {
+			true => DbError::MissingTimeZone { model: std::any::type_name::<M>(), table: model_sql.table(), field },
+			false => DbError::RowDecode {
+				model: std::any::type_name::<M>(),
+				table: model_sql.table(),
+				field,
+				column: column.to_string(),
+				index,
+				sql_type: value_kind(value).to_string(),
+			},
+		};
+		let mut rows = db_date::with_session_utc_offset(connection.session_utc_offset(), || rows.into_iter()
+			.map(|(value, pk)| Ok((
+				T::from_value_opt(value).map_err(|e| decode_error("cursor value", column.sql(), 0, &e.0))?,
+				M::PrimaryKey::from_value_opt(pk).map_err(|e| decode_error("cursor pk", model_sql.pk(), 1, &e.0))?,
+			)))
+			.collect::<Result<Vec<(T, M::PrimaryKey)>, DbError>>())?;
+		let has_more = rows.len() as u64 > limit;
+		rows.truncate(limit as usize);
+		let next_cursor = if has_more {
+			rows.last().map(|(value, pk)| Cursor { value: value.clone(), pk: pk.clone() })
+		} else {
+			None
+		};
+		let order: HashMap<M::PrimaryKey, usize> = rows.iter().enumerate().map(|(i, (_, pk))| (pk.clone(), i)).collect();
+		let mut items = Self::fetch_pks(rows.into_iter().map(|(_, pk)| pk.into()).collect(), mode, connection).await?;
+		items.sort_by_key(|item| item.get_pk().and_then(|pk| order.get(&pk).copied()));
+		Ok(Page {
+			items,
+			total: None,
+			next_cursor,
+		})
+	}
+
+	fn condition(&self) -> (String, Vec<Value>) {
+		match &self.filter {
+			Some(filter) => (format!(" WHERE {}", filter.sql()), filter.params().to_vec()),
+			None => (String::new(), Vec::new()),
+		}
+	}
+
+	async fn fetch_pks<C: QueryableConn>(pks: Vec<Value>, mode: DecodeMode, connection: &mut C) -> Result<Vec<M>, DbError> {
+		if pks.is_empty() {
+			return Ok(Vec::new());
+		}
+		let chunk_size = db_model::MAX_PLACEHOLDERS / M::model_sql().branch_count();
+		let mut res = Vec::with_capacity(pks.len());
+		for chunk in pks.chunks(chunk_size) {
+			let placeholders = vec!["?"; chunk.len()].join(",");
+			let query = Query::<M> { mode, ..Query::new() };
+			res.extend(query
+				.filter(Filter::raw(format!("{} IN ({})", M::model_sql().pk(), placeholders), chunk.to_vec()))
+				.fetch(connection)
+				.await?);
+		}
+		Ok(res)
+	}
+}
+
+fn value_kind(value: &Value) -> &'static str {
+	match value {
+		Value::NULL => "NULL",
+		Value::Bytes(_) => "BYTES",
+		Value::Int(_) => "INT",
+		Value::UInt(_) => "UINT",
+		Value::Float(_) => "FLOAT",
+		Value::Double(_) => "DOUBLE",
+		Value::Date(..) => "DATE",
+		Value::Time(..) => "TIME",
+	}
+}
+
+enum StreamState<'a, C, M: DbModel> {
+	Pending(&'a mut C, String, Vec<Value>),
+	Running(QueryResult<'a, 'static, BinaryProtocol>, M::DataCollector, VecDeque<M>),
+	Finishing(VecDeque<M>),
+	Done,
+}
@@ -0,0 +1,862 @@
+use std::{
+	collections::{HashMap, HashSet, VecDeque},
+	fmt,
+};
+
+use mysql_async::Value;
+
+use crate::{
+	db_connection::{DbError, QueryableConn},
+	db_date::{DBDate, DBDateTime, DBTime, DBTimestamp},
+	db_model::DbModel,
+};
+
+/// Column options given with `#[ddl(...)]`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ColumnDdl {
+	pub length: Option<u32>,
+	pub precision: Option<u32>,
+	pub scale: Option<u32>,
+	/// Raw SQL, e.g. `"0"`, `"'none'"` or `"CURRENT_TIMESTAMP"`.
+	pub default: Option<&'static str>,
+	pub nullable: Option<bool>,
+	/// Replaces the type derived from the Rust type.
+	pub sql_type: Option<&'static str>,
+	pub index: bool,
+}
+
+/// Maps a Rust field type to the MySQL column type used by `create_table_sql()`.
+pub trait SqlType {
+	const NULLABLE: bool = false;
+	const AUTO_INCREMENT: bool = false;
+	fn sql_type(ddl: &ColumnDdl) -> String;
+}
+
+macro_rules! integer_sql_type {
+	($($ty:ty => $sql:literal),* $(,)?) => {
+		$(
+			impl SqlType for $ty {
+				const AUTO_INCREMENT: bool = true;
+				fn sql_type(_: &ColumnDdl) -> String {
+					$sql.to_string()
+				}
+			}
+		)*
+	};
+}
+
+integer_sql_type! {
+	i8 => "TINYINT",
+	u8 => "TINYINT UNSIGNED",
+	i16 => "SMALLINT",
+	u16 => "SMALLINT UNSIGNED",
+	i32 => "INT",
+	u32 => "INT UNSIGNED",
+	i64 => "BIGINT",
+	u64 => "BIGINT UNSIGNED",
+	isize => "BIGINT",
+	usize => "BIGINT UNSIGNED",
+}
+
+fn float_sql_type(name: &str, ddl: &ColumnDdl) -> String {
+	match ddl.precision {
+		Some(precision) => format!("DECIMAL({},{})", precision, ddl.scale.unwrap_or(0)),
+		None => name.to_string(),
+	}
+}
+
+impl SqlType for f32 {
+	fn sql_type(ddl: &ColumnDdl) -> String {
+		float_sql_type("FLOAT", ddl)
+	}
+}
+
+impl SqlType for f64 {
+	fn sql_type(ddl: &ColumnDdl) -> String {
+		float_sql_type("DOUBLE", ddl)
+	}
+}
+
+/// `DECIMAL(10,0)` like MySQL unless `precision`/`scale` are given.
+#[cfg(feature = "rust_decimal")]
+impl SqlType for rust_decimal::Decimal {
+	fn sql_type(ddl: &ColumnDdl) -> String {
+		format!("DECIMAL({},{})", ddl.precision.unwrap_or(10), ddl.scale.unwrap_or(0))
+	}
+}
+
+impl SqlType for bool {
+	fn sql_type(_: &ColumnDdl) -> String {
+		"TINYINT(1)".to_string()
+	}
+}
+
+impl SqlType for String {
+	fn sql_type(ddl: &ColumnDdl) -> String {
+		format!("VARCHAR({})", ddl.length.unwrap_or(255))
+	}
+}
+
+impl SqlType for Vec<u8> {
+	fn sql_type(ddl: &ColumnDdl) -> String {
+		format!("VARBINARY({})", ddl.length.unwrap_or(255))
+	}
+}
+
+fn temporal_sql_type(name: &str, ddl: &ColumnDdl) -> String {
+	match ddl.precision {
+		Some(fsp) => format!("{}({})", name, fsp),
+		None => name.to_string(),
+	}
+}
+
+impl SqlType for DBDate {
+	fn sql_type(_: &ColumnDdl) -> String {
+		"DATE".to_string()
+	}
+}
+
+impl SqlType for DBTime {
+	fn sql_type(ddl: &ColumnDdl) -> String {
+		temporal_sql_type("TIME", ddl)
+	}
+}
+
+impl SqlType for DBDateTime {
+	fn sql_type(ddl: &ColumnDdl) -> String {
+		temporal_sql_type("DATETIME", ddl)
+	}
+}
+
+impl SqlType for DBTimestamp {
+	fn sql_type(ddl: &ColumnDdl) -> String {
+		temporal_sql_type("TIMESTAMP", ddl)
+	}
+}
+
+/// External temporal types map to the column type of the matching `db_date` type.
+#[allow(unused_macros)]
+macro_rules! same_sql_type {
+	($($ty:ty => $as:ty),* $(,)?) => {
+		$(
+			impl SqlType for $ty {
+				fn sql_type(ddl: &ColumnDdl) -> String {
+					<$as>::sql_type(ddl)
+				}
+			}
+		)*
+	};
+}
+
+#[cfg(feature = "chrono")]
+same_sql_type! {
+	chrono::NaiveDate => DBDate,
+	chrono::NaiveTime => DBTime,
+	chrono::NaiveDateTime => DBDateTime,
+}
+
+#[cfg(feature = "time")]
+same_sql_type! {
+	time::Date => DBDate,
+	time::Time => DBTime,
+	time::PrimitiveDateTime => DBDateTime,
+}
+
+impl<T: SqlType> SqlType for Option<T> {
+	const NULLABLE: bool = true;
+	const AUTO_INCREMENT: bool = T::AUTO_INCREMENT;
+	fn sql_type(ddl: &ColumnDdl) -> String {
+		T::sql_type(ddl)
+	}
+}
+
+/// The tables and columns of a model, generated by `#[derive(DbModel)]` for models marked `#[db_schema]`.
+#[diagnostic::on_unimplemented(
+	message = "`{Self}` is not marked `#[db_schema]`",
+	label = "add `#[db_schema]` to `{Self}`",
+	note = "the relations of a `#[db_schema]` model must be marked `#[db_schema]` too"
+)]
+pub trait DbSchema: DbModel {
+	/// Pushes the table of the model, with `fk` added, and then those of its relations.
+	fn table_defs(fk: Option<&ForeignKey<'_>>, tables: &mut Vec<TableDef>);
+	fn model_info() -> &'static ModelInfo;
+}
+
+/// The parent side of a relation, added as a column and a constraint to the child table.
+#[derive(Debug, Clone, Copy)]
+pub struct ForeignKey<'a> {
+	pub column: &'a str,
+	pub table: &'a str,
+	pub pk: &'a str,
+	pub sql_type: &'a str,
+}
+
+pub fn column_sql_type<T: SqlType>(ddl: &ColumnDdl) -> String {
+	match ddl.sql_type {
+		Some(sql_type) => sql_type.to_string(),
+		None => T::sql_type(ddl),
+	}
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ColumnDef {
+	pub name: String,
+	pub sql_type: String,
+	pub nullable: bool,
+	pub default: Option<String>,
+	pub auto_increment: bool,
+	pub index: bool,
+}
+
+impl ColumnDef {
+	pub fn new<T: SqlType>(name: &str, ddl: &ColumnDdl) -> Self {
+		ColumnDef {
+			name: name.to_string(),
+			sql_type: column_sql_type::<T>(ddl),
+			nullable: ddl.nullable.unwrap_or(T::NULLABLE),
+			default: ddl.default.map(str::to_string),
+			auto_increment: false,
+			index: ddl.index,
+		}
+	}
+
+	/// A column typed by `#[ddl(sql_type = ...)]`, whose Rust type needs no `SqlType`.
+	pub fn with_sql_type(name: &str, nullable: bool, ddl: &ColumnDdl) -> Self {
+		ColumnDef {
+			name: name.to_string(),
+			sql_type: ddl.sql_type.unwrap_or_default().to_string(),
+			nullable,
+			default: ddl.default.map(str::to_string),
+			auto_increment: false,
+			index: ddl.index,
+		}
+	}
+
+	/// Integer primary keys are `AUTO_INCREMENT`, also when `#[ddl(sql_type)]` names another integer type.
+	pub fn pk<T: SqlType>(name: &str, ddl: &ColumnDdl) -> Self {
+		let sql_type = column_sql_type::<T>(ddl);
+		ColumnDef {
+			name: name.to_string(),
+			auto_increment: T::AUTO_INCREMENT && is_integer_sql_type(&sql_type),
+			sql_type,
+			nullable: false,
+			default: None,
+			index: false,
+		}
+	}
+
+	/// `name TYPE [NOT] NULL [DEFAULT ...] [AUTO_INCREMENT]`, as used by CREATE TABLE and ALTER TABLE.
+	pub fn definition(&self) -> String {
+		let mut res = format!("{} {}", self.name, self.sql_type);
+		res.push_str(if self.nullable { " NULL" } else { " NOT NULL" });
+		if let Some(default) = &self.default {
+			res.push_str(" DEFAULT ");
+			res.push_str(default);
+		}
+		if self.auto_increment {
+			res.push_str(" AUTO_INCREMENT");
+		}
+		res
+	}
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ForeignKeyDef {
+	pub column: String,
+	pub table: String,
+	pub pk: String,
+}
+
+/// A table as the models expect it: the pk first, then the written columns.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TableDef {
+	pub table: String,
+	pub columns: Vec<ColumnDef>,
+	pub foreign_keys: Vec<ForeignKeyDef>,
+}
+
+impl TableDef {
+	/// `columns` starts with the pk; the foreign key column is added unless already among them.
+	pub fn new(table: &str, columns: Vec<ColumnDef>, fk: Option<&ForeignKey<'_>>) -> Self {
+		let mut res = TableDef { table: table.to_string(), columns, foreign_keys: Vec::new() };
+		if let Some(fk) = fk {
+			res.add_foreign_key(fk);
+		}
+		res
+	}
+
+	pub fn pk(&self) -> &ColumnDef {
+		&self.columns[0]
+	}
+
+	pub fn add_foreign_key(&mut self, fk: &ForeignKey<'_>) {
+		if !self.columns.iter().any(|column| column.name == fk.column) {
+			self.columns.insert(1, ColumnDef {
+				name: fk.column.to_string(),
+				sql_type: fk.sql_type.to_string(),
+				nullable: false,
+				default: None,
+				auto_increment: false,
+				index: false,
+			});
+		}
+		if !self.foreign_keys.iter().any(|key| key.column == fk.column) {
+			self.foreign_keys.push(ForeignKeyDef { column: fk.column.to_string(), table: fk.table.to_string(), pk: fk.pk.to_string() });
+		}
+	}
+
+	pub fn index_name(&self, column: &str) -> String {
+		format!("{}_{}_idx", self.table, column)
+	}
+
+	pub fn foreign_key_name(&self, column: &str) -> String {
+		format!("{}_{}_fk", self.table, column)
+	}
+
+	pub fn create_sql(&self) -> String {
+		let mut lines: Vec<String> = self.columns.iter().map(ColumnDef::definition).collect();
+		lines[0].push_str(" PRIMARY KEY");
+		for column in self.columns.iter().filter(|column| column.index) {
+			lines.push(format!("INDEX {} ({})", self.index_name(&column.name), column.name));
+		}
+		for fk in &self.foreign_keys {
+			lines.push(format!("CONSTRAINT {} FOREIGN KEY ({}) REFERENCES {}({})", self.foreign_key_name(&fk.column), fk.column, fk.table, fk.pk));
+		}
+		format!("CREATE TABLE {} (\n\t{}\n);", self.table, lines.join(",\n\t"))
+	}
+}
+
+/// What a `DbModel` reads and writes, as generated by the derive.
+#[derive(Debug)]
+pub struct ModelInfo {
+	pub name: &'static str,
+	pub table: &'static str,
+	pub joins: Vec<JoinInfo>,
+	pub pk: ColumnInfo,
+	pub columns: Vec<ColumnInfo>,
+	pub relations: Vec<RelationInfo>,
+}
+
+#[derive(Debug)]
+pub struct JoinInfo {
+	pub table: &'static str,
+	pub alias: &'static str,
+}
+
+#[derive(Debug)]
+pub struct ColumnInfo {
+	pub field: &'static str,
+	/// Table the column is read from, with join aliases resolved. `None` for expressions.
+	pub table: Option<&'static str>,
+	/// Column name, or the SQL of an expression.
+	pub column: &'static str,
+	pub readonly: bool,
+	pub sql_type: Option<String>,
+	/// Whether the Rust type accepts NULL.
+	pub optional: bool,
+}
+
+#[derive(Debug)]
+pub struct RelationInfo {
+	pub field: &'static str,
+	/// Column of the child table referencing the parent pk.
+	pub fk: &'static str,
+	pub model: fn() -> &'static ModelInfo,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SchemaIssue {
+	MissingTable { model: &'static str, table: &'static str },
+	MissingColumn { model: &'static str, field: &'static str, table: &'static str, column: &'static str },
+	TypeMismatch { model: &'static str, field: &'static str, table: &'static str, column: &'static str, expected: String, found: String },
+	/// The column accepts NULL but the field is not an `Option`.
+	Nullable { model: &'static str, field: &'static str, table: &'static str, column: &'static str },
+	MissingForeignKey { model: &'static str, field: &'static str, table: &'static str, column: &'static str },
+}
+
+impl fmt::Display for SchemaIssue {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			SchemaIssue::MissingTable { model, table } => write!(f, "{}: table {} does not exist", model, table),
+			SchemaIssue::MissingColumn { model, field, table, column } => write!(f, "{}.{}: column {}.{} does not exist", model, field, table, column),
+			SchemaIssue::TypeMismatch { model, field, table, column, expected, found } => {
+				write!(f, "{}.{}: column {}.{} is {}, expected {}", model, field, table, column, found, expected)
+			}
+			SchemaIssue::Nullable { model, field, table, column } => {
+				write!(f, "{}.{}: column {}.{} is nullable but the field is not an Option", model, field, table, column)
+			}
+			SchemaIssue::MissingForeignKey { model, field, table, column } => {
+				write!(f, "{}.{}: foreign key column {}.{} does not exist", model, field, table, column)
+			}
+		}
+	}
+}
+
+#[derive(Debug, Default)]
+pub struct SchemaReport {
+	pub models: Vec<&'static str>,
+	pub tables: Vec<&'static str>,
+	pub issues: Vec<SchemaIssue>,
+}
+
+impl SchemaReport {
+	pub fn is_ok(&self) -> bool {
+		self.issues.is_empty()
+	}
+}
+
+impl fmt::Display for SchemaReport {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "{} models, {} tables checked, {} issues", self.models.len(), self.tables.len(), self.issues.len())?;
+		for issue in &self.issues {
+			write!(f, "\n{}", issue)?;
+		}
+		Ok(())
+	}
+}
+
+/// Models checked together by `verify_schema`, e.g. every model of the application at startup.
+#[derive(Debug, Default, Clone)]
+pub struct ModelRegistry {
+	models: Vec<fn() -> &'static ModelInfo>,
+	tables: Vec<TableDefsFn>,
+}
+
+type TableDefsFn = fn(Option<&ForeignKey<'_>>, &mut Vec<TableDef>);
+
+impl ModelRegistry {
+	pub fn new() -> Self {
+		ModelRegistry::default()
+	}
+
+	pub fn register<T: DbSchema>(&mut self) -> &mut Self {
+		self.models.push(T::model_info);
+		self.tables.push(T::table_defs);
+		self
+	}
+
+	pub fn models(&self) -> impl Iterator<Item = &'static ModelInfo> + '_ {
+		self.models.iter().map(|model| model())
+	}
+
+	pub async fn verify_schema(&self, connection: &mut impl QueryableConn) -> Result<SchemaReport, DbError> {
+		verify_models(&self.models, connection).await
+	}
+
+	/// The tables of every registered model and relation, each once, referenced tables first.
+	pub fn table_defs(&self) -> Vec<TableDef> {
+		let mut all = Vec::new();
+		for tables in &self.tables {
+			tables(None, &mut all);
+		}
+		let mut merged: Vec<TableDef> = Vec::new();
+		for table in all {
+			match merged.iter_mut().find(|t| t.table == table.table) {
+				Some(existing) => {
+					for column in table.columns {
+						if !existing.columns.iter().any(|c| c.name == column.name) {
+							existing.columns.push(column);
+						}
+					}
+					for fk in table.foreign_keys {
+						if !existing.foreign_keys.iter().any(|key| key.column == fk.column) {
+							existing.foreign_keys.push(fk);
+						}
+					}
+				}
+				None => merged.push(table),
+			}
+		}
+		let mut res: Vec<TableDef> = Vec::with_capacity(merged.len());
+		while !merged.is_empty() {
+			let ready = merged.iter().position(|table| table.foreign_keys.iter().all(|fk| {
+				fk.table == table.table || res.iter().any(|t| t.table == fk.table) || !merged.iter().any(|t| t.table == fk.table)
+			})).unwrap_or(0);
+			res.push(merged.remove(ready));
+		}
+		res
+	}
+
+	pub fn create_table_sql(&self) -> Vec<String> {
+		self.table_defs().iter().map(TableDef::create_sql).collect()
+	}
+
+	/// Compares `table_defs()` with the current database, see `SchemaDiff`.
+	pub async fn diff_schema(&self, connection: &mut impl QueryableConn) -> Result<SchemaDiff, DbError> {
+		let tables = self.table_defs();
+		if tables.is_empty() {
+			return Ok(SchemaDiff::default());
+		}
+		let mut known: HashSet<(&str, String)> = HashSet::new();
+		for model in collect_models(&self.models) {
+			for info in [&model.pk].into_iter().chain(model.columns.iter()) {
+				if let Some(table) = info.table {
+					known.insert((table, info.column.to_lowercase()));
+				}
+			}
+		}
+		let names: Vec<&str> = tables.iter().map(|table| table.table.as_str()).collect();
+		let db_tables = read_columns(&names, connection).await?;
+		let (indexes, foreign_keys) = read_keys(&names, connection).await?;
+		let mut up = Vec::new();
+		let mut down = Vec::new();
+		for table in &tables {
+			let name = &table.table;
+			let Some(db_columns) = db_tables.get(name) else {
+				up.push(table.create_sql());
+				down.push(format!("DROP TABLE {};", name));
+				continue;
+			};
+			for column in &table.columns {
+				match db_columns.get(&column.name.to_lowercase()) {
+					None => {
+						up.push(format!("ALTER TABLE {} ADD COLUMN {};", name, column.definition()));
+						down.push(format!("ALTER TABLE {} DROP COLUMN {};", name, column.name));
+					}
+					Some(db_column) => {
+						// A type the models can read, e.g. TEXT for a String, is kept rather than narrowed.
+						let modified = if !compatible_types(&column.sql_type, &db_column.data_type, &db_column.column_type) {
+							column.clone()
+						} else if column.nullable != db_column.nullable {
+							ColumnDef { sql_type: db_column.column_type.clone(), ..column.clone() }
+						} else {
+							continue;
+						};
+						up.push(format!("ALTER TABLE {} MODIFY COLUMN {};", name, modified.definition()));
+						down.push(format!("ALTER TABLE {} MODIFY COLUMN {} {}{};", name, column.name, db_column.column_type, if db_column.nullable { " NULL" } else { " NOT NULL" }));
+					}
+				}
+			}
+			let mut db_column_names: Vec<&String> = db_columns.keys().collect();
+			db_column_names.sort();
+			for column in db_column_names {
+				if !table.columns.iter().any(|c| c.name.eq_ignore_ascii_case(column)) && !known.contains(&(name.as_str(), column.clone())) {
+					up.push(format!("-- ALTER TABLE {} DROP COLUMN {};", name, column));
+				}
+			}
+			let has_key = |keys: &KeyColumns, column: &str| keys.contains(&(name.clone(), column.to_lowercase()));
+			for fk in table.foreign_keys.iter().filter(|fk| !has_key(&foreign_keys, &fk.column)) {
+				if !has_key(&indexes, &fk.column) {
+					up.push(format!("ALTER TABLE {} ADD INDEX {} ({});", name, table.index_name(&fk.column), fk.column));
+					down.push(format!("ALTER TABLE {} DROP INDEX {};", name, table.index_name(&fk.column)));
+				}
+				let constraint = table.foreign_key_name(&fk.column);
+				up.push(format!("ALTER TABLE {} ADD CONSTRAINT {} FOREIGN KEY ({}) REFERENCES {}({});", name, constraint, fk.column, fk.table, fk.pk));
+				down.push(format!("ALTER TABLE {} DROP FOREIGN KEY {};", name, constraint));
+			}
+			for column in table.columns.iter().filter(|column| column.index && !has_key(&indexes, &column.name)) {
+				up.push(format!("ALTER TABLE {} ADD INDEX {} ({});", name, table.index_name(&column.name), column.name));
+				down.push(format!("ALTER TABLE {} DROP INDEX {};", name, table.index_name(&column.name)));
+			}
+		}
+		down.reverse();
+		Ok(SchemaDiff { up, down })
+	}
+}
+
+/// Statements bringing the database in line with the models, and back.
+/// Columns that no model reads are not dropped: their DROP COLUMN is emitted commented out
+/// in `up`, to be enabled by whoever reviews the migration.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SchemaDiff {
+	pub up: Vec<String>,
+	/// In reverse order of `up`. Dropped or modified columns are restored with their type and
+	/// nullability only.
+	pub down: Vec<String>,
+}
+
+impl SchemaDiff {
+	/// Whether `up` has nothing but commented out statements.
+	pub fn is_empty(&self) -> bool {
+		self.up.iter().all(|statement| statement.starts_with("--"))
+	}
+}
+
+/// Checks the tables, columns, joins and relation foreign keys of `T` and of its relations
+/// against `information_schema.COLUMNS` of the current database.
+pub async fn verify_schema<T: DbSchema>(connection: &mut impl QueryableConn) -> Result<SchemaReport, DbError> {
+	verify_models(&[T::model_info], connection).await
+}
+
+struct DbColumn {
+	data_type: String,
+	column_type: String,
+	nullable: bool,
+}
+
+/// The models and, breadth first, their relations, each once.
+fn collect_models(roots: &[fn() -> &'static ModelInfo]) -> Vec<&'static ModelInfo> {
+	let mut models: Vec<&'static ModelInfo> = Vec::new();
+	let mut pending: VecDeque<&'static ModelInfo> = roots.iter().map(|model| model()).collect();
+	while let Some(model) = pending.pop_front() {
+		if models.iter().any(|m| std::ptr::eq(*m, model)) {
+			continue;
+		}
+		pending.extend(model.relations.iter().map(|relation| (relation.model)()));
+		models.push(model);
+	}
+	models
+}
+
+async fn verify_models(roots: &[fn() -> &'static ModelInfo], connection: &mut impl QueryableConn) -> Result<SchemaReport, DbError> {
+	let models = collect_models(roots);
+	let mut tables: Vec<&'static str> = Vec::new();
+	for model in &models {
+		let model_tables = [model.table].into_iter()
+			.chain(model.joins.iter().map(|join| join.table))
+			.chain(model.columns.iter().filter_map(|column| column.table));
+		for table in model_tables {
+			if !tables.contains(&table) {
+				tables.push(table);
+			}
+		}
+	}
+	let db_tables = read_columns(&tables, connection).await?;
+	let mut issues = Vec::new();
+	let mut missing_tables = HashSet::new();
+	for model in &models {
+		for table in [model.table].into_iter().chain(model.joins.iter().map(|join| join.table)) {
+			if !db_tables.contains_key(table) && missing_tables.insert((model.name, table)) {
+				issues.push(SchemaIssue::MissingTable { model: model.name, table });
+			}
+		}
+		for info in [&model.pk].into_iter().chain(model.columns.iter()) {
+			let Some(table) = info.table else { continue };
+			let Some(db_columns) = db_tables.get(table) else {
+				if missing_tables.insert((model.name, table)) {
+					issues.push(SchemaIssue::MissingTable { model: model.name, table });
+				}
+				continue;
+			};
+			let Some(db_column) = db_columns.get(&info.column.to_lowercase()) else {
+				issues.push(SchemaIssue::MissingColumn { model: model.name, field: info.field, table, column: info.column });
+				continue;
+			};
+			if let Some(expected) = &info.sql_type {
+				if !compatible_types(expected, &db_column.data_type, &db_column.column_type) {
+					issues.push(SchemaIssue::TypeMismatch {
+						model: model.name,
+						field: info.field,
+						table,
+						column: info.column,
+						expected: expected.clone(),
+						found: db_column.column_type.clone(),
+					});
+				}
+			}
+			if db_column.nullable && !info.optional && !std::ptr::eq(info, &model.pk) {
+				issues.push(SchemaIssue::Nullable { model: model.name, field: info.field, table, column: info.column });
+			}
+		}
+		for relation in &model.relations {
+			let child = (relation.model)();
+			if let Some(db_columns) = db_tables.get(child.table) {
+				if !db_columns.contains_key(&relation.fk.to_lowercase()) {
+					issues.push(SchemaIssue::MissingForeignKey { model: model.name, field: relation.field, table: child.table, column: relation.fk });
+				}
+			}
+		}
+	}
+	Ok(SchemaReport {
+		models: models.iter().map(|model| model.name).collect(),
+		tables,
+		issues,
+	})
+}
+
+async fn read_columns(tables: &[&str], connection: &mut impl QueryableConn) -> Result<HashMap<String, HashMap<String, DbColumn>>, DbError> {
+	if tables.is_empty() {
+		return Ok(HashMap::new());
+	}
+	let sql = format!(
+		"SELECT TABLE_NAME, COLUMN_NAME, DATA_TYPE, COLUMN_TYPE, IS_NULLABLE FROM information_schema.COLUMNS WHERE TABLE_SCHEMA = DATABASE() AND TABLE_NAME IN ({})",
+		vec!["?"; tables.len()].join(",")
+	);
+	let params: Vec<Value> = tables.iter().map(|table| Value::from(*table)).collect();
+	let rows: Vec<(String, String, String, String, String)> = connection.exec(sql, params).await?;
+	let mut res: HashMap<String, HashMap<String, DbColumn>> = HashMap::new();
+	for (table, column, data_type, column_type, nullable) in rows {
+		res.entry(table).or_default().insert(column.to_lowercase(), DbColumn {
+			data_type: data_type.to_lowercase(),
+			column_type: column_type.to_lowercase(),
+			nullable: nullable == "YES",
+		});
+	}
+	Ok(res)
+}
+
+/// (table, lowercase column) pairs.
+type KeyColumns = HashSet<(String, String)>;
+
+/// The first column of every index and the columns with a foreign key.
+async fn read_keys(tables: &[&str], connection: &mut impl QueryableConn) -> Result<(KeyColumns, KeyColumns), DbError> {
+	if tables.is_empty() {
+		return Ok((KeyColumns::new(), KeyColumns::new()));
+	}
+	let placeholders = vec!["?"; tables.len()].join(",");
+	let params: Vec<Value> = tables.iter().map(|table| Value::from(*table)).collect();
+	let indexes: Vec<(String, String)> = connection.exec(format!(
+		"SELECT TABLE_NAME, COLUMN_NAME FROM information_schema.STATISTICS WHERE TABLE_SCHEMA = DATABASE() AND SEQ_IN_INDEX = 1 AND TABLE_NAME IN ({})",
+		placeholders
+	), params.clone()).await?;
+	let foreign_keys: Vec<(String, String)> = connection.exec(format!(
+		"SELECT TABLE_NAME, COLUMN_NAME FROM information_schema.KEY_COLUMN_USAGE WHERE TABLE_SCHEMA = DATABASE() AND REFERENCED_TABLE_NAME IS NOT NULL AND TABLE_NAME IN ({})",
+		placeholders
+	), params).await?;
+	let lowercase = |keys: Vec<(String, String)>| keys.into_iter().map(|(table, column)| (table, column.to_lowercase())).collect();
+	Ok((lowercase(indexes), lowercase(foreign_keys)))
+}
+
+fn type_family(data_type: &str) -> &str {
+	match data_type {
+		"tinyint" | "smallint" | "mediumint" | "int" | "integer" | "bigint" | "bit" | "year" | "bool" | "boolean" => "integer",
+		"float" | "double" | "real" => "float",
+		"decimal" | "numeric" => "decimal",
+		"char" | "varchar" | "tinytext" | "text" | "mediumtext" | "longtext" | "enum" | "set" => "string",
+		"binary" | "varbinary" | "tinyblob" | "blob" | "mediumblob" | "longblob" => "binary",
+		"datetime" | "timestamp" => "datetime",
+		other => other,
+	}
+}
+
+fn is_integer_sql_type(sql_type: &str) -> bool {
+	let name = sql_type.split(|c: char| c == '(' || c.is_whitespace()).next().unwrap_or_default().to_lowercase();
+	matches!(name.as_str(), "tinyint" | "smallint" | "mediumint" | "int" | "integer" | "bigint")
+}
+
+/// Compares the type `create_table_sql()` would use with the `DATA_TYPE` and `COLUMN_TYPE` found in the
+/// database; numbers must also agree on `UNSIGNED`.
+fn compatible_types(expected: &str, data_type: &str, column_type: &str) -> bool {
+	let expected = expected.to_lowercase();
+	let expected_unsigned = expected.contains("unsigned");
+	let expected = expected.split(|c: char| c == '(' || c.is_whitespace()).next().unwrap_or_default();
+	match (type_family(expected), type_family(data_type)) {
+		("string", "binary" | "json") => true,
+		(family @ ("integer" | "float" | "decimal"), found) => {
+			family == found && expected_unsigned == column_type.to_lowercase().contains("unsigned")
+		}
+		(expected, found) => expected == found,
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{column_sql_type, compatible_types, ColumnDdl, ColumnDef, ForeignKey, SqlType, TableDef};
+	use crate::db_date::{DBDate, DBDateTime, DBTime, DBTimestamp};
+
+	#[test]
+	fn types_match_by_family() {
+		assert!(compatible_types("VARCHAR(255)", "text", "text"));
+		assert!(compatible_types("INT UNSIGNED", "bigint", "bigint unsigned"));
+		assert!(compatible_types("DATETIME", "timestamp", "timestamp"));
+		assert!(compatible_types("JSON", "json", "json"));
+		assert!(!compatible_types("INT", "varchar", "varchar(10)"));
+		assert!(!compatible_types("DATE", "datetime", "datetime"));
+	}
+
+	#[test]
+	fn strings_accept_binary_and_json_columns() {
+		assert!(compatible_types("VARCHAR(255)", "blob", "blob"));
+		assert!(compatible_types("TEXT", "json", "json"));
+		assert!(!compatible_types("BLOB", "varchar", "varchar(255)"));
+	}
+
+	#[test]
+	fn numbers_must_agree_on_signedness() {
+		assert!(compatible_types("INT", "int", "int"));
+		assert!(compatible_types("INT UNSIGNED", "int", "int unsigned"));
+		assert!(!compatible_types("INT", "int", "int unsigned"));
+		assert!(!compatible_types("BIGINT UNSIGNED", "bigint", "bigint"));
+		assert!(!compatible_types("DOUBLE", "double", "double unsigned"));
+	}
+
+	#[test]
+	fn decimals_are_not_floats() {
+		assert!(compatible_types("DECIMAL(10,2)", "decimal", "decimal(10,2)"));
+		assert!(compatible_types("DECIMAL(10,2)", "numeric", "decimal(12,4)"));
+		assert!(!compatible_types("DOUBLE", "decimal", "decimal(10,2)"));
+		assert!(!compatible_types("DECIMAL(10,2)", "float", "float"));
+		assert!(compatible_types("FLOAT", "double", "double"));
+	}
+
+	#[test]
+	fn integer_pks_keep_auto_increment() {
+		let ddl = ColumnDdl { sql_type: Some("MEDIUMINT UNSIGNED"), ..Default::default() };
+		assert_eq!(ColumnDef::pk::<u32>("id", &ddl).definition(), "id MEDIUMINT UNSIGNED NOT NULL AUTO_INCREMENT");
+		let ddl = ColumnDdl { sql_type: Some("CHAR(8)"), ..Default::default() };
+		assert_eq!(ColumnDef::pk::<u32>("id", &ddl).definition(), "id CHAR(8) NOT NULL");
+		assert_eq!(ColumnDef::pk::<u32>("id", &ColumnDdl::default()).definition(), "id INT UNSIGNED NOT NULL AUTO_INCREMENT");
+	}
+
+	#[test]
+	fn rust_types_map_to_sql_types() {
+		let ddl = ColumnDdl::default();
+		assert_eq!(i8::sql_type(&ddl), "TINYINT");
+		assert_eq!(u64::sql_type(&ddl), "BIGINT UNSIGNED");
+		assert_eq!(f32::sql_type(&ddl), "FLOAT");
+		assert_eq!(f64::sql_type(&ddl), "DOUBLE");
+		assert_eq!(bool::sql_type(&ddl), "TINYINT(1)");
+		assert_eq!(String::sql_type(&ddl), "VARCHAR(255)");
+		assert_eq!(Vec::<u8>::sql_type(&ddl), "VARBINARY(255)");
+		assert_eq!(DBDate::sql_type(&ddl), "DATE");
+		assert_eq!(DBTime::sql_type(&ddl), "TIME");
+		assert_eq!(DBDateTime::sql_type(&ddl), "DATETIME");
+		assert_eq!(Option::<DBTimestamp>::sql_type(&ddl), "TIMESTAMP");
+		assert!(ColumnDef::new::<Option<u32>>("n", &ddl).nullable);
+		assert!(!ColumnDef::new::<u32>("n", &ddl).nullable);
+		assert!(!ColumnDef::pk::<String>("id", &ddl).auto_increment);
+	}
+
+	#[test]
+	fn ddl_options_refine_the_sql_type() {
+		let ddl = ColumnDdl { length: Some(40), precision: Some(3), scale: Some(1), ..Default::default() };
+		assert_eq!(String::sql_type(&ddl), "VARCHAR(40)");
+		assert_eq!(Vec::<u8>::sql_type(&ddl), "VARBINARY(40)");
+		assert_eq!(f64::sql_type(&ddl), "DECIMAL(3,1)");
+		assert_eq!(DBDateTime::sql_type(&ddl), "DATETIME(3)");
+		assert_eq!(DBDate::sql_type(&ddl), "DATE");
+		let ddl = ColumnDdl { sql_type: Some("MEDIUMTEXT"), ..Default::default() };
+		assert_eq!(column_sql_type::<String>(&ddl), "MEDIUMTEXT");
+	}
+
+	#[test]
+	fn create_sql_lists_columns_indexes_and_foreign_keys() {
+		let columns = vec![
+			ColumnDef::pk::<u32>("id", &ColumnDdl::default()),
+			ColumnDef::new::<String>("codice", &ColumnDdl { length: Some(20), index: true, ..Default::default() }),
+			ColumnDef::new::<Option<f64>>("qta", &ColumnDdl { default: Some("0"), ..Default::default() }),
+		];
+		let fk = ForeignKey { column: "id_ordine", table: "ordini", pk: "id", sql_type: "INT UNSIGNED" };
+		let table = TableDef::new("righe", columns, Some(&fk));
+		assert_eq!(table.create_sql(), "CREATE TABLE righe (
+	id INT UNSIGNED NOT NULL AUTO_INCREMENT PRIMARY KEY,
+	id_ordine INT UNSIGNED NOT NULL,
+	codice VARCHAR(20) NOT NULL,
+	qta DOUBLE NULL DEFAULT 0,
+	INDEX righe_codice_idx (codice),
+	CONSTRAINT righe_id_ordine_fk FOREIGN KEY (id_ordine) REFERENCES ordini(id)
+);");
+	}
+
+	#[test]
+	fn foreign_keys_are_added_once() {
+		let fk = ForeignKey { column: "id_ordine", table: "ordini", pk: "id", sql_type: "INT UNSIGNED" };
+		let mut table = TableDef::new("righe", vec![ColumnDef::pk::<u32>("id", &ColumnDdl::default())], Some(&fk));
+		table.add_foreign_key(&fk);
+		assert_eq!(table.columns.len(), 2);
+		assert_eq!(table.foreign_keys.len(), 1);
+	}
+
+	#[cfg(feature = "rust_decimal")]
+	#[test]
+	fn decimals_take_precision_and_scale() {
+		use rust_decimal::Decimal;
+
+		assert_eq!(Decimal::sql_type(&ColumnDdl::default()), "DECIMAL(10,0)");
+		let ddl = ColumnDdl { precision: Some(12), scale: Some(4), ..Default::default() };
+		assert_eq!(ColumnDef::new::<Decimal>("imponibile", &ddl).definition(), "imponibile DECIMAL(12,4) NOT NULL");
+		assert_eq!(ColumnDef::new::<Option<Decimal>>("sconto", &ColumnDdl { scale: Some(2), ..Default::default() }).definition(), "sconto DECIMAL(10,2) NULL");
+		assert!(compatible_types(&Decimal::sql_type(&ddl), "decimal", "decimal(12,4)"));
+		assert!(!compatible_types(&Decimal::sql_type(&ddl), "double", "double"));
+	}
+}
@@ -0,0 +1,116 @@
+use std::path::Path;
+
+use crate::db_date::{DBDate, DBDateTime, DBTime, DBTimestamp};
+
+/// The TypeScript type of a field of a `#[fe_export]` model.
+pub trait TsType {
+	fn ts_type() -> String;
+	/// Adds the exported models this type refers to.
+	fn export_dependencies(_: &mut TsExporter) {}
+}
+
+/// Implemented by `#[derive(DbModel)]` for models marked `#[fe_export]`.
+pub trait TsExport: TsType {
+	const NAME: &'static str;
+	fn ts_interface() -> String;
+}
+
+macro_rules! ts_type {
+	($ts:literal: $($ty:ty),*) => {
+		$(
+			impl TsType for $ty {
+				fn ts_type() -> String {
+					$ts.to_string()
+				}
+			}
+		)*
+	};
+}
+
+ts_type!("number": i8, u8, i16, u16, i32, u32, i64, u64, isize, usize, f32, f64);
+ts_type!("boolean": bool);
+ts_type!("string": String, DBDate, DBTime, DBDateTime, DBTimestamp);
+#[cfg(feature = "rust_decimal")]
+ts_type!("string": rust_decimal::Decimal);
+#[cfg(feature = "chrono")]
+ts_type!("string": chrono::NaiveDate, chrono::NaiveTime, chrono::NaiveDateTime);
+#[cfg(feature = "time")]
+ts_type!("string": time::Date, time::Time, time::PrimitiveDateTime);
+
+impl TsType for Vec<u8> {
+	fn ts_type() -> String {
+		"number[]".to_string()
+	}
+}
+
+impl<T: TsType> TsType for Option<T> {
+	fn ts_type() -> String {
+		format!("{} | null", T::ts_type())
+	}
+
+	fn export_dependencies(exporter: &mut TsExporter) {
+		T::export_dependencies(exporter);
+	}
+}
+
+impl<T: TsExport> TsType for Vec<T> {
+	fn ts_type() -> String {
+		format!("{}[]", T::ts_type())
+	}
+
+	fn export_dependencies(exporter: &mut TsExporter) {
+		exporter.export::<T>();
+	}
+}
+
+/// Appends a `name: type;` line to an interface body.
+pub fn ts_field(interface: &mut String, name: &str, ts_type: &str, readonly: bool) {
+	interface.push('\t');
+	if readonly {
+		interface.push_str("readonly ");
+	}
+	interface.push_str(name);
+	interface.push_str(": ");
+	interface.push_str(ts_type);
+	interface.push_str(";\n");
+}
+
+/// Collects the interfaces of the exported models and of their relations into one file.
+#[derive(Debug, Default)]
+pub struct TsExporter {
+	interfaces: Vec<(&'static str, String)>,
+}
+
+impl TsExporter {
+	pub fn new() -> Self {
+		TsExporter::default()
+	}
+
+	pub fn export<T: TsExport>(&mut self) -> &mut Self {
+		if !self.interfaces.iter().any(|(name, _)| *name == T::NAME) {
+			self.interfaces.push((T::NAME, T::ts_interface()));
+			T::export_dependencies(self);
+		}
+		self
+	}
+
+	pub fn source(&self) -> String {
+		let mut res = String::from("// Generated by mysql_async_orm from the #[fe_export] models, do not edit.\n");
+		for (_, interface) in &self.interfaces {
+			res.push('\n');
+			res.push_str(interface);
+		}
+		res
+	}
+
+	/// Writes `source()` to `path` unless the file already has that content, so that watchers
+	/// of the frontend are not triggered needlessly. Returns whether the file was written.
+	pub fn write<P: AsRef<Path>>(&self, path: P) -> std::io::Result<bool> {
+		let source = self.source();
+		if std::fs::read_to_string(&path).is_ok_and(|current| current == source) {
+			return Ok(false);
+		}
+		std::fs::write(path, source)?;
+		Ok(true)
+	}
+}
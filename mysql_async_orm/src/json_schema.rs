@@ -0,0 +1,251 @@
+use serde_json::{json, Map, Value};
+
+use crate::{
+	db_date::{DBDate, DBDateTime, DBTime, DBTimestamp},
+	db_schema::ColumnDdl,
+};
+
+pub const DRAFT: &str = "https://json-schema.org/draft/2020-12/schema";
+
+/// The JSON Schema of a field of a `DbModel`.
+pub trait JsonSchemaType {
+	/// Whether the field accepts `null`, so it is not required.
+	const NULLABLE: bool = false;
+	fn json_schema(ddl: &ColumnDdl) -> Value;
+	/// Adds the definitions of the models this type refers to.
+	fn add_definitions(_: &mut Map<String, Value>) {}
+}
+
+/// Implemented by `#[derive(DbModel)]` for models marked `#[json_schema]`; models are referenced as `#/$defs/<name>`.
+pub trait JsonSchemaModel: JsonSchemaType {
+	const NAME: &'static str;
+	fn json_schema_definition() -> Value;
+}
+
+/// A standalone document for `T`, with `T` and every related model under `$defs`.
+pub fn schema_for<T: JsonSchemaModel>() -> Value {
+	let mut definitions = Map::new();
+	add_model_definition::<T>(&mut definitions);
+	json!({
+		"$schema": DRAFT,
+		"$ref": format!("#/$defs/{}", T::NAME),
+		"$defs": definitions,
+	})
+}
+
+pub fn add_model_definition<T: JsonSchemaModel>(definitions: &mut Map<String, Value>) {
+	if !definitions.contains_key(T::NAME) {
+		definitions.insert(T::NAME.to_string(), T::json_schema_definition());
+		T::add_definitions(definitions);
+	}
+}
+
+pub struct JsonProperty {
+	pub name: &'static str,
+	pub schema: Value,
+	pub required: bool,
+	pub read_only: bool,
+}
+
+pub fn model_schema(title: &str, properties: Vec<JsonProperty>) -> Value {
+	let required: Vec<&str> = properties.iter().filter(|p| p.required).map(|p| p.name).collect();
+	let properties: Map<String, Value> = properties.into_iter().map(|p| {
+		let mut schema = p.schema;
+		if p.read_only {
+			if let Value::Object(schema) = &mut schema {
+				schema.insert("readOnly".to_string(), Value::Bool(true));
+			}
+		}
+		(p.name.to_string(), schema)
+	}).collect();
+	json!({
+		"title": title,
+		"type": "object",
+		"properties": properties,
+		"required": required,
+		"additionalProperties": false,
+	})
+}
+
+macro_rules! integer_json_schema {
+	($($ty:ty),*) => {
+		$(
+			impl JsonSchemaType for $ty {
+				fn json_schema(_: &ColumnDdl) -> Value {
+					json!({ "type": "integer", "minimum": <$ty>::MIN, "maximum": <$ty>::MAX })
+				}
+			}
+		)*
+	};
+}
+
+integer_json_schema!(i8, u8, i16, u16, i32, u32, i64, u64, isize, usize);
+
+impl JsonSchemaType for f32 {
+	fn json_schema(_: &ColumnDdl) -> Value {
+		json!({ "type": "number" })
+	}
+}
+
+impl JsonSchemaType for f64 {
+	fn json_schema(_: &ColumnDdl) -> Value {
+		json!({ "type": "number" })
+	}
+}
+
+/// A string, as `rust_decimal` serializes it, limited to the digits of the column.
+#[cfg(feature = "rust_decimal")]
+impl JsonSchemaType for rust_decimal::Decimal {
+	fn json_schema(ddl: &ColumnDdl) -> Value {
+		let precision = ddl.precision.unwrap_or(10);
+		let scale = ddl.scale.unwrap_or(0);
+		let pattern = match scale {
+			0 => format!(r"^-?\d{{1,{}}}$", precision),
+			_ => format!(r"^-?\d{{1,{}}}(\.\d{{1,{}}})?$", precision.saturating_sub(scale).max(1), scale),
+		};
+		json!({ "type": "string", "pattern": pattern })
+	}
+}
+
+impl JsonSchemaType for bool {
+	fn json_schema(_: &ColumnDdl) -> Value {
+		json!({ "type": "boolean" })
+	}
+}
+
+/// The length given with `#[ddl(length)]`, or the `n` of a `#[ddl(sql_type)]` among `types`,
+/// e.g. `VARCHAR(n)`. The column may be a TEXT otherwise, so no length is assumed.
+fn declared_length(ddl: &ColumnDdl, types: &[&str]) -> Option<u32> {
+	if ddl.length.is_some() {
+		return ddl.length;
+	}
+	let sql_type = ddl.sql_type?.trim().to_lowercase();
+	let (name, length) = sql_type.split_once('(')?;
+	if !types.contains(&name.trim()) {
+		return None;
+	}
+	length.split_once(')')?.0.trim().parse().ok()
+}
+
+impl JsonSchemaType for String {
+	fn json_schema(ddl: &ColumnDdl) -> Value {
+		match declared_length(ddl, &["varchar", "char"]) {
+			Some(length) => json!({ "type": "string", "maxLength": length }),
+			None => json!({ "type": "string" }),
+		}
+	}
+}
+
+impl JsonSchemaType for Vec<u8> {
+	fn json_schema(ddl: &ColumnDdl) -> Value {
+		let mut schema = json!({ "type": "array", "items": u8::json_schema(ddl) });
+		if let Some(length) = declared_length(ddl, &["varbinary", "binary"]) {
+			schema["maxItems"] = json!(length);
+		}
+		schema
+	}
+}
+
+/// A pattern rather than `"format": "date"`, which would reject the zero date `0000-00-00`.
+impl JsonSchemaType for DBDate {
+	fn json_schema(_: &ColumnDdl) -> Value {
+		json!({ "type": "string", "pattern": r"^\d{4}-\d{2}-\d{2}$" })
+	}
+}
+
+impl JsonSchemaType for DBTime {
+	fn json_schema(_: &ColumnDdl) -> Value {
+		json!({ "type": "string", "pattern": r"^-?\d{2,3}:\d{2}:\d{2}(\.\d{1,6})?$" })
+	}
+}
+
+impl JsonSchemaType for DBDateTime {
+	fn json_schema(_: &ColumnDdl) -> Value {
+		json!({ "type": "string", "pattern": r"^\d{4}-\d{2}-\d{2}[T ]\d{2}:\d{2}:\d{2}(\.\d{1,6})?$" })
+	}
+}
+
+impl JsonSchemaType for DBTimestamp {
+	fn json_schema(_: &ColumnDdl) -> Value {
+		json!({ "type": "string", "format": "date-time" })
+	}
+}
+
+#[allow(unused_macros)]
+macro_rules! same_json_schema {
+	($($ty:ty => $as:ty),* $(,)?) => {
+		$(
+			impl JsonSchemaType for $ty {
+				fn json_schema(ddl: &ColumnDdl) -> Value {
+					<$as>::json_schema(ddl)
+				}
+			}
+		)*
+	};
+}
+
+#[cfg(feature = "chrono")]
+same_json_schema! {
+	chrono::NaiveDate => DBDate,
+	chrono::NaiveTime => DBTime,
+	chrono::NaiveDateTime => DBDateTime,
+}
+
+#[cfg(feature = "time")]
+same_json_schema! {
+	time::Date => DBDate,
+	time::Time => DBTime,
+	time::PrimitiveDateTime => DBDateTime,
+}
+
+impl<T: JsonSchemaType> JsonSchemaType for Option<T> {
+	const NULLABLE: bool = true;
+	fn json_schema(ddl: &ColumnDdl) -> Value {
+		let schema = T::json_schema(ddl);
+		match &schema["type"] {
+			Value::String(ty) if schema.get("$ref").is_none() => {
+				let mut schema = schema.clone();
+				schema["type"] = json!([ty, "null"]);
+				if let Some(Value::Array(members)) = schema.get_mut("enum") {
+					members.push(Value::Null);
+				}
+				schema
+			}
+			_ => json!({ "anyOf": [schema, { "type": "null" }] }),
+		}
+	}
+
+	fn add_definitions(definitions: &mut Map<String, Value>) {
+		T::add_definitions(definitions);
+	}
+}
+
+impl<T: JsonSchemaModel> JsonSchemaType for Vec<T> {
+	fn json_schema(ddl: &ColumnDdl) -> Value {
+		json!({ "type": "array", "items": T::json_schema(ddl) })
+	}
+
+	fn add_definitions(definitions: &mut Map<String, Value>) {
+		add_model_definition::<T>(definitions);
+	}
+}
+
+#[cfg(all(test, feature = "rust_decimal"))]
+mod tests {
+	use rust_decimal::Decimal;
+	use serde_json::json;
+
+	use super::JsonSchemaType;
+	use crate::db_schema::ColumnDdl;
+
+	fn ddl(precision: Option<u32>, scale: Option<u32>) -> ColumnDdl {
+		ColumnDdl { precision, scale, ..Default::default() }
+	}
+
+	#[test]
+	fn decimals_are_strings_limited_to_the_column_digits() {
+		assert_eq!(Decimal::json_schema(&ddl(None, None)), json!({ "type": "string", "pattern": r"^-?\d{1,10}$" }));
+		assert_eq!(Decimal::json_schema(&ddl(Some(12), Some(4))), json!({ "type": "string", "pattern": r"^-?\d{1,8}(\.\d{1,4})?$" }));
+		assert_eq!(Decimal::json_schema(&ddl(Some(4), Some(4))), json!({ "type": "string", "pattern": r"^-?\d{1,1}(\.\d{1,4})?$" }));
+	}
+}
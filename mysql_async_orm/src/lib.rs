@@ -1,7 +1,21 @@
 pub use mysql_async_orm_proc_macros::*;
 pub use lazy_static::*;
 pub extern crate mysql_async;
+pub extern crate futures_util;
+pub extern crate serde_json;
+#[cfg(feature = "rust_decimal")]
+pub extern crate rust_decimal;
 
 pub mod db_connection;
 pub mod db_model;
-pub mod db_date;
\ No newline at end of file
+pub mod db_date;
+pub mod db_enum;
+#[cfg(feature = "serde")]
+pub mod db_json;
+pub mod db_query;
+pub mod db_schema;
+pub mod migrations;
+pub mod fe_export;
+pub mod json_schema;
+#[cfg(feature = "mock")]
+pub mod db_mock;
\ No newline at end of file
@@ -0,0 +1,482 @@
+use std::{
+	fmt,
+	path::{Path, PathBuf},
+};
+
+use sha2::{Digest, Sha256};
+
+use crate::{
+	db_connection::{DbError, QueryableConn},
+	db_schema::{ModelRegistry, SchemaDiff},
+};
+
+pub const MIGRATIONS_TABLE: &str = "_orm_migrations";
+
+/// First line of a migration script that must not run inside a transaction.
+///
+/// Such a migration is recorded only after its script succeeded: if it fails halfway, the
+/// statements already executed stay applied but the migration is not recorded, so the
+/// database must be fixed by hand before running `migrate` again.
+pub const NO_TRANSACTION_MARKER: &str = "-- orm:no-transaction";
+
+#[derive(Debug)]
+pub enum MigrationError {
+	Io { path: PathBuf, error: std::io::Error },
+	/// Migration files must be named `<version>_<name>.up.sql`, `<version>_<name>.down.sql`
+	/// or `<version>_<name>.sql`.
+	InvalidFileName(PathBuf),
+	DuplicateVersion(u64),
+	/// A down script without the matching up script.
+	MissingUp(u64),
+	MissingDown { version: u64, name: String },
+	/// The up script of an applied migration was modified.
+	ChecksumMismatch { version: u64, name: String },
+	/// An applied migration is not among the known ones.
+	UnknownVersion { version: u64, name: String },
+	/// Another connection kept the migrations lock for longer than the lock timeout.
+	Locked,
+}
+
+impl fmt::Display for MigrationError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			MigrationError::Io { path, error } => write!(f, "Cannot read {}: {}", path.display(), error),
+			MigrationError::InvalidFileName(path) => write!(f, "Invalid migration file name {}", path.display()),
+			MigrationError::DuplicateVersion(version) => write!(f, "Duplicate migration version {}", version),
+			MigrationError::MissingUp(version) => write!(f, "Migration {} has a down script but no up script", version),
+			MigrationError::MissingDown { version, name } => write!(f, "Migration {} {} has no down script", version, name),
+			MigrationError::ChecksumMismatch { version, name } => write!(f, "Migration {} {} was modified after being applied", version, name),
+			MigrationError::UnknownVersion { version, name } => write!(f, "Applied migration {} {} is unknown", version, name),
+			MigrationError::Locked => write!(f, "Migrations are being applied by another connection"),
+		}
+	}
+}
+
+impl std::error::Error for MigrationError {
+	fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+		match self {
+			MigrationError::Io { error, .. } => Some(error),
+			_ => None,
+		}
+	}
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Migration {
+	pub version: u64,
+	pub name: String,
+	pub up: String,
+	pub down: Option<String>,
+	/// MySQL commits implicitly after DDL statements, so a transaction only protects DML.
+	pub transactional: bool,
+}
+
+impl Migration {
+	pub fn new<N: Into<String>, S: Into<String>>(version: u64, name: N, up: S) -> Self {
+		let up = up.into();
+		Migration {
+			version,
+			name: name.into(),
+			transactional: !up.trim_start().starts_with(NO_TRANSACTION_MARKER),
+			up,
+			down: None,
+		}
+	}
+
+	pub fn with_down<S: Into<String>>(mut self, down: S) -> Self {
+		self.down = Some(down.into());
+		self
+	}
+
+	/// Runs the scripts without a transaction, see `NO_TRANSACTION_MARKER` for what a failure leaves behind.
+	pub fn non_transactional(mut self) -> Self {
+		self.transactional = false;
+		self
+	}
+
+	/// Hex SHA-256 of the up script, stored when the migration is applied.
+	pub fn checksum(&self) -> String {
+		Sha256::digest(self.up.as_bytes()).iter().map(|b| format!("{:02x}", b)).collect()
+	}
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AppliedMigration {
+	pub version: u64,
+	pub name: String,
+	pub checksum: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MigrationStatus {
+	pub version: u64,
+	pub name: String,
+	pub applied: bool,
+}
+
+/// Ordered set of migrations applied to a database and tracked in `_orm_migrations`.
+///
+/// `migrate` and `rollback` hold the MySQL named lock `_orm_migrations` while they run, so
+/// concurrent processes apply every migration once.
+#[derive(Debug, Clone)]
+pub struct Migrator {
+	migrations: Vec<Migration>,
+	lock_timeout: u32,
+}
+
+impl Default for Migrator {
+	fn default() -> Self {
+		Migrator { migrations: Vec::new(), lock_timeout: 60 }
+	}
+}
+
+impl Migrator {
+	pub fn new() -> Self {
+		Migrator::default()
+	}
+
+	/// Seconds `migrate` and `rollback` wait for the migrations lock, 60 by default.
+	pub fn with_lock_timeout(mut self, seconds: u32) -> Self {
+		self.lock_timeout = seconds;
+		self
+	}
+
+	pub fn with_migration(mut self, migration: Migration) -> Self {
+		self.migrations.push(migration);
+		self
+	}
+
+	/// Reads `<version>_<name>.up.sql` / `<version>_<name>.down.sql` (or `<version>_<name>.sql`)
+	/// files from `dir`.
+	pub fn from_dir<P: AsRef<Path>>(dir: P) -> Result<Self, MigrationError> {
+		let dir = dir.as_ref();
+		let io_error = |path: &Path| {
+			let path = path.to_path_buf();
+			move |error| MigrationError::Io { path, error }
+		};
+		let mut ups = Vec::new();
+		let mut downs = Vec::new();
+		for entry in std::fs::read_dir(dir).map_err(io_error(dir))? {
+			let path = entry.map_err(io_error(dir))?.path();
+			let Some(file_name) = path.file_name().and_then(|name| name.to_str()) else { continue };
+			let Some(stem) = file_name.strip_suffix(".sql") else { continue };
+			let (stem, is_down) = if let Some(stem) = stem.strip_suffix(".up") {
+				(stem, false)
+			} else if let Some(stem) = stem.strip_suffix(".down") {
+				(stem, true)
+			} else {
+				(stem, false)
+			};
+			let (version, name) = stem.split_once('_')
+				.and_then(|(version, name)| Some((version.parse::<u64>().ok()?, name)))
+				.ok_or_else(|| MigrationError::InvalidFileName(path.clone()))?;
+			let script = std::fs::read_to_string(&path).map_err(io_error(&path))?;
+			if is_down {
+				downs.push((version, script));
+			} else {
+				ups.push(Migration::new(version, name, script));
+			}
+		}
+		for (version, down) in downs {
+			let up = ups.iter_mut().find(|up| up.version == version).ok_or(MigrationError::MissingUp(version))?;
+			up.down = Some(down);
+		}
+		let mut migrator = Migrator { migrations: ups, ..Migrator::default() };
+		migrator.sort()?;
+		Ok(migrator)
+	}
+
+	pub fn migrations(&self) -> &[Migration] {
+		&self.migrations
+	}
+
+	fn sort(&mut self) -> Result<(), MigrationError> {
+		self.migrations.sort_by_key(|migration| migration.version);
+		match self.migrations.windows(2).find(|pair| pair[0].version == pair[1].version) {
+			Some(pair) => Err(MigrationError::DuplicateVersion(pair[0].version)),
+			None => Ok(()),
+		}
+	}
+
+	pub async fn applied(&self, connection: &mut impl QueryableConn) -> Result<Vec<AppliedMigration>, DbError> {
+		connection.query_drop(format!(
+			"CREATE TABLE IF NOT EXISTS {} (\
+				version BIGINT UNSIGNED NOT NULL PRIMARY KEY, \
+				name VARCHAR(255) NOT NULL, \
+				checksum CHAR(64) NOT NULL, \
+				applied_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP\
+			)",
+			MIGRATIONS_TABLE
+		)).await?;
+		let rows: Vec<(u64, String, String)> = connection
+			.query(format!("SELECT version, name, checksum FROM {} ORDER BY version", MIGRATIONS_TABLE))
+			.await?;
+		Ok(rows.into_iter().map(|(version, name, checksum)| AppliedMigration { version, name, checksum }).collect())
+	}
+
+	/// Fails if an applied migration is unknown or was modified.
+	pub async fn status(&self, connection: &mut impl QueryableConn) -> Result<Vec<MigrationStatus>, DbError> {
+		let (migrations, applied) = self.check(connection).await?;
+		Ok(migrations.iter().map(|migration| MigrationStatus {
+			version: migration.version,
+			name: migration.name.clone(),
+			applied: applied.iter().any(|applied| applied.version == migration.version),
+		}).collect())
+	}
+
+	/// Applies the pending migrations in version order and returns their versions.
+	/// Refuses to run if an applied migration is unknown or was modified.
+	///
+	/// Transactional migrations run in the transaction of `QueryableConn::begin_atomic`, so
+	/// directly on a connection that is already a transaction or has auto transactions disabled.
+	pub async fn migrate(&self, connection: &mut impl QueryableConn) -> Result<Vec<u64>, DbError> {
+		self.lock(connection).await?;
+		let result = self.migrate_locked(connection).await;
+		unlock(connection, result).await
+	}
+
+	async fn migrate_locked<C: QueryableConn>(&self, connection: &mut C) -> Result<Vec<u64>, DbError> {
+		let (migrations, applied) = self.check(connection).await?;
+		let mut res = Vec::new();
+		for migration in migrations.iter().filter(|migration| !applied.iter().any(|applied| applied.version == migration.version)) {
+			let record = format!("INSERT INTO {} (version, name, checksum) VALUES (?, ?, ?)", MIGRATIONS_TABLE);
+			let params = (migration.version, migration.name.clone(), migration.checksum());
+			run_script(connection, migration.transactional, &migration.up, &record, params).await?;
+			res.push(migration.version);
+		}
+		Ok(res)
+	}
+
+	/// Runs the down scripts of the last `steps` applied migrations, newest first, and returns
+	/// their versions.
+	pub async fn rollback(&self, connection: &mut impl QueryableConn, steps: usize) -> Result<Vec<u64>, DbError> {
+		self.lock(connection).await?;
+		let result = self.rollback_locked(connection, steps).await;
+		unlock(connection, result).await
+	}
+
+	async fn rollback_locked<C: QueryableConn>(&self, connection: &mut C, steps: usize) -> Result<Vec<u64>, DbError> {
+		let (migrations, applied) = self.check(connection).await?;
+		let mut res = Vec::new();
+		for applied in applied.iter().rev().take(steps) {
+			let migration = migrations.iter().find(|migration| migration.version == applied.version).ok_or_else(|| MigrationError::UnknownVersion {
+				version: applied.version,
+				name: applied.name.clone(),
+			})?;
+			let down = migration.down.as_ref().ok_or_else(|| MigrationError::MissingDown {
+				version: migration.version,
+				name: migration.name.clone(),
+			})?;
+			let record = format!("DELETE FROM {} WHERE version = ?", MIGRATIONS_TABLE);
+			run_script(connection, migration.transactional, down, &record, (migration.version,)).await?;
+			res.push(migration.version);
+		}
+		Ok(res)
+	}
+
+	/// Takes the named lock `_orm_migrations`, waiting at most `lock_timeout` seconds.
+	async fn lock(&self, connection: &mut impl QueryableConn) -> Result<(), DbError> {
+		let locked: Option<Option<i64>> = connection.exec_first("SELECT GET_LOCK(?, ?)", (MIGRATIONS_TABLE, self.lock_timeout)).await?;
+		match locked {
+			Some(Some(1)) => Ok(()),
+			_ => Err(MigrationError::Locked.into()),
+		}
+	}
+
+	async fn check(&self, connection: &mut impl QueryableConn) -> Result<(Vec<Migration>, Vec<AppliedMigration>), DbError> {
+		let mut migrator = self.clone();
+		migrator.sort()?;
+		let applied = self.applied(connection).await?;
+		for applied in &applied {
+			match migrator.migrations.iter().find(|migration| migration.version == applied.version) {
+				None => return Err(MigrationError::UnknownVersion {
+					version: applied.version,
+					name: applied.name.clone(),
+				}.into()),
+				Some(migration) if migration.checksum() != applied.checksum => return Err(MigrationError::ChecksumMismatch {
+					version: applied.version,
+					name: applied.name.clone(),
+				}.into()),
+				Some(_) => {}
+			}
+		}
+		Ok((migrator.migrations, applied))
+	}
+}
+
+/// Writes `diff` as the next migration of `dir`, `<version>_<name>.up.sql` and `.down.sql`,
+/// and returns the path of the up script. Existing files are never overwritten.
+///
+/// The scripts start with `NO_TRANSACTION_MARKER`: DDL commits implicitly, so a transaction
+/// would not undo the statements before a failing one anyway.
+pub fn write_migration<P: AsRef<Path>>(dir: P, name: &str, diff: &SchemaDiff) -> Result<PathBuf, MigrationError> {
+	let dir = dir.as_ref();
+	let version = Migrator::from_dir(dir)?.migrations().last().map_or(1, |migration| migration.version + 1);
+	let header = format!("{}\n-- Generated from the DbModel definitions, review before applying.\n", NO_TRANSACTION_MARKER);
+	let up = dir.join(format!("{:04}_{}.up.sql", version, name));
+	let down = dir.join(format!("{:04}_{}.down.sql", version, name));
+	for (path, statements) in [(&up, &diff.up), (&down, &diff.down)] {
+		let mut script = header.clone();
+		for statement in statements {
+			script.push_str(statement);
+			script.push('\n');
+		}
+		std::fs::OpenOptions::new()
+			.write(true)
+			.create_new(true)
+			.open(path)
+			.and_then(|mut file| std::io::Write::write_all(&mut file, script.as_bytes()))
+			.map_err(|error| MigrationError::Io { path: path.clone(), error })?;
+	}
+	Ok(up)
+}
+
+/// Diffs the registered models against the database and, if anything differs, writes the
+/// ALTER TABLE statements as a new migration of `dir` for review. Nothing is executed.
+pub async fn generate_migration<P: AsRef<Path>>(
+	registry: &ModelRegistry,
+	connection: &mut impl QueryableConn,
+	dir: P,
+	name: &str,
+) -> Result<Option<PathBuf>, DbError> {
+	let diff = registry.diff_schema(connection).await?;
+	if diff.is_empty() {
+		return Ok(None);
+	}
+	Ok(Some(write_migration(dir, name, &diff)?))
+}
+
+/// Releases the lock taken by `Migrator::lock`, returning `result` unless releasing fails.
+async fn unlock<T>(connection: &mut impl QueryableConn, result: Result<T, DbError>) -> Result<T, DbError> {
+	let released = connection.exec_drop("SELECT RELEASE_LOCK(?)", (MIGRATIONS_TABLE,)).await;
+	let value = result?;
+	released?;
+	Ok(value)
+}
+
+/// Runs a migration script and records it, in a transaction if `transactional`.
+async fn run_script<C, P>(connection: &mut C, transactional: bool, script: &str, record: &str, params: P) -> Result<(), DbError>
+where
+	C: QueryableConn,
+	P: Into<mysql_async::Params> + Send,
+{
+	if transactional {
+		if let Some(mut transaction) = connection.begin_atomic().await? {
+			let result = run_and_record(&mut transaction, script, record, params).await;
+			return transaction.finish(result).await;
+		}
+	}
+	run_and_record(connection, script, record, params).await
+}
+
+async fn run_and_record<P: Into<mysql_async::Params> + Send>(
+	connection: &mut impl QueryableConn,
+	script: &str,
+	record: &str,
+	params: P,
+) -> Result<(), DbError> {
+	connection.query_drop(script).await?;
+	connection.exec_drop(record, params).await
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	/// A fresh directory under the system temp dir, removed on drop.
+	struct TempDir(PathBuf);
+
+	impl TempDir {
+		fn new(name: &str) -> Self {
+			let path = std::env::temp_dir().join(format!("mysql_async_orm_{}_{}", name, std::process::id()));
+			let _ = std::fs::remove_dir_all(&path);
+			std::fs::create_dir_all(&path).unwrap();
+			TempDir(path)
+		}
+
+		fn write(&self, file_name: &str, script: &str) {
+			std::fs::write(self.0.join(file_name), script).unwrap();
+		}
+	}
+
+	impl Drop for TempDir {
+		fn drop(&mut self) {
+			let _ = std::fs::remove_dir_all(&self.0);
+		}
+	}
+
+	#[test]
+	fn from_dir_parses_names() {
+		let dir = TempDir::new("parse");
+		dir.write("0002_items.up.sql", "CREATE TABLE items (id INT);");
+		dir.write("0002_items.down.sql", "DROP TABLE items;");
+		dir.write("0001_init.sql", "-- orm:no-transaction\nCREATE TABLE a (id INT);");
+		dir.write("10_add_index_on_code.sql", "CREATE INDEX code ON items (code);");
+		dir.write("README.md", "not a migration");
+		let migrator = Migrator::from_dir(&dir.0).unwrap();
+		let migrations = migrator.migrations();
+		assert_eq!(migrations.iter().map(|m| (m.version, m.name.as_str())).collect::<Vec<_>>(), vec![
+			(1, "init"),
+			(2, "items"),
+			(10, "add_index_on_code"),
+		]);
+		assert!(!migrations[0].transactional);
+		assert_eq!(migrations[0].down, None);
+		assert!(migrations[1].transactional);
+		assert_eq!(migrations[1].up, "CREATE TABLE items (id INT);");
+		assert_eq!(migrations[1].down.as_deref(), Some("DROP TABLE items;"));
+	}
+
+	#[test]
+	fn from_dir_rejects_invalid_names() {
+		let dir = TempDir::new("invalid");
+		dir.write("init.sql", "SELECT 1;");
+		assert!(matches!(Migrator::from_dir(&dir.0), Err(MigrationError::InvalidFileName(_))));
+		let dir = TempDir::new("invalid_version");
+		dir.write("v1_init.sql", "SELECT 1;");
+		assert!(matches!(Migrator::from_dir(&dir.0), Err(MigrationError::InvalidFileName(_))));
+	}
+
+	#[test]
+	fn from_dir_rejects_duplicate_versions() {
+		let dir = TempDir::new("duplicate");
+		dir.write("0001_init.sql", "SELECT 1;");
+		dir.write("1_other.up.sql", "SELECT 2;");
+		assert!(matches!(Migrator::from_dir(&dir.0), Err(MigrationError::DuplicateVersion(1))));
+	}
+
+	#[test]
+	fn from_dir_rejects_down_without_up() {
+		let dir = TempDir::new("missing_up");
+		dir.write("0001_init.up.sql", "SELECT 1;");
+		dir.write("0002_items.down.sql", "DROP TABLE items;");
+		assert!(matches!(Migrator::from_dir(&dir.0), Err(MigrationError::MissingUp(2))));
+	}
+
+	#[test]
+	fn written_migrations_are_non_transactional() {
+		let dir = TempDir::new("write");
+		dir.write("0003_init.sql", "SELECT 1;");
+		let diff = SchemaDiff {
+			up: vec!["ALTER TABLE items ADD COLUMN code VARCHAR(255) NOT NULL;".to_string()],
+			down: vec!["ALTER TABLE items DROP COLUMN code;".to_string()],
+		};
+		let up = write_migration(&dir.0, "add_code", &diff).unwrap();
+		assert_eq!(up, dir.0.join("0004_add_code.up.sql"));
+		let migrator = Migrator::from_dir(&dir.0).unwrap();
+		let migration = &migrator.migrations()[1];
+		assert!(!migration.transactional);
+		assert!(migration.up.starts_with(NO_TRANSACTION_MARKER));
+		assert!(migration.up.ends_with("ADD COLUMN code VARCHAR(255) NOT NULL;\n"));
+		assert!(migration.down.as_deref().unwrap().ends_with("DROP COLUMN code;\n"));
+		assert!(matches!(write_migration(&dir.0, "again", &diff), Ok(path) if path.ends_with("0005_again.up.sql")));
+	}
+
+	#[test]
+	fn checksum_is_sha256_of_up() {
+		let migration = Migration::new(1, "init", "abc").with_down("anything");
+		assert_eq!(migration.checksum(), "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad");
+		assert_eq!(migration.checksum(), Migration::new(2, "other", "abc").checksum());
+		assert_ne!(migration.checksum(), Migration::new(1, "init", "abc ").checksum());
+	}
+}
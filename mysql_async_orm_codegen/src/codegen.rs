@@ -0,0 +1,437 @@
+use std::fmt::Write;
+
+use crate::schema::{Column, Schema, Table};
+
+const KEYWORDS: &[&str] = &[
+	"as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum", "extern", "false", "fn", "for", "if", "impl", "in",
+	"let", "loop", "match", "mod", "move", "mut", "pub", "ref", "return", "self", "static", "struct", "super", "trait", "true", "type",
+	"unsafe", "use", "where", "while", "abstract", "become", "box", "do", "final", "gen", "macro", "override", "priv", "try", "typeof",
+	"unsized", "virtual", "yield",
+];
+
+/// Converters for BIT(1) columns, which MySQL sends as a single byte that `bool` cannot decode.
+const BIT_BOOL: &str = "mod bit_bool {
+	use mysql_async_orm::mysql_async::{FromValueError, Value};
+
+	pub fn from_value(value: Value) -> Result<bool, FromValueError> {
+		match &value {
+			Value::Bytes(b) if b.as_slice() == [0] => Ok(false),
+			Value::Bytes(b) if b.as_slice() == [1] => Ok(true),
+			_ => Err(FromValueError(value)),
+		}
+	}
+
+	pub fn to_value(value: &bool) -> Value {
+		Value::from(*value as u8)
+	}
+}
+";
+
+const BIT_BOOL_OPT: &str = "mod bit_bool_opt {
+	use mysql_async_orm::mysql_async::{FromValueError, Value};
+
+	pub fn from_value(value: Value) -> Result<Option<bool>, FromValueError> {
+		match value {
+			Value::NULL => Ok(None),
+			value => super::bit_bool::from_value(value).map(Some),
+		}
+	}
+
+	pub fn to_value(value: &Option<bool>) -> Value {
+		value.as_ref().map_or(Value::NULL, super::bit_bool::to_value)
+	}
+}
+";
+
+struct Relation<'a> {
+	parent: &'a str,
+	child: &'a str,
+	fk: &'a str,
+	/// Relations closing a cycle are emitted commented out, the derive cannot load them.
+	active: bool,
+}
+
+/// Rust source with one `#[derive(DbModel)]` struct per table with a single-column primary key.
+/// With `decimal`, DECIMAL columns become `rust_decimal::Decimal` instead of `f64`.
+pub fn generate(schema: &Schema, decimal: bool) -> String {
+	let struct_names = struct_names(&schema.tables);
+	let relations = relations(schema);
+	let mut date_types: Vec<&str> = Vec::new();
+	let mut uses_decimal = false;
+	let mut bit_converters: Vec<&str> = Vec::new();
+	let mut body = String::new();
+	for (table, struct_name) in schema.tables.iter().zip(&struct_names) {
+		let Some(pk) = &table.pk else {
+			writeln!(body, "// {}: skipped, a DbModel needs a single-column primary key\n", table.name).unwrap();
+			continue;
+		};
+		writeln!(body, "#[derive(DbModel, Debug)]\n#[from({:?})]\n#[db_schema]\npub struct {} {{", table.name, struct_name).unwrap();
+		let mut fields: Vec<String> = Vec::new();
+		for column in &table.columns {
+			let is_fk = relations.iter().any(|r| r.active && r.child == table.name && r.fk == column.name);
+			if is_fk {
+				continue;
+			}
+			let (rs_type, ddl) = rust_type(column, decimal);
+			uses_decimal |= rs_type == "Decimal";
+			if let Some(date_type) = ["DBDateTime", "DBDate", "DBTime", "DBTimestamp"].into_iter().find(|t| rs_type == *t) {
+				if !date_types.contains(&date_type) {
+					date_types.push(date_type);
+				}
+			}
+			let field = field_name(&column.name);
+			if &column.name == pk {
+				body.push_str("\t#[pk]\n");
+			}
+			if field != column.name {
+				writeln!(body, "\t#[from({:?})]", column.name).unwrap();
+			}
+			let mut ddl = ddl;
+			if rs_type == "bool" && column.data_type == "bit" {
+				let converter = if column.nullable { "bit_bool_opt" } else { "bit_bool" };
+				for needed in ["bit_bool", converter] {
+					if !bit_converters.contains(&needed) {
+						bit_converters.push(needed);
+					}
+				}
+				writeln!(body, "\t#[convert(with = {})]", converter).unwrap();
+				if column.nullable {
+					ddl.push("nullable".to_string());
+				}
+			}
+			if !ddl.is_empty() {
+				writeln!(body, "\t#[ddl({})]", ddl.join(", ")).unwrap();
+			}
+			let rs_type = if &column.name == pk || column.nullable { format!("Option<{}>", rs_type) } else { rs_type };
+			writeln!(body, "\tpub {}: {},", field, rs_type).unwrap();
+			fields.push(field);
+		}
+		for relation in relations.iter().filter(|r| r.parent == table.name) {
+			let child = schema.tables.iter().position(|t| t.name == relation.child).unwrap();
+			let mut field = field_name(relation.child);
+			let same_child = relations.iter().filter(|r| r.parent == relation.parent && r.child == relation.child).count();
+			if same_child > 1 || fields.contains(&field) {
+				field = format!("{}_by_{}", field, field_name(relation.fk));
+			}
+			let prefix = if relation.active { "" } else { "// " };
+			if !relation.active {
+				body.push_str("\t// cyclic relation, the derive cannot load it\n");
+			}
+			writeln!(body, "\t{}#[relation({:?})]", prefix, relation.fk).unwrap();
+			writeln!(body, "\t{}pub {}: Vec<{}>,", prefix, field, struct_names[child]).unwrap();
+			fields.push(field);
+		}
+		body.push_str("}\n\n");
+	}
+	let mut res = String::from("// Generated by mysql_async_orm_codegen from information_schema, review before use.\n\n");
+	let mut imports = Vec::new();
+	if !date_types.is_empty() {
+		date_types.sort();
+		imports.push(format!("db_date::{{{}}}", date_types.join(", ")));
+	}
+	if uses_decimal {
+		imports.push("rust_decimal::Decimal".to_string());
+	}
+	match imports.len() {
+		0 => res.push_str("use mysql_async_orm::DbModel;\n\n"),
+		_ => writeln!(res, "use mysql_async_orm::{{{}, DbModel}};\n", imports.join(", ")).unwrap(),
+	}
+	for converter in [("bit_bool", BIT_BOOL), ("bit_bool_opt", BIT_BOOL_OPT)] {
+		if bit_converters.contains(&converter.0) {
+			writeln!(res, "{}", converter.1).unwrap();
+		}
+	}
+	res.push_str(body.trim_end());
+	res.push('\n');
+	res
+}
+
+/// `produzione__distinta_base` becomes `ProduzioneDistintaBase`, with a numeric suffix on collisions.
+fn struct_names(tables: &[Table]) -> Vec<String> {
+	let mut res: Vec<String> = Vec::new();
+	for table in tables {
+		let mut name: String = table.name.split(|c: char| !c.is_ascii_alphanumeric())
+			.filter(|part| !part.is_empty())
+			.map(|part| {
+				let mut chars = part.chars();
+				let first = chars.next().unwrap().to_ascii_uppercase();
+				std::iter::once(first).chain(chars.map(|c| c.to_ascii_lowercase())).collect::<String>()
+			})
+			.collect();
+		if !name.starts_with(|c: char| c.is_ascii_alphabetic()) {
+			name.insert(0, 'T');
+		}
+		let base = name.clone();
+		let mut n = 2;
+		while res.contains(&name) {
+			name = format!("{}{}", base, n);
+			n += 1;
+		}
+		res.push(name);
+	}
+	res
+}
+
+/// A snake case identifier for a column or table name.
+fn field_name(name: &str) -> String {
+	let mut res = String::with_capacity(name.len());
+	for c in name.chars() {
+		let c = if c.is_ascii_alphanumeric() { c.to_ascii_lowercase() } else { '_' };
+		if !(c == '_' && res.ends_with('_')) {
+			res.push(c);
+		}
+	}
+	if res.is_empty() || res.starts_with(|c: char| c.is_ascii_digit()) {
+		res.insert(0, '_');
+	}
+	if KEYWORDS.contains(&res.as_str()) {
+		res.push('_');
+	}
+	res
+}
+
+/// Foreign keys referencing the primary key of the parent become `#[relation]` fields of the parent.
+fn relations(schema: &Schema) -> Vec<Relation<'_>> {
+	let pk = |name: &str| schema.tables.iter().find(|t| t.name == name).and_then(|t| t.pk.as_deref());
+	let mut res: Vec<Relation<'_>> = Vec::new();
+	for fk in &schema.foreign_keys {
+		let Some(child_pk) = pk(&fk.table) else { continue };
+		if pk(&fk.referenced_table) != Some(fk.referenced_column.as_str()) || child_pk == fk.column {
+			continue;
+		}
+		let active = !reaches(&res, &fk.table, &fk.referenced_table);
+		res.push(Relation { parent: &fk.referenced_table, child: &fk.table, fk: &fk.column, active });
+	}
+	res.sort_by(|a, b| (a.parent, a.child, a.fk).cmp(&(b.parent, b.child, b.fk)));
+	res
+}
+
+/// Whether `to` is `from` or one of its (transitive) active children.
+fn reaches(relations: &[Relation<'_>], from: &str, to: &str) -> bool {
+	from == to || relations.iter().any(|r| r.active && r.parent == from && reaches(relations, r.child, to))
+}
+
+/// The Rust type of a column and the `#[ddl]` options reproducing its MySQL type.
+fn rust_type(column: &Column, decimal: bool) -> (String, Vec<String>) {
+	let column_type = column.column_type.to_lowercase();
+	let unsigned = column_type.contains("unsigned");
+	let integer = |signed: &str, unsigned_type: &str| if unsigned { unsigned_type } else { signed }.to_string();
+	let args: Vec<u32> = column_type.split_once('(')
+		.and_then(|(_, rest)| rest.split_once(')'))
+		.map(|(args, _)| args.split(',').filter_map(|arg| arg.trim().parse().ok()).collect())
+		.unwrap_or_default();
+	let sql_type = || vec![format!("sql_type = {:?}", column_type.to_uppercase())];
+	match column.data_type.as_str() {
+		"tinyint" if column_type.starts_with("tinyint(1)") => ("bool".to_string(), Vec::new()),
+		"tinyint" => (integer("i8", "u8"), Vec::new()),
+		"smallint" => (integer("i16", "u16"), Vec::new()),
+		"mediumint" => (integer("i32", "u32"), sql_type()),
+		"int" | "integer" => (integer("i32", "u32"), Vec::new()),
+		"bigint" => (integer("i64", "u64"), Vec::new()),
+		"float" => ("f32".to_string(), Vec::new()),
+		"double" | "real" => ("f64".to_string(), Vec::new()),
+		"decimal" | "numeric" => {
+			let ddl = match args.as_slice() {
+				[precision, scale] => vec![format!("precision = {}", precision), format!("scale = {}", scale)],
+				[precision] => vec![format!("precision = {}", precision)],
+				_ => sql_type(),
+			};
+			(if decimal { "Decimal" } else { "f64" }.to_string(), ddl)
+		}
+		"varchar" => ("String".to_string(), match args.as_slice() {
+			[255] => Vec::new(),
+			[length] => vec![format!("length = {}", length)],
+			_ => sql_type(),
+		}),
+		"varbinary" => ("Vec<u8>".to_string(), match args.as_slice() {
+			[255] => Vec::new(),
+			[length] => vec![format!("length = {}", length)],
+			_ => sql_type(),
+		}),
+		"binary" | "tinyblob" | "blob" | "mediumblob" | "longblob" => ("Vec<u8>".to_string(), sql_type()),
+		"date" => ("DBDate".to_string(), Vec::new()),
+		"datetime" | "time" | "timestamp" => {
+			let rs_type = match column.data_type.as_str() {
+				"time" => "DBTime",
+				"timestamp" => "DBTimestamp",
+				_ => "DBDateTime",
+			};
+			let ddl = match args.as_slice() {
+				[fsp] => vec![format!("precision = {}", fsp)],
+				_ => Vec::new(),
+			};
+			(rs_type.to_string(), ddl)
+		}
+		"year" => ("u16".to_string(), sql_type()),
+		"bit" if args.as_slice() == [1] => ("bool".to_string(), sql_type()),
+		"bit" => ("Vec<u8>".to_string(), sql_type()),
+		// char, text, enum, set, json and anything else is read as text.
+		_ => {
+			let ddl = match column.data_type.as_str() {
+				"enum" | "set" => vec![format!("sql_type = {:?}", column.column_type.replacen(&column.data_type, &column.data_type.to_uppercase(), 1))],
+				_ => sql_type(),
+			};
+			("String".to_string(), ddl)
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::schema::ForeignKey;
+
+	fn column(name: &str, data_type: &str, column_type: &str, nullable: bool) -> Column {
+		Column { name: name.to_string(), data_type: data_type.to_string(), column_type: column_type.to_string(), nullable }
+	}
+
+	fn table(name: &str, pk: Option<&str>, columns: Vec<Column>) -> Table {
+		Table { name: name.to_string(), columns, pk: pk.map(str::to_string) }
+	}
+
+	fn foreign_key(table: &str, column: &str, referenced_table: &str, referenced_column: &str) -> ForeignKey {
+		ForeignKey {
+			table: table.to_string(),
+			column: column.to_string(),
+			referenced_table: referenced_table.to_string(),
+			referenced_column: referenced_column.to_string(),
+		}
+	}
+
+	/// Articles with bills of materials, a pk-less log table and a cycle between reparti and dipendenti.
+	fn schema() -> Schema {
+		Schema {
+			tables: vec![
+				table("articoli", Some("id"), vec![
+					column("id", "mediumint", "mediumint unsigned", false),
+					column("Codice Articolo", "varchar", "varchar(40)", false),
+					column("prezzo", "decimal", "decimal(10,2)", true),
+					column("attivo", "bit", "bit(1)", false),
+					column("creato", "datetime", "datetime", false),
+				]),
+				table("distinte", Some("id"), vec![
+					column("id", "int", "int unsigned", false),
+					column("id_articolo", "int", "int unsigned", false),
+					column("type", "varchar", "varchar(255)", true),
+				]),
+				table("log", None, vec![column("messaggio", "text", "text", false)]),
+				table("reparti", Some("id"), vec![
+					column("id", "int", "int", false),
+					column("id_responsabile", "int", "int", true),
+				]),
+				table("dipendenti", Some("id"), vec![
+					column("id", "int", "int", false),
+					column("id_reparto", "int", "int", false),
+					column("presente", "bit", "bit(1)", true),
+				]),
+			],
+			foreign_keys: vec![
+				foreign_key("distinte", "id_articolo", "articoli", "id"),
+				foreign_key("dipendenti", "id_reparto", "reparti", "id"),
+				foreign_key("reparti", "id_responsabile", "dipendenti", "id"),
+			],
+		}
+	}
+
+	fn rust_type_of(data_type: &str, column_type: &str) -> (String, Vec<String>) {
+		rust_type(&column("c", data_type, column_type, false), false)
+	}
+
+	#[test]
+	fn rust_types_follow_the_column_type() {
+		assert_eq!(rust_type_of("int", "int unsigned"), ("u32".to_string(), vec![]));
+		assert_eq!(rust_type_of("tinyint", "tinyint(1)"), ("bool".to_string(), vec![]));
+		assert_eq!(rust_type_of("tinyint", "tinyint"), ("i8".to_string(), vec![]));
+		assert_eq!(rust_type_of("mediumint", "mediumint"), ("i32".to_string(), vec!["sql_type = \"MEDIUMINT\"".to_string()]));
+		assert_eq!(rust_type_of("varchar", "varchar(40)"), ("String".to_string(), vec!["length = 40".to_string()]));
+		assert_eq!(rust_type_of("varchar", "varchar(255)"), ("String".to_string(), vec![]));
+		assert_eq!(rust_type_of("datetime", "datetime(3)"), ("DBDateTime".to_string(), vec!["precision = 3".to_string()]));
+		assert_eq!(rust_type_of("bit", "bit(1)"), ("bool".to_string(), vec!["sql_type = \"BIT(1)\"".to_string()]));
+		assert_eq!(rust_type_of("bit", "bit(12)"), ("Vec<u8>".to_string(), vec!["sql_type = \"BIT(12)\"".to_string()]));
+		assert_eq!(rust_type_of("enum", "enum('a','B')"), ("String".to_string(), vec!["sql_type = \"ENUM('a','B')\"".to_string()]));
+	}
+
+	#[test]
+	fn decimals_are_f64_unless_requested() {
+		let prezzo = column("prezzo", "decimal", "decimal(10,2)", false);
+		let ddl = vec!["precision = 10".to_string(), "scale = 2".to_string()];
+		assert_eq!(rust_type(&prezzo, false), ("f64".to_string(), ddl.clone()));
+		assert_eq!(rust_type(&prezzo, true), ("Decimal".to_string(), ddl));
+	}
+
+	#[test]
+	fn field_names_are_snake_case_identifiers() {
+		assert_eq!(field_name("Codice Articolo"), "codice_articolo");
+		assert_eq!(field_name("a--b"), "a_b");
+		assert_eq!(field_name("type"), "type_");
+		assert_eq!(field_name("1col"), "_1col");
+		assert_eq!(field_name(""), "_");
+	}
+
+	#[test]
+	fn struct_names_are_unique_camel_case() {
+		let tables = ["produzione__distinta_base", "a_b", "A-B", "1abc"].map(|name| table(name, None, vec![]));
+		assert_eq!(struct_names(&tables), ["ProduzioneDistintaBase", "AB", "AB2", "T1abc"]);
+	}
+
+	#[test]
+	fn cyclic_relations_are_inactive() {
+		let schema = schema();
+		let relations: Vec<_> = relations(&schema).iter().map(|r| (r.parent, r.child, r.fk, r.active)).collect();
+		assert_eq!(relations, [
+			("articoli", "distinte", "id_articolo", true),
+			("dipendenti", "reparti", "id_responsabile", false),
+			("reparti", "dipendenti", "id_reparto", true),
+		]);
+	}
+
+	#[test]
+	fn foreign_keys_not_on_the_parent_pk_are_not_relations() {
+		let mut schema = schema();
+		schema.foreign_keys = vec![foreign_key("distinte", "id_articolo", "articoli", "Codice Articolo"), foreign_key("log", "messaggio", "articoli", "id")];
+		assert!(relations(&schema).is_empty());
+	}
+
+	#[test]
+	fn generate_emits_the_models() {
+		let source = generate(&schema(), false);
+		assert!(source.starts_with("// Generated by mysql_async_orm_codegen from information_schema, review before use.\n\nuse mysql_async_orm::{db_date::{DBDateTime}, DbModel};\n\nmod bit_bool {"));
+		assert!(source.contains("mod bit_bool_opt {"));
+		assert!(source.contains(concat!(
+			"pub struct Articoli {\n",
+			"\t#[pk]\n",
+			"\t#[ddl(sql_type = \"MEDIUMINT UNSIGNED\")]\n",
+			"\tpub id: Option<u32>,\n",
+			"\t#[from(\"Codice Articolo\")]\n",
+			"\t#[ddl(length = 40)]\n",
+			"\tpub codice_articolo: String,\n",
+			"\t#[ddl(precision = 10, scale = 2)]\n",
+			"\tpub prezzo: Option<f64>,\n",
+			"\t#[convert(with = bit_bool)]\n",
+			"\t#[ddl(sql_type = \"BIT(1)\")]\n",
+			"\tpub attivo: bool,\n",
+			"\tpub creato: DBDateTime,\n",
+			"\t#[relation(\"id_articolo\")]\n",
+			"\tpub distinte: Vec<Distinte>,\n",
+			"}\n",
+		)));
+		assert!(source.contains("pub struct Distinte {\n\t#[pk]\n\tpub id: Option<u32>,\n\t#[from(\"type\")]\n\tpub type_: Option<String>,\n}\n"));
+		assert!(source.contains("// log: skipped, a DbModel needs a single-column primary key\n"));
+		assert!(source.contains("\tpub id_responsabile: Option<i32>,\n\t#[relation(\"id_reparto\")]\n\tpub dipendenti: Vec<Dipendenti>,\n"));
+		assert!(source.contains(concat!(
+			"\t#[convert(with = bit_bool_opt)]\n",
+			"\t#[ddl(sql_type = \"BIT(1)\", nullable)]\n",
+			"\tpub presente: Option<bool>,\n",
+			"\t// cyclic relation, the derive cannot load it\n",
+			"\t// #[relation(\"id_responsabile\")]\n",
+			"\t// pub reparti: Vec<Reparti>,\n",
+		)));
+	}
+
+	#[test]
+	fn generate_imports_decimal_on_request() {
+		let source = generate(&schema(), true);
+		assert!(source.contains("use mysql_async_orm::{db_date::{DBDateTime}, rust_decimal::Decimal, DbModel};\n"));
+		assert!(source.contains("\tpub prezzo: Option<Decimal>,\n"));
+	}
+}
@@ -0,0 +1,54 @@
+mod codegen;
+mod schema;
+
+use mysql_async_orm::db_connection::DbConnectionPool;
+
+const USAGE: &str = "Usage: mysql_async_orm_codegen <database url> [--out <file>] [--decimal] [table ...]
+
+Reads information_schema of the database and prints a #[derive(DbModel)] struct for each table
+(or only for the given ones), with #[relation] fields proposed from the foreign keys.
+With --decimal, DECIMAL columns are mapped to rust_decimal::Decimal (feature rust_decimal)
+instead of f64.";
+
+#[tokio::main]
+async fn main() {
+	let mut url = None;
+	let mut out = None;
+	let mut decimal = false;
+	let mut tables = Vec::new();
+	let mut args = std::env::args().skip(1);
+	while let Some(arg) = args.next() {
+		match arg.as_str() {
+			"-h" | "--help" => {
+				println!("{}", USAGE);
+				return;
+			}
+			"-o" | "--out" => out = args.next(),
+			"--decimal" => decimal = true,
+			_ if url.is_none() => url = Some(arg),
+			_ => tables.push(arg),
+		}
+	}
+	let Some(url) = url else {
+		eprintln!("{}", USAGE);
+		std::process::exit(2);
+	};
+	if let Err(error) = run(&url, out.as_deref(), decimal, &tables).await {
+		eprintln!("{}", error);
+		std::process::exit(1);
+	}
+}
+
+async fn run(url: &str, out: Option<&str>, decimal: bool, tables: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+	let pool = DbConnectionPool::from_url(url)?;
+	let mut conn = pool.get_conn().await?;
+	let schema = schema::read_schema(&mut conn, tables).await?;
+	drop(conn);
+	pool.disconnect().await?;
+	let source = codegen::generate(&schema, decimal);
+	match out {
+		Some(path) => std::fs::write(path, source)?,
+		None => print!("{}", source),
+	}
+	Ok(())
+}
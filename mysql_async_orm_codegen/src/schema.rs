@@ -0,0 +1,80 @@
+use mysql_async_orm::db_connection::{DbError, QueryableConn};
+
+pub struct Column {
+	pub name: String,
+	pub data_type: String,
+	pub column_type: String,
+	pub nullable: bool,
+}
+
+pub struct Table {
+	pub name: String,
+	pub columns: Vec<Column>,
+	/// `None` when the primary key is missing or spans several columns.
+	pub pk: Option<String>,
+}
+
+/// A single-column foreign key.
+pub struct ForeignKey {
+	pub table: String,
+	pub column: String,
+	pub referenced_table: String,
+	pub referenced_column: String,
+}
+
+pub struct Schema {
+	pub tables: Vec<Table>,
+	pub foreign_keys: Vec<ForeignKey>,
+}
+
+/// Reads the tables of the current database, all of them if `only` is empty.
+pub async fn read_schema(connection: &mut impl QueryableConn, only: &[String]) -> Result<Schema, DbError> {
+	let rows: Vec<(String, String, String, String, String, String)> = connection.query(
+		"SELECT TABLE_NAME, COLUMN_NAME, DATA_TYPE, COLUMN_TYPE, IS_NULLABLE, COLUMN_KEY FROM information_schema.COLUMNS \
+		WHERE TABLE_SCHEMA = DATABASE() ORDER BY TABLE_NAME, ORDINAL_POSITION"
+	).await?;
+	let mut tables: Vec<Table> = Vec::new();
+	let mut pk_columns: Vec<(String, String)> = Vec::new();
+	for (table, name, data_type, column_type, nullable, key) in rows {
+		if !only.is_empty() && !only.contains(&table) {
+			continue;
+		}
+		if key == "PRI" {
+			pk_columns.push((table.clone(), name.clone()));
+		}
+		let column = Column {
+			name,
+			data_type: data_type.to_lowercase(),
+			column_type,
+			nullable: nullable == "YES",
+		};
+		match tables.last_mut() {
+			Some(last) if last.name == table => last.columns.push(column),
+			_ => tables.push(Table { name: table, columns: vec![column], pk: None }),
+		}
+	}
+	for table in tables.iter_mut() {
+		let mut pk = pk_columns.iter().filter(|(t, _)| *t == table.name);
+		if let (Some((_, column)), None) = (pk.next(), pk.next()) {
+			table.pk = Some(column.clone());
+		}
+	}
+	let rows: Vec<(String, String, String, String, String)> = connection.query(
+		"SELECT CONSTRAINT_NAME, TABLE_NAME, COLUMN_NAME, REFERENCED_TABLE_NAME, REFERENCED_COLUMN_NAME FROM information_schema.KEY_COLUMN_USAGE \
+		WHERE TABLE_SCHEMA = DATABASE() AND REFERENCED_TABLE_NAME IS NOT NULL ORDER BY TABLE_NAME, CONSTRAINT_NAME, ORDINAL_POSITION"
+	).await?;
+	let mut foreign_keys = Vec::new();
+	for (constraint, table, column, referenced_table, referenced_column) in &rows {
+		let composite = rows.iter().filter(|(c, t, ..)| c == constraint && t == table).count() > 1;
+		let known = |table: &String| tables.iter().any(|t| &t.name == table);
+		if !composite && known(table) && known(referenced_table) {
+			foreign_keys.push(ForeignKey {
+				table: table.clone(),
+				column: column.clone(),
+				referenced_table: referenced_table.clone(),
+				referenced_column: referenced_column.clone(),
+			});
+		}
+	}
+	Ok(Schema { tables, foreign_keys })
+}
@@ -0,0 +1,227 @@
+use quote::quote;
+use syn::{parse::Parse, Result};
+
+/// `#[db_enum(...)]` on the enum or on a variant.
+#[derive(Default)]
+struct DbEnumAttribute {
+	integer: bool,
+	rename_all: Option<(String, proc_macro2::Span)>,
+	rename: Option<String>,
+}
+
+impl Parse for DbEnumAttribute {
+	fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+		let content;
+		let _ = syn::parenthesized!(content in input);
+		let mut res = DbEnumAttribute::default();
+		while !content.is_empty() {
+			let name: syn::Ident = content.parse()?;
+			let value: Option<syn::LitStr> = if content.peek(syn::Token![=]) {
+				let _: syn::Token![=] = content.parse()?;
+				Some(content.parse()?)
+			} else {
+				None
+			};
+			match (name.to_string().as_str(), value) {
+				("integer", None) => res.integer = true,
+				("rename_all", Some(value)) => res.rename_all = Some((value.value(), value.span())),
+				("rename", Some(value)) => res.rename = Some(value.value()),
+				_ => return Err(syn::Error::new(name.span(), "Unknown db_enum option")),
+			}
+			if !content.is_empty() {
+				let _: syn::Token![,] = content.parse()?;
+			}
+		}
+		Ok(res)
+	}
+}
+
+fn get_db_enum_attribute(attrs: &[syn::Attribute]) -> Result<DbEnumAttribute> {
+	match attrs.iter().find(|a| a.path.is_ident("db_enum")) {
+		Some(a) => syn::parse2(a.tokens.clone()),
+		None => Ok(DbEnumAttribute::default()),
+	}
+}
+
+fn rename(name: &str, rename_all: &Option<(String, proc_macro2::Span)>) -> Result<String> {
+	let snake_case = || {
+		let mut res = String::new();
+		for (i, c) in name.chars().enumerate() {
+			if c.is_uppercase() && i > 0 {
+				res.push('_');
+			}
+			res.extend(c.to_lowercase());
+		}
+		res
+	};
+	Ok(match rename_all {
+		None => name.to_string(),
+		Some((case, span)) => match case.as_str() {
+			"lowercase" => name.to_lowercase(),
+			"UPPERCASE" => name.to_uppercase(),
+			"snake_case" => snake_case(),
+			"SCREAMING_SNAKE_CASE" => snake_case().to_uppercase(),
+			_ => return Err(syn::Error::new(*span, "rename_all must be lowercase, UPPERCASE, snake_case or SCREAMING_SNAKE_CASE")),
+		},
+	})
+}
+
+fn discriminant(expr: &syn::Expr) -> Result<i64> {
+	match expr {
+		syn::Expr::Lit(syn::ExprLit { lit: syn::Lit::Int(value), .. }) => value.base10_parse(),
+		syn::Expr::Unary(syn::ExprUnary { op: syn::UnOp::Neg(_), expr, .. }) => Ok(-discriminant(expr)?),
+		_ => Err(syn::Error::new_spanned(expr, "db_enum(integer) discriminants must be integer literals")),
+	}
+}
+
+/// The smallest integer column holding every code.
+fn integer_sql_type(codes: &[i64]) -> &'static str {
+	let min = codes.iter().copied().min().unwrap_or(0);
+	let max = codes.iter().copied().max().unwrap_or(0);
+	let types: [(&str, i64, i64); 6] = [
+		("TINYINT UNSIGNED", 0, u8::MAX as i64),
+		("TINYINT", i8::MIN as i64, i8::MAX as i64),
+		("SMALLINT UNSIGNED", 0, u16::MAX as i64),
+		("SMALLINT", i16::MIN as i64, i16::MAX as i64),
+		("INT UNSIGNED", 0, u32::MAX as i64),
+		("INT", i32::MIN as i64, i32::MAX as i64),
+	];
+	types.iter().find(|(_, lo, hi)| *lo <= min && max <= *hi).map_or("BIGINT", |(sql_type, _, _)| sql_type)
+}
+
+/// `DbEnum`, value conversions, `SqlType`, `JsonSchemaType` and `TsType` for a fieldless enum,
+/// stored as an `ENUM` of the variant names or, with `#[db_enum(integer)]`, as its discriminants.
+pub fn db_enum_macro(crate_name: &syn::Path, input: &syn::DeriveInput) -> Result<proc_macro2::TokenStream> {
+	let name = &input.ident;
+	let syn::Data::Enum(data) = &input.data else {
+		return Err(syn::Error::new(name.span(), "DbEnum can only be derived for enums"));
+	};
+	let options = get_db_enum_attribute(&input.attrs)?;
+	if options.rename.is_some() {
+		return Err(syn::Error::new(name.span(), "rename is a variant option"));
+	}
+	let mut variants = Vec::new();
+	let mut names = Vec::new();
+	let mut codes = Vec::new();
+	let mut next_code = 0;
+	for variant in &data.variants {
+		if !matches!(variant.fields, syn::Fields::Unit) {
+			return Err(syn::Error::new(variant.ident.span(), "DbEnum variants cannot have fields"));
+		}
+		let attribute = get_db_enum_attribute(&variant.attrs)?;
+		if attribute.integer || attribute.rename_all.is_some() {
+			return Err(syn::Error::new(variant.ident.span(), "integer and rename_all are enum options"));
+		}
+		let db_name = match attribute.rename {
+			Some(db_name) => db_name,
+			None => rename(&variant.ident.to_string(), &options.rename_all)?,
+		};
+		if names.contains(&db_name) {
+			return Err(syn::Error::new(variant.ident.span(), format!("Duplicate member {:?}", db_name)));
+		}
+		let code = match (&variant.discriminant, options.integer) {
+			(Some((_, expr)), true) => discriminant(expr)?,
+			_ => next_code,
+		};
+		next_code = code + 1;
+		variants.push(&variant.ident);
+		names.push(db_name);
+		codes.push(code);
+	}
+	if variants.is_empty() {
+		return Err(syn::Error::new(name.span(), "DbEnum needs at least one variant"));
+	}
+	// A SET holds at most 64 members, so larger enums cannot be a DbSet member.
+	let set_member = (variants.len() <= 64).then(|| quote! {
+		impl #crate_name::db_enum::SetMember for #name {}
+	});
+	let indexes = 0..variants.len();
+	let from_indexes = 0..variants.len();
+	let (try_from, into_value, sql_type, json_schema, ts_type) = if options.integer {
+		let ts_type = codes.iter().map(|code| code.to_string()).collect::<Vec<_>>().join(" | ");
+		let sql_type = integer_sql_type(&codes);
+		(
+			quote! {
+				match #crate_name::db_enum::code_from_value(&value) {
+					#(::std::option::Option::Some(#codes) => ::std::result::Result::Ok(Self::#variants),)*
+					_ => ::std::result::Result::Err(#crate_name::mysql_async::FromValueError(value)),
+				}
+			},
+			quote! {
+				#crate_name::mysql_async::Value::Int(match value {
+					#(#name::#variants => #codes,)*
+				})
+			},
+			quote! { #sql_type.to_string() },
+			quote! { #crate_name::serde_json::json!({ "type": "integer", "enum": [#(#codes),*] }) },
+			quote! { #ts_type.to_string() },
+		)
+	} else {
+		(
+			quote! {
+				match #crate_name::db_enum::name_from_value(&value).and_then(<Self as #crate_name::db_enum::DbEnum>::from_name) {
+					::std::option::Option::Some(res) => ::std::result::Result::Ok(res),
+					::std::option::Option::None => ::std::result::Result::Err(#crate_name::mysql_async::FromValueError(value)),
+				}
+			},
+			quote! {
+				#crate_name::mysql_async::Value::from(#crate_name::db_enum::DbEnum::name(&value))
+			},
+			quote! {
+				#crate_name::db_enum::members_sql_type("ENUM", <Self as #crate_name::db_enum::DbEnum>::NAMES)
+			},
+			quote! {
+				#crate_name::serde_json::json!({ "type": "string", "enum": <Self as #crate_name::db_enum::DbEnum>::NAMES })
+			},
+			quote! {
+				#crate_name::db_enum::names_ts_type(<Self as #crate_name::db_enum::DbEnum>::NAMES)
+			},
+		)
+	};
+	Ok(quote! {
+		impl #crate_name::db_enum::DbEnum for #name {
+			const NAMES: &'static [&'static str] = &[#(#names),*];
+			fn index(&self) -> ::std::primitive::usize {
+				match self {
+					#(Self::#variants => #indexes,)*
+				}
+			}
+			fn from_index(index: ::std::primitive::usize) -> ::std::option::Option<Self> {
+				match index {
+					#(#from_indexes => ::std::option::Option::Some(Self::#variants),)*
+					_ => ::std::option::Option::None,
+				}
+			}
+		}
+		#set_member
+		impl #crate_name::mysql_async::prelude::FromValue for #name {
+			type Intermediate = Self;
+		}
+		impl ::std::convert::TryFrom<#crate_name::mysql_async::Value> for #name {
+			type Error = #crate_name::mysql_async::FromValueError;
+			fn try_from(value: #crate_name::mysql_async::Value) -> ::std::result::Result<Self, Self::Error> {
+				#try_from
+			}
+		}
+		impl ::std::convert::From<#name> for #crate_name::mysql_async::Value {
+			fn from(value: #name) -> Self {
+				#into_value
+			}
+		}
+		impl #crate_name::db_schema::SqlType for #name {
+			fn sql_type(_: &#crate_name::db_schema::ColumnDdl) -> ::std::string::String {
+				#sql_type
+			}
+		}
+		impl #crate_name::json_schema::JsonSchemaType for #name {
+			fn json_schema(_: &#crate_name::db_schema::ColumnDdl) -> #crate_name::serde_json::Value {
+				#json_schema
+			}
+		}
+		impl #crate_name::fe_export::TsType for #name {
+			fn ts_type() -> ::std::string::String {
+				#ts_type
+			}
+		}
+	})
+}
@@ -2,16 +2,19 @@ use std::collections::HashMap;
 
 use syn::{punctuated::Punctuated, spanned::Spanned, Result};
 
-use crate::db_model_parse::{FromAttribute, RelationAttribute};
+use crate::db_model_parse::{ConvertAttribute, DdlAttribute, FromAttribute, RelationAttribute};
 
 use super::get_attributes;
 
 
+#[allow(dead_code)]
 pub struct DbModelFrom<'a> {
 	pub rs_type: &'a syn::Ident,
 	pub from: String,
 	pub table: String,
 	pub joins: String,
+	/// `(table, alias)` of every join.
+	pub join_list: Vec<(String, String)>,
 }
 
 fn get_table_from<'a>(input: &'a syn::DeriveInput, struct_attributes: &HashMap<String, &syn::Attribute>) -> Result<DbModelFrom<'a>> {
@@ -23,6 +26,7 @@ fn get_table_from<'a>(input: &'a syn::DeriveInput, struct_attributes: &HashMap<S
 	} else {
 		from.clone()
 	};
+	let mut join_list = Vec::new();
 	let joins = if let Some((joins, span)) = struct_from_attribute.named_arrs.remove("joins") {
 		let mut res = String::new();
 		let joins: proc_macro2::TokenStream = joins.parse().map_err(|_| syn::Error::new(span, "joins invalid syntax"))?;
@@ -44,6 +48,7 @@ fn get_table_from<'a>(input: &'a syn::DeriveInput, struct_attributes: &HashMap<S
 			let alias = alias.to_string();
 			let on = on.value();
 			res.push_str(&format!(" LEFT JOIN {tbl} {alias} ON {on}"));
+			join_list.push((tbl, alias));
 		}
 		res
 	} else {
@@ -54,20 +59,28 @@ fn get_table_from<'a>(input: &'a syn::DeriveInput, struct_attributes: &HashMap<S
 		from,
 		table,
 		joins,
+		join_list,
 	})
 }
 
 
+#[allow(dead_code)]
 pub struct DbColumn<'a> {
 	pub rs_name: String,
 	pub rs_name_ident: &'a syn::Ident,
 	pub db_name: String,
 	pub rs_type: &'a syn::Type,
 	pub from_attribute: Option<FromAttribute>,
+	pub ddl: DdlAttribute,
+	/// The converter module of `#[convert(with = ...)]`.
+	pub convert: Option<syn::Path>,
 	pub readonly: bool,
+	/// `#[serde(default)]` on the field or the struct.
+	pub serde_default: bool,
 	pub attributes: HashMap<String, &'a syn::Attribute>,
 }
 
+#[allow(dead_code)]
 pub struct DbRelation<'a> {
 	pub rs_name: String,
 	pub rs_name_ident: &'a syn::Ident,
@@ -88,8 +101,10 @@ pub fn get_db_model<'a>(input: &'a syn::DeriveInput, struct_attributes: &HashMap
 	let mut pk: Option<DbColumn> = None;
 	let mut columns_except_pk: Vec<DbColumn> = Vec::with_capacity(fields.named.len());
 	let mut relations: Vec<DbRelation> = Vec::with_capacity(fields.named.len());
+	let struct_serde_default = has_serde_default(&input.attrs);
 	for field in &fields.named {
 		let attributes = get_attributes(field.attrs.iter());
+		let serde_default = struct_serde_default || has_serde_default(&field.attrs);
 		let rs_name = field.ident.as_ref().unwrap().to_string();
 		let rs_name_ident = field.ident.as_ref().unwrap();
 		let from_attribute: Option<FromAttribute> = if let Some(&a) = attributes.get("from") {
@@ -97,6 +112,17 @@ pub fn get_db_model<'a>(input: &'a syn::DeriveInput, struct_attributes: &HashMap
 		} else {
 			None
 		};
+		let ddl: DdlAttribute = if let Some(&a) = attributes.get("ddl") {
+			syn::parse(a.tokens.clone().into())?
+		} else {
+			DdlAttribute::default()
+		};
+		let convert = if let Some(&a) = attributes.get("convert") {
+			let ConvertAttribute { with } = syn::parse(a.tokens.clone().into())?;
+			Some(with)
+		} else {
+			None
+		};
 		let db_name = if let Some(from_attribute) = &from_attribute {
 			if let Some(db_name) = &from_attribute.attr {
 				db_name.clone()
@@ -113,12 +139,18 @@ pub fn get_db_model<'a>(input: &'a syn::DeriveInput, struct_attributes: &HashMap
 				if let Some(&ro) = attributes.get("readonly") {
 					return Err(syn::Error::new(ro.path.span(), "Primary key can't be readonly"));
 				}
+				if let Some(&c) = attributes.get("convert") {
+					return Err(syn::Error::new(c.path.span(), "Primary key can't have a converter"));
+				}
 				pk = Some(DbColumn {
 					db_name,
 					rs_name,
 					rs_name_ident,
 					from_attribute,
+					ddl,
+					convert: None,
 					readonly: false,
+					serde_default,
 					attributes,
 					rs_type: &field.ty
 				});
@@ -133,13 +165,13 @@ pub fn get_db_model<'a>(input: &'a syn::DeriveInput, struct_attributes: &HashMap
 				attributes,
 			});
 		} else {
-			let readonly = attributes.get("readonly").is_some();
+			let readonly = attributes.contains_key("readonly");
 			if !readonly {
 				if let Some(from_attribute) = &from_attribute {
-					if from_attribute.named_arrs.get("table").is_some() {
+					if from_attribute.named_arrs.contains_key("table") {
 						return Err(syn::Error::new(rs_name_ident.span(), "Columns from other tables must be readonly"));
 					}
-					if from_attribute.named_arrs.get("expression").is_some() {
+					if from_attribute.named_arrs.contains_key("expression") {
 						return Err(syn::Error::new(rs_name_ident.span(), "Expressions must be readonly"));
 					}
 				}
@@ -150,7 +182,10 @@ pub fn get_db_model<'a>(input: &'a syn::DeriveInput, struct_attributes: &HashMap
 				rs_name_ident,
 				attributes,
 				readonly,
+				serde_default,
 				from_attribute,
+				ddl,
+				convert,
 				rs_type: &field.ty
 			});
 		}
@@ -163,6 +198,18 @@ pub fn get_db_model<'a>(input: &'a syn::DeriveInput, struct_attributes: &HashMap
 	})
 }
 
+/// Whether the attributes contain a `#[serde(default)]` or `#[serde(default = "...")]`.
+fn has_serde_default(attrs: &[syn::Attribute]) -> bool {
+	attrs.iter().filter(|a| a.path.is_ident("serde")).any(|a| match a.parse_meta() {
+		Ok(syn::Meta::List(list)) => list.nested.iter().any(|nested| match nested {
+			syn::NestedMeta::Meta(syn::Meta::Path(path)) => path.is_ident("default"),
+			syn::NestedMeta::Meta(syn::Meta::NameValue(name_value)) => name_value.path.is_ident("default"),
+			_ => false,
+		}),
+		_ => false,
+	})
+}
+
 pub fn get_inner_type(ty: &syn::Type) -> Result<&syn::Type> {
 	let get_unsupported_type_err = || Err(syn::Error::new(ty.span(), "Unsupported type."));
 	let ty = if let syn::Type::Path(ty) = ty {
@@ -191,4 +238,32 @@ pub fn get_inner_type(ty: &syn::Type) -> Result<&syn::Type> {
 	} else {
 		unsafe { std::hint::unreachable_unchecked() }
 	}
-}
\ No newline at end of file
+}
+/// Whether the type is spelled `Option<...>`, for columns whose nullability cannot come from `SqlType`.
+pub fn is_option_type(ty: &syn::Type) -> bool {
+	match ty {
+		syn::Type::Path(ty) => ty.path.segments.last().map(|segment| segment.ident == "Option").unwrap_or(false),
+		_ => false,
+	}
+}
+
+/// Whether the type is spelled `DbJson<...>` or `Option<DbJson<...>>`, whose values are encoded fallibly.
+pub fn is_json_type(ty: &syn::Type) -> bool {
+	let segment = match ty {
+		syn::Type::Path(ty) => match ty.path.segments.last() {
+			Some(segment) => segment,
+			None => return false,
+		},
+		_ => return false,
+	};
+	if segment.ident == "DbJson" {
+		return true;
+	}
+	match &segment.arguments {
+		syn::PathArguments::AngleBracketed(arguments) if segment.ident == "Option" => match arguments.args.first() {
+			Some(syn::GenericArgument::Type(inner)) => is_json_type(inner) && !is_option_type(inner),
+			_ => false,
+		},
+		_ => false,
+	}
+}
@@ -1,6 +1,8 @@
 use std::{collections::HashMap, borrow::Borrow};
 use quote::quote;
-use syn::{Ident, Result};
+use syn::Result;
+
+use crate::db_model_parse::DdlAttribute;
 
 use self::into_db_model::{DbColumn, DbRelation};
 
@@ -48,18 +50,22 @@ pub fn get_partial_data_init_collectors(crate_name: &syn::Path, relations: &Vec<
 		let f_name = r.rs_name_ident;
 		let f_type = &r.ty;
 		quote! {
-			let mut #f_name = <#f_type as #crate_name::db_model::DbModel>::DataCollector::new(offset_sub);
-			#f_name.push_next(row);
+			let mut #f_name = <#f_type as #crate_name::db_model::DbModel>::DataCollector::new(offset_sub, mode);
+			#f_name.push_next(row, skipped)?;
 			let offset_sub = offset_sub + <#f_type as #crate_name::db_model::DbModel>::DataCollector::SIZE;
 		}
 	}).collect())
 }
 
-pub fn get_partial_data_init(columns_except_pk: &Vec<DbColumn<'_>>, relations: &Vec<DbRelation<'_>>) -> Result<Vec<proc_macro2::TokenStream>> {
+pub fn get_partial_data_init(crate_name: &syn::Path, columns_except_pk: &Vec<DbColumn<'_>>, relations: &Vec<DbRelation<'_>>) -> Result<Vec<proc_macro2::TokenStream>> {
 	Ok(columns_except_pk.iter().enumerate().map(|(index, f)| {
 		let f_name = f.rs_name_ident;
+		let f_name_str = &f.rs_name;
 		let index = index + 1;
-		quote! { #f_name: row.take_opt(offset + #index)?.ok()? }
+		match &f.convert {
+			Some(with) => quote! { #f_name: #crate_name::db_model::take_converted(row, offset + #index, MODEL, TABLE, #f_name_str, #with::from_value)? },
+			None => quote! { #f_name: #crate_name::db_model::take_column(row, offset + #index, MODEL, TABLE, #f_name_str)? },
+		}
 	}).chain(
 		relations.iter().map(|r| {
 			let f_name = r.rs_name_ident;
@@ -96,30 +102,69 @@ pub fn get_push_next_sub(relations: &Vec<DbRelation<'_>>) -> Result<Vec<proc_mac
 	Ok(relations.iter().map(|r| {
 		let f_name = r.rs_name_ident;
 		quote! {
-			current.#f_name.push_next(next_row);
+			current.#f_name.push_next(next_row, skipped)?;
 		}
 	}).collect())
 }
 
+/// The `Value` written for a column of `data`, through the converter of `#[convert(with = ...)]` if any.
+/// `DbJson` columns return early with `DbError::Encode` if their value cannot be serialized.
+fn get_column_value(crate_name: &syn::Path, db_model: &into_db_model::DbModel, c: &DbColumn<'_>, data: proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+	let rs_name = c.rs_name_ident;
+	match &c.convert {
+		Some(with) => quote! { #with::to_value(&#data.#rs_name) },
+		None if into_db_model::is_json_type(c.rs_type) => {
+			let model = db_model.from.rs_type.to_string();
+			let table = &db_model.from.table;
+			let field = rs_name.to_string();
+			quote! { #crate_name::db_json::encode_column(&#data.#rs_name, #model, #table, #field)? }
+		}
+		None => quote! { #crate_name::mysql_async::Value::from(&#data.#rs_name) },
+	}
+}
+
+/// The type giving the `SqlType` of a column. Converted columns are strings unless `#[ddl(sql_type = ...)]` says otherwise.
+fn get_sql_type_source(c: &DbColumn<'_>) -> proc_macro2::TokenStream {
+	match &c.convert {
+		Some(_) => quote! { ::std::string::String },
+		None => {
+			let rs_type = c.rs_type;
+			quote! { #rs_type }
+		}
+	}
+}
+
+/// Whether the column is nullable: from the field type, or from `#[ddl(nullable)]` for converted columns.
+/// Columns typed with `#[ddl(sql_type = ...)]` are nullable if spelled `Option<...>`, without requiring `SqlType`.
+fn get_nullable(crate_name: &syn::Path, c: &DbColumn<'_>) -> proc_macro2::TokenStream {
+	match (&c.convert, &c.ddl.sql_type) {
+		(Some(_), _) => {
+			let nullable = c.ddl.nullable.unwrap_or(false);
+			quote! { #nullable }
+		}
+		(None, Some(_)) => {
+			let nullable = c.ddl.nullable.unwrap_or_else(|| into_db_model::is_option_type(c.rs_type));
+			quote! { #nullable }
+		}
+		(None, None) => {
+			let rs_type = c.rs_type;
+			quote! { <#rs_type as #crate_name::db_schema::SqlType>::NULLABLE }
+		}
+	}
+}
+
 pub fn get_prepare_insert(crate_name: &syn::Path, db_model: &into_db_model::DbModel) -> Result<proc_macro2::TokenStream> {
 	let columns_except_pk = db_model.columns_except_pk.iter().filter(|c| !c.readonly).collect::<Vec<_>>();
-	let insert_col_list = columns_except_pk.iter().map(|c| &c.db_name as &str).collect::<Vec<&str>>().join(",");
-	let insert_str_with_fk = format!("INSERT INTO {} ({{}},{}) VALUES (@id_{{}}", db_model.from.table, insert_col_list);
-	let insert_str_without_fk = format!("INSERT INTO {} ({}) VALUES (", db_model.from.table, insert_col_list);
+	let insert_col_list = columns_except_pk.iter().map(|c| &c.db_name as &str).collect::<Vec<&str>>();
+	let placeholders = |n: usize| (0..n).map(|_| "?").collect::<Vec<&str>>().join(",");
+	let insert_str_with_fk = format!("INSERT INTO {} ({}) VALUES ({})", db_model.from.table, ["{}"].into_iter().chain(insert_col_list.iter().copied()).collect::<Vec<&str>>().join(","), placeholders(insert_col_list.len() + 1));
+	let insert_str_without_fk = format!("INSERT INTO {} ({}) VALUES ({})", db_model.from.table, insert_col_list.join(","), placeholders(insert_col_list.len()));
+	let col_count = insert_col_list.len();
 	let pk_rs_name = db_model.pk.rs_name_ident;
-	let query_push_with_fk = columns_except_pk.iter().map(|col| {
-		let rs_name = col.rs_name_ident;
+	let params_push = columns_except_pk.iter().map(|col| {
+		let value = get_column_value(crate_name, db_model, col, quote! { data });
 		quote! {
-			query.push(',');
-			query.push_str(&#crate_name::mysql_async::Value::from(&data.#rs_name).as_sql(false));
-		}
-	});
-	let query_push_without_fk = columns_except_pk.iter().enumerate().map(|(index, col)| {
-		let comma = if index == 0 { quote!() } else { quote!(query.push(',');) };
-		let rs_name = col.rs_name_ident;
-		quote! {
-			#comma
-			query.push_str(&#crate_name::mysql_async::Value::from(&data.#rs_name).as_sql(false));
+			params.push(#crate_name::db_model::WriteParam::Value(#value));
 		}
 	});
 	let relations: Vec<_> = db_model.relations.iter().map(|r| {
@@ -128,32 +173,35 @@ pub fn get_prepare_insert(crate_name: &syn::Path, db_model: &into_db_model::DbMo
 		let join_col = &r.join_col;
 		quote! {
 			for row in &data.#rs_name {
-				<#rs_type as #crate_name::db_model::DbModel>::prepare_insert(Some(#join_col), row, query, this_id);
+				<#rs_type as #crate_name::db_model::DbModel>::prepare_insert(Some((#join_col, &this_fk)), row, statements, this_id)?;
 			}
 		}
 	}).collect();
 	Ok(quote! {
 		if data.#pk_rs_name.is_none() {
 			let this_id = this_id + 1;
-			if let Some(fk_db_name) = fk {
-				query.push_str(&::std::format!(#insert_str_with_fk, fk_db_name, this_id - 1));
-				#(#query_push_with_fk)*
+			let (sql, mut params) = if let Some((fk_db_name, fk_value)) = fk {
+				let mut params = ::std::vec::Vec::with_capacity(#col_count + 1);
+				params.push(fk_value.clone());
+				(::std::format!(#insert_str_with_fk, fk_db_name), params)
 			} else {
-				query.push_str(#insert_str_without_fk);
-				#(#query_push_without_fk)*
-			}
-			query.push_str(");SET @id_");
-			query.push_str(&format!("{}", this_id));
-			query.push_str(" = LAST_INSERT_ID();");
-			
+				(#insert_str_without_fk.to_string(), ::std::vec::Vec::with_capacity(#col_count))
+			};
+			#(#params_push)*
+			statements.push(#crate_name::db_model::WriteStatement {
+				sql,
+				params,
+				insert_id: Some(this_id),
+			});
+			let this_fk = #crate_name::db_model::WriteParam::InsertId(this_id);
 			#(#relations)*
 		}
 	})
 }
 
 pub fn get_prepare_delete(crate_name: &syn::Path, db_model: &into_db_model::DbModel) -> Result<proc_macro2::TokenStream> {
-	let delete_str_with_fk = format!("DELETE FROM {} WHERE {{}}=@id_{{}} AND {}=@id_{{}};", db_model.from.table, db_model.pk.db_name);
-	let delete_str_without_fk = format!("DELETE FROM {} WHERE {}=@id_{{}};", db_model.from.table, db_model.pk.db_name);
+	let delete_str_with_fk = format!("DELETE FROM {} WHERE {{}}=? AND {}=?", db_model.from.table, db_model.pk.db_name);
+	let delete_str_without_fk = format!("DELETE FROM {} WHERE {}=?", db_model.from.table, db_model.pk.db_name);
 	let pk_rs_name = db_model.pk.rs_name_ident;
 	let relations: Vec<_> = db_model.relations.iter().map(|r| {
 		let rs_type = r.ty;
@@ -161,19 +209,27 @@ pub fn get_prepare_delete(crate_name: &syn::Path, db_model: &into_db_model::DbMo
 		let join_col = &r.join_col;
 		quote! {
 			for row in &data.#rs_name {
-				<#rs_type as #crate_name::db_model::DbModel>::prepare_delete(Some(#join_col), row, query, this_id);
+				<#rs_type as #crate_name::db_model::DbModel>::prepare_delete(Some((#join_col, &this_fk)), row, statements, this_id);
 			}
 		}
 	}).collect();
 	Ok(quote! {
 		if let Some(pk) = data.#pk_rs_name {
 			let this_id = this_id + 1;
-			query.push_str(&::std::format!("SET @id_{}={};", this_id, pk));
+			let this_fk = #crate_name::db_model::WriteParam::Value(#crate_name::mysql_async::Value::from(pk));
 			#(#relations)*
-			query.push_str(&if let Some(fk_db_name) = fk {
-				::std::format!(#delete_str_with_fk, fk_db_name, this_id - 1, this_id)
+			statements.push(if let Some((fk_db_name, fk_value)) = fk {
+				#crate_name::db_model::WriteStatement {
+					sql: ::std::format!(#delete_str_with_fk, fk_db_name),
+					params: vec![fk_value.clone(), this_fk],
+					insert_id: None,
+				}
 			} else {
-				::std::format!(#delete_str_without_fk, this_id)
+				#crate_name::db_model::WriteStatement {
+					sql: #delete_str_without_fk.to_string(),
+					params: vec![this_fk],
+					insert_id: None,
+				}
 			});
 		}
 	})
@@ -182,20 +238,17 @@ pub fn get_prepare_delete(crate_name: &syn::Path, db_model: &into_db_model::DbMo
 
 pub fn get_prepare_update(crate_name: &syn::Path, db_model: &into_db_model::DbModel) -> Result<proc_macro2::TokenStream> {
 	let columns_except_pk = db_model.columns_except_pk.iter().filter(|c| !c.readonly).collect::<Vec<_>>();
-	let update_str_prefix = format!("UPDATE {} SET ", db_model.from.table);
-	let update_str_with_fk_suffix = format!(" WHERE {{}}=@id_{{}} AND {}=@id_{{}};", db_model.pk.db_name);
-	let update_str_without_fk_suffix = format!(" WHERE {}=@id_{{}};", db_model.pk.db_name);
+	let update_str_prefix = format!("UPDATE {} SET {}", db_model.from.table, columns_except_pk.iter().map(|c| format!("{}=?", c.db_name)).collect::<Vec<String>>().join(","));
+	let update_str_with_fk = format!("{} WHERE {{}}=? AND {}=?", update_str_prefix, db_model.pk.db_name);
+	let update_str_without_fk = format!("{} WHERE {}=?", update_str_prefix, db_model.pk.db_name);
+	let col_count = columns_except_pk.len();
 	let pk_rs_name = db_model.pk.rs_name_ident;
-	let query_push_update_cols = columns_except_pk.iter().enumerate().map(|(index, c)| {
-		let comma = if index == 0 { quote!() } else { quote!(query.push(',');) };
-		let db_name_str = format!("{}=", c.db_name);
-		let rs_name = c.rs_name_ident;
+	let params_push = columns_except_pk.iter().map(|c| {
+		let value = get_column_value(crate_name, db_model, c, quote! { new_data });
 		quote! {
-			#comma
-			query.push_str(#db_name_str);
-			query.push_str(&#crate_name::mysql_async::Value::from(&new_data.#rs_name).as_sql(false));
+			params.push(#crate_name::db_model::WriteParam::Value(#value));
 		}
-	});
+	}).collect::<Vec<_>>();
 	let relations: Vec<_> = db_model.relations.iter().map(|r| {
 		let rs_type = r.ty;
 		let rs_name = r.rs_name_ident;
@@ -208,39 +261,382 @@ pub fn get_prepare_update(crate_name: &syn::Path, db_model: &into_db_model::DbMo
 			for row in &new_data.#rs_name {
 				if let Some(pk) = <#rs_type as #crate_name::db_model::DbModel>::get_pk(row) {
 					if let Some(old_row) = old_rows.remove(&pk) {
-						<#rs_type as #crate_name::db_model::DbModel>::prepare_update(Some(#join_col), row, old_row, query, this_id);
+						<#rs_type as #crate_name::db_model::DbModel>::prepare_update(Some((#join_col, &this_fk)), row, old_row, statements, this_id)?;
 					}
 				} else {
-					<#rs_type as #crate_name::db_model::DbModel>::prepare_insert(Some(#join_col), row, query, this_id);
+					<#rs_type as #crate_name::db_model::DbModel>::prepare_insert(Some((#join_col, &this_fk)), row, statements, this_id)?;
 				}
 			}
 			for (_, row) in old_rows {
-				<#rs_type as #crate_name::db_model::DbModel>::prepare_delete(Some(#join_col), row, query, this_id);
+				<#rs_type as #crate_name::db_model::DbModel>::prepare_delete(Some((#join_col, &this_fk)), row, statements, this_id);
 			}
 		}
 	}).collect();
-	if query_push_update_cols.len() != 0 {
-		Ok(quote! {
-			if let Some(pk) = new_data.#pk_rs_name {
-				let this_id = this_id + 1;
-				query.push_str(&::std::format!("SET @id_{}={};", this_id, pk));
-				query.push_str(#update_str_prefix);
-				#(#query_push_update_cols)*
-				if let Some(fk_db_name) = fk {
-					query.push_str(&::std::format!(#update_str_with_fk_suffix, fk_db_name, this_id - 1, this_id));
-				} else {
-					query.push_str(&::std::format!(#update_str_without_fk_suffix, this_id));
-				}
-				#(#relations)*
-			}
-		})
+	let update_statement = if !params_push.is_empty() {
+		quote! {
+			let mut params = ::std::vec::Vec::with_capacity(#col_count + 2);
+			#(#params_push)*
+			let sql = if let Some((fk_db_name, fk_value)) = fk {
+				params.push(fk_value.clone());
+				::std::format!(#update_str_with_fk, fk_db_name)
+			} else {
+				#update_str_without_fk.to_string()
+			};
+			params.push(this_fk.clone());
+			statements.push(#crate_name::db_model::WriteStatement {
+				sql,
+				params,
+				insert_id: None,
+			});
+		}
 	} else {
-		Ok(quote! {
-			if let Some(pk) = new_data.#pk_rs_name {
-				let this_id = this_id + 1;
-				query.push_str(&::std::format!("SET @id_{}={};", this_id, pk));
-				#(#relations)*
+		quote! {}
+	};
+	Ok(quote! {
+		if let Some(pk) = new_data.#pk_rs_name {
+			let this_id = this_id + 1;
+			let this_fk = #crate_name::db_model::WriteParam::Value(#crate_name::mysql_async::Value::from(pk));
+			#update_statement
+			#(#relations)*
+		}
+	})
+}
+
+pub fn get_columns_fields(crate_name: &syn::Path, pk: &DbColumn<'_>, pk_inner_type: &syn::Type, columns_except_pk: &[DbColumn<'_>]) -> Result<Vec<proc_macro2::TokenStream>> {
+	let pk_name = pk.rs_name_ident;
+	Ok([quote! { pub #pk_name: #crate_name::db_query::Column<#pk_inner_type> }].into_iter().chain(
+		columns_except_pk.iter().map(|c| {
+			let f_name = c.rs_name_ident;
+			let f_type = c.rs_type;
+			if c.convert.is_none() && into_db_model::is_json_type(f_type) {
+				quote! { pub #f_name: #crate_name::db_json::JsonDocument<#f_type> }
+			} else {
+				quote! { pub #f_name: #crate_name::db_query::Column<#f_type> }
 			}
 		})
+	).collect())
+}
+
+pub fn get_columns_init(crate_name: &syn::Path, pk: &DbColumn<'_>, columns_except_pk: &[DbColumn<'_>], sql_names: &[String]) -> Result<Vec<proc_macro2::TokenStream>> {
+	Ok([pk].into_iter().chain(columns_except_pk.iter()).zip(sql_names.iter()).map(|(c, sql_name)| {
+		let f_name = c.rs_name_ident;
+		match &c.convert {
+			Some(with) => quote! { #f_name: #crate_name::db_query::Column::with_encoder(#sql_name, |value| #with::to_value(&value)) },
+			None if into_db_model::is_json_type(c.rs_type) => quote! { #f_name: #crate_name::db_json::JsonDocument::new(#sql_name) },
+			None => quote! { #f_name: #crate_name::db_query::Column::new(#sql_name) },
+		}
+	}).collect())
+}
+
+fn get_column_ddl(crate_name: &syn::Path, ddl: &DdlAttribute) -> proc_macro2::TokenStream {
+	let option_u32 = |value: Option<u32>| match value {
+		Some(value) => quote! { ::std::option::Option::Some(#value) },
+		None => quote! { ::std::option::Option::None },
+	};
+	let option_str = |value: &Option<String>| match value {
+		Some(value) => quote! { ::std::option::Option::Some(#value) },
+		None => quote! { ::std::option::Option::None },
+	};
+	let length = option_u32(ddl.length);
+	let precision = option_u32(ddl.precision);
+	let scale = option_u32(ddl.scale);
+	let default = option_str(&ddl.default);
+	let sql_type = option_str(&ddl.sql_type);
+	let index = ddl.index;
+	let nullable = match ddl.nullable {
+		Some(nullable) => quote! { ::std::option::Option::Some(#nullable) },
+		None => quote! { ::std::option::Option::None },
+	};
+	quote! {
+		#crate_name::db_schema::ColumnDdl {
+			length: #length,
+			precision: #precision,
+			scale: #scale,
+			default: #default,
+			nullable: #nullable,
+			sql_type: #sql_type,
+			index: #index,
+		}
 	}
-}
\ No newline at end of file
+}
+
+pub fn get_table_defs(crate_name: &syn::Path, db_model: &into_db_model::DbModel, pk_inner_type: &syn::Type) -> Result<proc_macro2::TokenStream> {
+	let table = &db_model.from.table;
+	let pk_name = &db_model.pk.db_name;
+	let pk_ddl = get_column_ddl(crate_name, &db_model.pk.ddl);
+	let columns: Vec<_> = db_model.columns_except_pk.iter().filter(|c| !c.readonly).collect();
+	let columns_push = columns.iter().map(|c| {
+		let db_name = &c.db_name;
+		let rs_type = get_sql_type_source(c);
+		let ddl = get_column_ddl(crate_name, &c.ddl);
+		if c.ddl.sql_type.is_some() {
+			let nullable = get_nullable(crate_name, c);
+			quote! {
+				columns.push(#crate_name::db_schema::ColumnDef::with_sql_type(#db_name, #nullable, &#ddl));
+			}
+		} else {
+			quote! {
+				columns.push(#crate_name::db_schema::ColumnDef::new::<#rs_type>(#db_name, &#ddl));
+			}
+		}
+	});
+	let relations = db_model.relations.iter().map(|r| {
+		let rs_type = r.ty;
+		let join_col = &r.join_col;
+		quote! {
+			<#rs_type as #crate_name::db_schema::DbSchema>::table_defs(::std::option::Option::Some(&#crate_name::db_schema::ForeignKey {
+				column: #join_col,
+				table: #table,
+				pk: #pk_name,
+				sql_type: &pk_sql_type,
+			}), tables);
+		}
+	});
+	Ok(quote! {
+		let pk_ddl = #pk_ddl;
+		let mut columns = ::std::vec![#crate_name::db_schema::ColumnDef::pk::<#pk_inner_type>(#pk_name, &pk_ddl)];
+		#(#columns_push)*
+		tables.push(#crate_name::db_schema::TableDef::new(#table, columns, fk));
+		let pk_sql_type = #crate_name::db_schema::column_sql_type::<#pk_inner_type>(&pk_ddl);
+		#(#relations)*
+	})
+}
+
+pub enum ColumnSource {
+	/// `table` is the root table or a join alias.
+	Column { table: String, column: String },
+	Expression(String),
+}
+
+pub fn get_column_source(column: &DbColumn<'_>, root_table: &str) -> ColumnSource {
+	let default_column_name = column.rs_name_ident;
+	if let Some(from) = &column.from_attribute {
+		let mut from = from.clone();
+		let column_name = if let Some(column_name) = from.named_arrs.remove("column") {
+			column_name.0
+		} else if let Some(column_name) = from.attr {
+			column_name
+		} else {
+			default_column_name.to_string()
+		};
+		let expression = from.named_arrs.remove("expression");
+		let table = if let Some(table) = from.named_arrs.get("table") {
+			&table.0
+		} else {
+			root_table
+		};
+		if let Some(expression) = expression {
+			ColumnSource::Expression(expression.0)
+		} else {
+			ColumnSource::Column { table: table.to_string(), column: column_name }
+		}
+	} else {
+		ColumnSource::Column { table: root_table.to_string(), column: default_column_name.to_string() }
+	}
+}
+
+pub fn get_model_info(crate_name: &syn::Path, db_model: &into_db_model::DbModel, pk_inner_type: &syn::Type) -> Result<proc_macro2::TokenStream> {
+	let name = db_model.from.rs_type.to_string();
+	let table = &db_model.from.table;
+	let resolve_alias = |alias: &str| db_model.from.join_list.iter()
+		.find(|(_, a)| a == alias)
+		.map(|(table, _)| table.clone())
+		.unwrap_or_else(|| alias.to_string());
+	let column_info = |field: &str, source: ColumnSource, readonly: bool, sql_type: proc_macro2::TokenStream, optional: proc_macro2::TokenStream| match source {
+		ColumnSource::Column { table, column } => {
+			let table = resolve_alias(&table);
+			quote! {
+				#crate_name::db_schema::ColumnInfo {
+					field: #field,
+					table: ::std::option::Option::Some(#table),
+					column: #column,
+					readonly: #readonly,
+					sql_type: #sql_type,
+					optional: #optional,
+				}
+			}
+		}
+		ColumnSource::Expression(expression) => quote! {
+			#crate_name::db_schema::ColumnInfo {
+				field: #field,
+				table: ::std::option::Option::None,
+				column: #expression,
+				readonly: #readonly,
+				sql_type: ::std::option::Option::None,
+				optional: false,
+			}
+		},
+	};
+	let pk_ddl = get_column_ddl(crate_name, &db_model.pk.ddl);
+	let pk = column_info(
+		&db_model.pk.rs_name,
+		ColumnSource::Column { table: table.clone(), column: db_model.pk.db_name.clone() },
+		false,
+		quote! { ::std::option::Option::Some(#crate_name::db_schema::column_sql_type::<#pk_inner_type>(&#pk_ddl)) },
+		quote! { <#pk_inner_type as #crate_name::db_schema::SqlType>::NULLABLE },
+	);
+	// Readonly columns are not written, so their type is only checked when given with `#[ddl(sql_type)]`.
+	let columns = db_model.columns_except_pk.iter().map(|c| {
+		let sql_type = match (&c.ddl.sql_type, c.readonly) {
+			(Some(sql_type), _) => quote! { ::std::option::Option::Some(::std::string::String::from(#sql_type)) },
+			(None, true) => quote! { ::std::option::Option::None },
+			(None, false) => {
+				let rs_type = get_sql_type_source(c);
+				let ddl = get_column_ddl(crate_name, &c.ddl);
+				quote! { ::std::option::Option::Some(#crate_name::db_schema::column_sql_type::<#rs_type>(&#ddl)) }
+			}
+		};
+		let (source, optional) = if c.readonly {
+			let optional = c.ddl.nullable.unwrap_or_else(|| into_db_model::is_option_type(c.rs_type));
+			(get_column_source(c, table), quote! { #optional })
+		} else {
+			(ColumnSource::Column { table: table.clone(), column: c.db_name.clone() }, get_nullable(crate_name, c))
+		};
+		column_info(&c.rs_name, source, c.readonly, sql_type, optional)
+	});
+	let joins = db_model.from.join_list.iter().map(|(table, alias)| quote! {
+		#crate_name::db_schema::JoinInfo { table: #table, alias: #alias }
+	});
+	let relations = db_model.relations.iter().map(|r| {
+		let field = &r.rs_name;
+		let fk = &r.join_col;
+		let rs_type = r.ty;
+		quote! {
+			#crate_name::db_schema::RelationInfo {
+				field: #field,
+				fk: #fk,
+				model: <#rs_type as #crate_name::db_schema::DbSchema>::model_info,
+			}
+		}
+	});
+	Ok(quote! {
+		#crate_name::lazy_static! {
+			static ref MODEL_INFO: #crate_name::db_schema::ModelInfo = #crate_name::db_schema::ModelInfo {
+				name: #name,
+				table: #table,
+				joins: ::std::vec![#(#joins),*],
+				pk: #pk,
+				columns: ::std::vec![#(#columns),*],
+				relations: ::std::vec![#(#relations),*],
+			};
+		}
+		&MODEL_INFO
+	})
+}
+
+/// `TsType` and `TsExport` for models marked `#[fe_export]`.
+pub fn get_fe_export(crate_name: &syn::Path, name: &syn::Ident, db_model: &into_db_model::DbModel) -> Result<proc_macro2::TokenStream> {
+	let name_str = name.to_string();
+	let header = format!("export interface {} {{\n", name_str);
+	let columns = [&db_model.pk].into_iter().chain(db_model.columns_except_pk.iter());
+	let fields = columns.map(|c| {
+		let rs_name = &c.rs_name;
+		let rs_type = c.rs_type;
+		let readonly = c.readonly;
+		let ts_type = match &c.convert {
+			Some(_) => quote! { "unknown" },
+			None => quote! { &<#rs_type as #crate_name::fe_export::TsType>::ts_type() },
+		};
+		quote! {
+			#crate_name::fe_export::ts_field(&mut res, #rs_name, #ts_type, #readonly);
+		}
+	}).chain(db_model.relations.iter().map(|r| {
+		let rs_name = &r.rs_name;
+		let ty = r.ty;
+		quote! {
+			#crate_name::fe_export::ts_field(&mut res, #rs_name, &<::std::vec::Vec<#ty> as #crate_name::fe_export::TsType>::ts_type(), false);
+		}
+	}));
+	let dependencies = db_model.relations.iter().map(|r| {
+		let ty = r.ty;
+		quote! {
+			<::std::vec::Vec<#ty> as #crate_name::fe_export::TsType>::export_dependencies(exporter);
+		}
+	});
+	Ok(quote! {
+		impl #crate_name::fe_export::TsType for #name {
+			fn ts_type() -> ::std::string::String {
+				#name_str.to_string()
+			}
+			fn export_dependencies(exporter: &mut #crate_name::fe_export::TsExporter) {
+				#(#dependencies)*
+			}
+		}
+		impl #crate_name::fe_export::TsExport for #name {
+			const NAME: &'static str = #name_str;
+			fn ts_interface() -> ::std::string::String {
+				let mut res = ::std::string::String::from(#header);
+				#(#fields)*
+				res.push_str("}\n");
+				res
+			}
+		}
+	})
+}
+
+/// `JsonSchemaType` and `JsonSchemaModel` for models marked `#[json_schema]`: the pk, relations, nullable and
+/// `#[serde(default)]` fields are not required, readonly fields are `readOnly`.
+pub fn get_json_schema(crate_name: &syn::Path, name: &syn::Ident, db_model: &into_db_model::DbModel) -> Result<proc_macro2::TokenStream> {
+	let name_str = name.to_string();
+	let reference = format!("#/$defs/{}", name_str);
+	let pk = &db_model.pk;
+	let pk_properties = std::iter::once((pk, false));
+	let columns = pk_properties.chain(db_model.columns_except_pk.iter().map(|c| (c, !c.readonly && !c.serde_default)));
+	let properties = columns.map(|(c, writable)| {
+		let rs_name = &c.rs_name;
+		let rs_type = c.rs_type;
+		let readonly = c.readonly;
+		let ddl = get_column_ddl(crate_name, &c.ddl);
+		let (schema, nullable) = match &c.convert {
+			Some(_) => {
+				let nullable = c.ddl.nullable.unwrap_or(false);
+				(quote! { #crate_name::serde_json::json!({}) }, quote! { #nullable })
+			}
+			None => (
+				quote! { <#rs_type as #crate_name::json_schema::JsonSchemaType>::json_schema(&#ddl) },
+				quote! { <#rs_type as #crate_name::json_schema::JsonSchemaType>::NULLABLE },
+			),
+		};
+		quote! {
+			#crate_name::json_schema::JsonProperty {
+				name: #rs_name,
+				schema: #schema,
+				required: #writable && !#nullable,
+				read_only: #readonly,
+			}
+		}
+	}).chain(db_model.relations.iter().map(|r| {
+		let rs_name = &r.rs_name;
+		let ty = r.ty;
+		quote! {
+			#crate_name::json_schema::JsonProperty {
+				name: #rs_name,
+				schema: <::std::vec::Vec<#ty> as #crate_name::json_schema::JsonSchemaType>::json_schema(&::std::default::Default::default()),
+				required: false,
+				read_only: false,
+			}
+		}
+	}));
+	let definitions = db_model.relations.iter().map(|r| {
+		let ty = r.ty;
+		quote! {
+			<::std::vec::Vec<#ty> as #crate_name::json_schema::JsonSchemaType>::add_definitions(definitions);
+		}
+	});
+	Ok(quote! {
+		impl #crate_name::json_schema::JsonSchemaType for #name {
+			fn json_schema(_: &#crate_name::db_schema::ColumnDdl) -> #crate_name::serde_json::Value {
+				#crate_name::serde_json::json!({ "$ref": #reference })
+			}
+			fn add_definitions(definitions: &mut #crate_name::serde_json::Map<::std::string::String, #crate_name::serde_json::Value>) {
+				#(#definitions)*
+			}
+		}
+		impl #crate_name::json_schema::JsonSchemaModel for #name {
+			const NAME: &'static str = #name_str;
+			fn json_schema_definition() -> #crate_name::serde_json::Value {
+				#crate_name::json_schema::model_schema(#name_str, ::std::vec![#(#properties),*])
+			}
+		}
+	})
+}
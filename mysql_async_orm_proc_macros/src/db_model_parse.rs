@@ -12,6 +12,12 @@ pub struct RelationAttribute {
 	pub fk: String,
 }
 
+/// `#[convert(with = path)]`, a module with `from_value` and `to_value` functions.
+/// The field type says nothing about the column, so its DDL is `VARCHAR(255)` unless `#[ddl(sql_type)]` is given.
+pub struct ConvertAttribute {
+	pub with: syn::Path,
+}
+
 #[allow(dead_code)]
 pub struct NamedAttribute {
 	pub name: syn::Ident,
@@ -67,3 +73,80 @@ impl Parse for RelationAttribute {
 		})
 	}
 }
+
+impl Parse for ConvertAttribute {
+	fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+		let content;
+		let _ = syn::parenthesized!(content in input);
+		let name: syn::Ident = content.parse()?;
+		if name != "with" {
+			return Err(syn::Error::new(name.span(), "Unknown convert option"));
+		}
+		let _: syn::Token![=] = content.parse()?;
+		let with: syn::Path = content.parse()?;
+		if !content.is_empty() {
+			return Err(content.error("convert only takes a with path"));
+		}
+		Ok(ConvertAttribute {
+			with,
+		})
+	}
+}
+
+#[derive(Default)]
+pub struct DdlAttribute {
+	pub length: Option<u32>,
+	pub precision: Option<u32>,
+	pub scale: Option<u32>,
+	pub default: Option<String>,
+	pub nullable: Option<bool>,
+	pub sql_type: Option<String>,
+	pub index: bool,
+}
+
+impl Parse for DdlAttribute {
+	fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+		let content;
+		let _ = syn::parenthesized!(content in input);
+		let mut res = DdlAttribute::default();
+		while !content.is_empty() {
+			let name: syn::Ident = content.parse()?;
+			let value: Option<syn::Lit> = if content.peek(syn::Token![=]) {
+				let _: syn::Token![=] = content.parse()?;
+				Some(content.parse()?)
+			} else {
+				None
+			};
+			let int = |value: &Option<syn::Lit>| match value {
+				Some(syn::Lit::Int(value)) => value.base10_parse::<u32>(),
+				_ => Err(syn::Error::new(name.span(), format!("{} must be an integer", name))),
+			};
+			let string = |value: &Option<syn::Lit>| match value {
+				Some(syn::Lit::Str(value)) => Ok(value.value()),
+				_ => Err(syn::Error::new(name.span(), format!("{} must be a string", name))),
+			};
+			match name.to_string().as_str() {
+				"length" => res.length = Some(int(&value)?),
+				"precision" => res.precision = Some(int(&value)?),
+				"scale" => res.scale = Some(int(&value)?),
+				"default" => res.default = Some(string(&value)?),
+				"sql_type" => res.sql_type = Some(string(&value)?),
+				"nullable" => res.nullable = Some(match value {
+					None => true,
+					Some(syn::Lit::Bool(value)) => value.value,
+					_ => return Err(syn::Error::new(name.span(), "nullable must be a bool")),
+				}),
+				"index" => res.index = match value {
+					None => true,
+					Some(syn::Lit::Bool(value)) => value.value,
+					_ => return Err(syn::Error::new(name.span(), "index must be a bool")),
+				},
+				_ => return Err(syn::Error::new(name.span(), "Unknown ddl option")),
+			}
+			if !content.is_empty() {
+				let _: syn::Token![,] = content.parse()?;
+			}
+		}
+		Ok(res)
+	}
+}
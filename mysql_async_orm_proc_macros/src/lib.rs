@@ -3,8 +3,9 @@ use syn::Result;
 
 mod db_model_parse;
 mod db_model_macro;
+mod db_enum_macro;
 
-const CRATE_NAME: &'static str = "mysql_async_orm";
+const CRATE_NAME: &str = "mysql_async_orm";
 
 
 #[derive(Clone)]
@@ -15,7 +16,7 @@ impl syn::parse::Parse for CrateNameAttribute {
 	fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
 		let content;
 		let _ = syn::parenthesized!(content in input);
-		let input: syn::LitStr = (&content).parse()?;
+		let input: syn::LitStr = content.parse()?;
 		Ok(CrateNameAttribute {
 			value: input.value(),
 		})
@@ -28,6 +29,25 @@ fn generate_unique_ident(prefix: &str) -> syn::Ident {
     syn::Ident::new(&ident, proc_macro2::Span::call_site())
 }
 
+/// `::mysql_async_orm`, or the path given with `#[mysql_async_orm_crate_path("...")]`.
+fn get_crate_name(attributes: &std::collections::HashMap<String, &syn::Attribute>) -> Result<syn::Path> {
+	let mut segments = syn::punctuated::Punctuated::<syn::PathSegment, syn::token::Colon2>::new();
+	let path = match attributes.get("mysql_async_orm_crate_path") {
+		Some(mysql_async_orm_crate_path) => {
+			let crate_name: CrateNameAttribute = syn::parse(mysql_async_orm_crate_path.tokens.clone().into())?;
+			crate_name.value
+		}
+		None => CRATE_NAME.to_string(),
+	};
+	for p in path.split("::") {
+		segments.push(syn::PathSegment { ident: syn::Ident::new(p, proc_macro2::Span::call_site()), arguments: syn::PathArguments::None });
+	}
+	Ok(syn::Path {
+		leading_colon: Some(syn::token::Colon2::default()),
+		segments,
+	})
+}
+
 fn db_model_macro(input: &syn::DeriveInput) -> Result<proc_macro2::TokenStream> {
 	let name = &input.ident;
 	let fields = db_model_macro::get_struct_fields(input)?;
@@ -39,29 +59,14 @@ fn db_model_macro(input: &syn::DeriveInput) -> Result<proc_macro2::TokenStream>
 	let pk_inner_type = db_model_macro::into_db_model::get_inner_type(pk_type)?;
 	let pk_name_ident = db_model.pk.rs_name_ident;
 	let pk_db_string = format!("{}.{}", db_model.from.table, db_model.pk.db_name);
+	let pk_db_name = &db_model.pk.db_name;
+	let pk_name_str = &db_model.pk.rs_name;
 	
-	let crate_name = if let Some(mysql_async_orm_crate_path) = struct_attributes.get("mysql_async_orm_crate_path") {
-		let crate_name: CrateNameAttribute = syn::parse(mysql_async_orm_crate_path.tokens.clone().into())?;
-		let mut segments = syn::punctuated::Punctuated::<syn::PathSegment, syn::token::Colon2>::new();
-		for p in crate_name.value.split("::") {
-			segments.push(syn::PathSegment { ident: syn::Ident::new(p, proc_macro2::Span::call_site()), arguments: syn::PathArguments::None });
-		}
-		syn::Path {
-			leading_colon: Some(syn::token::Colon2::default()),
-			segments,
-		}
-	} else {
-		let mut segments = syn::punctuated::Punctuated::<syn::PathSegment, syn::token::Colon2>::new();
-		segments.push(syn::PathSegment { ident: syn::Ident::new(CRATE_NAME, proc_macro2::Span::call_site()), arguments: syn::PathArguments::None });
-		syn::Path {
-			leading_colon: Some(syn::token::Colon2::default()),
-			segments,
-		}
-	};
+	let crate_name = get_crate_name(&struct_attributes)?;
 	
 	let partial_data_fields = db_model_macro::get_partial_data_fields(&crate_name, &db_model.columns_except_pk, &db_model.relations)?;
 	let partial_data_init_collectors = db_model_macro::get_partial_data_init_collectors(&crate_name, &db_model.relations)?;
-	let partial_data_init = db_model_macro::get_partial_data_init(&db_model.columns_except_pk, &db_model.relations)?;
+	let partial_data_init = db_model_macro::get_partial_data_init(&crate_name, &db_model.columns_except_pk, &db_model.relations)?;
 	let partial_data_destruct = db_model_macro::get_partial_data_destruct(&db_model.columns_except_pk, &db_model.relations)?;
 	let partial_data_build = db_model_macro::get_partial_data_build(&db_model.columns_except_pk, &db_model.relations)?;
 	let push_next_sub = db_model_macro::get_push_next_sub(&db_model.relations)?;
@@ -69,41 +74,66 @@ fn db_model_macro(input: &syn::DeriveInput) -> Result<proc_macro2::TokenStream>
 	let prepare_insert = db_model_macro::get_prepare_insert(&crate_name, &db_model)?;
 	let prepare_update = db_model_macro::get_prepare_update(&crate_name, &db_model)?;
 	let prepare_delete = db_model_macro::get_prepare_delete(&crate_name, &db_model)?;
-	
-	let get_by_pk_sql = format!("SELECT {{}} FROM {{}} {{}} WHERE {}=?", pk_db_string);
-	
-	let sql_names = [pk_db_string.clone()].into_iter().chain(db_model.columns_except_pk.iter().map(|f| {
-		let default_column_name = f.rs_name_ident;
-		if let Some(from) = &f.from_attribute {
-			let mut from = from.clone();
-			let column_name = if let Some(column_name) = from.named_arrs.remove("column") {
-				column_name.0
-			} else if let Some(column_name) = from.attr {
-				column_name
-			} else {
-				default_column_name.to_string()
-			};
-			let expression = from.named_arrs.remove("expression");
-			let table = if let Some(table) = from.named_arrs.get("table") {
-				&table.0
-			} else {
-				&db_model.from.table
-			};
-			if let Some(expression) = expression {
-				expression.0
-			} else {
-				format!("{}.{}", table, column_name)
+	let db_schema = if struct_attributes.contains_key("db_schema") {
+		let table_defs = db_model_macro::get_table_defs(&crate_name, &db_model, pk_inner_type)?;
+		let model_info = db_model_macro::get_model_info(&crate_name, &db_model, pk_inner_type)?;
+		quote! {
+			impl #crate_name::db_schema::DbSchema for #name {
+				fn table_defs(fk: ::std::option::Option<&#crate_name::db_schema::ForeignKey<'_>>, tables: &mut ::std::vec::Vec<#crate_name::db_schema::TableDef>) {
+					#table_defs
+				}
+				fn model_info() -> &'static #crate_name::db_schema::ModelInfo {
+					#model_info
+				}
+			}
+			impl #name {
+				/// CREATE TABLE statements for this model and, after it, for its relations.
+				pub fn create_table_sql() -> ::std::vec::Vec<::std::string::String> {
+					let mut tables = ::std::vec::Vec::new();
+					<Self as #crate_name::db_schema::DbSchema>::table_defs(::std::option::Option::None, &mut tables);
+					tables.iter().map(#crate_name::db_schema::TableDef::create_sql).collect()
+				}
 			}
-		} else {
-			format!("{}.{}", db_model.from.table, default_column_name)
 		}
-	}));
+	} else {
+		quote! {}
+	};
+	let json_schema = if struct_attributes.contains_key("json_schema") {
+		let json_schema = db_model_macro::get_json_schema(&crate_name, name, &db_model)?;
+		quote! {
+			#json_schema
+			impl #name {
+				/// JSON Schema of the model and its relations, e.g. to validate payloads before `exec_insert`.
+				pub fn json_schema() -> #crate_name::serde_json::Value {
+					#crate_name::json_schema::schema_for::<Self>()
+				}
+			}
+		}
+	} else {
+		quote! {}
+	};
+	let fe_export = if struct_attributes.contains_key("fe_export") {
+		db_model_macro::get_fe_export(&crate_name, name, &db_model)?
+	} else {
+		quote! {}
+	};
+	
+	let sql_names: Vec<String> = [pk_db_string.clone()].into_iter().chain(db_model.columns_except_pk.iter().map(|f| {
+		match db_model_macro::get_column_source(f, &db_model.from.table) {
+			db_model_macro::ColumnSource::Column { table, column } => format!("{}.{}", table, column),
+			db_model_macro::ColumnSource::Expression(expression) => expression,
+		}
+	})).collect();
+	let columns_fields = db_model_macro::get_columns_fields(&crate_name, &db_model.pk, pk_inner_type, &db_model.columns_except_pk)?;
+	let columns_init = db_model_macro::get_columns_init(&crate_name, &db_model.pk, &db_model.columns_except_pk, &sql_names)?;
+	let get_by_pk_condition = format!("{}=?", pk_db_string);
 	let null_format_string = (0..(db_model.columns_except_pk.len() + 1)).map(|_| "NULL").collect::<Vec<&'static str>>().join(",");
 	let joins = &db_model.from.joins;
 	let table = &db_model.from.table;
 	let from = &db_model.from.from;
-	let select_format_string = sql_names.collect::<Vec<String>>().join(",");
-	let sql_fn = if db_model.relations.len() == 0 {
+	let root_from = format!("{} {}", from, joins);
+	let select_format_string = sql_names.join(",");
+	let sql_fn = if db_model.relations.is_empty() {
 		quote! {
 			(#table, #null_format_string.to_string(), vec![0], vec![(#from, #select_format_string.to_string(), #joins.to_string())])
 		}
@@ -176,103 +206,110 @@ fn db_model_macro(input: &syn::DeriveInput) -> Result<proc_macro2::TokenStream>
 		impl #crate_name::db_model::DbModel for #name {
 			type DataCollector = #mod_name::DataCollector;
 			type PrimaryKey = #pk_inner_type;
-			fn prepare_insert(fk: ::std::option::Option<&::std::primitive::str>, data: &Self, query: &mut ::std::string::String, this_id: ::std::primitive::usize) {
+			type Columns = #mod_name::Columns;
+			fn prepare_insert(fk: ::std::option::Option<(&::std::primitive::str, &#crate_name::db_model::WriteParam)>, data: &Self, statements: &mut ::std::vec::Vec<#crate_name::db_model::WriteStatement>, this_id: ::std::primitive::usize) -> ::std::result::Result<(), #crate_name::db_connection::DbError> {
 				#prepare_insert
+				::std::result::Result::Ok(())
 			}
-			fn prepare_update(fk: ::std::option::Option<&::std::primitive::str>, new_data: &Self, old_data: &Self, query: &mut ::std::string::String, this_id: ::std::primitive::usize) {
+			fn prepare_update(fk: ::std::option::Option<(&::std::primitive::str, &#crate_name::db_model::WriteParam)>, new_data: &Self, old_data: &Self, statements: &mut ::std::vec::Vec<#crate_name::db_model::WriteStatement>, this_id: ::std::primitive::usize) -> ::std::result::Result<(), #crate_name::db_connection::DbError> {
 				#prepare_update
+				::std::result::Result::Ok(())
 			}
-			fn prepare_delete(fk: ::std::option::Option<&::std::primitive::str>, data: &Self, query: &mut ::std::string::String, this_id: ::std::primitive::usize) {
+			fn prepare_delete(fk: ::std::option::Option<(&::std::primitive::str, &#crate_name::db_model::WriteParam)>, data: &Self, statements: &mut ::std::vec::Vec<#crate_name::db_model::WriteStatement>, this_id: ::std::primitive::usize) {
 				#prepare_delete
 			}
 			fn get_pk(&self) -> ::std::option::Option<Self::PrimaryKey> {
 				self.#pk_name_ident
 			}
+			fn columns() -> Self::Columns {
+				#mod_name::Columns {
+					#(#columns_init,)*
+				}
+			}
+			fn model_sql() -> &'static #crate_name::db_model::ModelSql {
+				#crate_name::lazy_static! {
+					static ref MODEL_SQL: #crate_name::db_model::ModelSql = #crate_name::db_model::ModelSql::new::<<#name as #crate_name::db_model::DbModel>::DataCollector>(#pk_db_string, #root_from);
+				}
+				&MODEL_SQL
+			}
 		}
 		impl #name {
-			pub fn vec_from_rows(mut rows: Vec<#crate_name::mysql_async::Row>) -> ::std::vec::Vec<Self> {
-				let mut collector = <<Self as #crate_name::db_model::DbModel>::DataCollector as #crate_name::db_model::DbModelDataCollector>::new(0);
-				for row in rows.iter_mut() {
-					#crate_name::db_model::DbModelDataCollector::push_next(&mut collector, row);
+			pub fn vec_from_rows(rows: Vec<#crate_name::mysql_async::Row>) -> ::std::result::Result<::std::vec::Vec<Self>, #crate_name::db_connection::DbError> {
+				#crate_name::db_model::vec_from_rows(rows, #crate_name::db_model::DecodeMode::Strict, &mut ::std::vec::Vec::new())
+			}
+			pub fn columns() -> <Self as #crate_name::db_model::DbModel>::Columns {
+				<Self as #crate_name::db_model::DbModel>::columns()
+			}
+			pub fn query() -> #crate_name::db_query::Query<Self> {
+				#crate_name::db_query::Query::new()
+			}
+			pub async fn find(filter: #crate_name::db_query::Filter, connection: &mut impl #crate_name::db_connection::QueryableConn) -> ::std::result::Result<::std::vec::Vec<Self>, #crate_name::db_connection::DbError> {
+				Self::query().filter(filter).fetch(connection).await
+			}
+			pub fn stream<'a, C: #crate_name::db_connection::QueryableConn>(connection: &'a mut C) -> impl #crate_name::futures_util::Stream<Item = ::std::result::Result<Self, #crate_name::db_connection::DbError>> + 'a {
+				Self::query().stream(connection)
+			}
+			pub async fn find_all(connection: &mut impl #crate_name::db_connection::QueryableConn) -> ::std::result::Result<::std::vec::Vec<Self>, #crate_name::db_connection::DbError> {
+				Self::query().fetch(connection).await
+			}
+			pub async fn get_by_pks(pks: &[#pk_inner_type], connection: &mut impl #crate_name::db_connection::QueryableConn) -> ::std::result::Result<::std::vec::Vec<Self>, #crate_name::db_connection::DbError> {
+				let chunk_size = #crate_name::db_model::MAX_PLACEHOLDERS / <#name as #crate_name::db_model::DbModel>::model_sql().branch_count();
+				let mut res = ::std::vec::Vec::with_capacity(pks.len());
+				for chunk in pks.chunks(chunk_size) {
+					res.extend(Self::find(Self::columns().#pk_name_ident.in_list(chunk.iter().copied()), connection).await?);
+				}
+				if pks.len() > chunk_size {
+					// Each chunk comes ordered by pk, the concatenation does not.
+					res.sort_by(|a, b| a.#pk_name_ident.partial_cmp(&b.#pk_name_ident).unwrap_or(::std::cmp::Ordering::Equal));
 				}
-				#crate_name::db_model::DbModelDataCollector::build(collector)
+				::std::result::Result::Ok(res)
+			}
+			pub async fn page(offset: u64, limit: u64, connection: &mut impl #crate_name::db_connection::QueryableConn) -> ::std::result::Result<#crate_name::db_query::Page<Self, u64>, #crate_name::db_connection::DbError> {
+				Self::query().page(offset, limit, connection).await
+			}
+			pub async fn page_after<T: #crate_name::mysql_async::prelude::FromValue + ::std::clone::Clone>(
+				column: #crate_name::db_query::Column<T>,
+				after: ::std::option::Option<#crate_name::db_query::Cursor<T, #pk_inner_type>>,
+				limit: u64,
+				connection: &mut impl #crate_name::db_connection::QueryableConn,
+			) -> ::std::result::Result<#crate_name::db_query::Page<Self, #crate_name::db_query::Cursor<T, #pk_inner_type>>, #crate_name::db_connection::DbError> {
+				Self::query().page_after(column, after, limit, connection).await
 			}
 			pub async fn get_by_pk(pk: #pk_inner_type, connection: &mut impl #crate_name::db_connection::QueryableConn) -> ::std::result::Result<Self, #crate_name::db_connection::DbError> {
 				#crate_name::lazy_static! {
-					static ref SQL: ::std::string::String = {
-						let (_, _, order_by, sql) = <<#name as #crate_name::db_model::DbModel>::DataCollector as #crate_name::db_model::DbModelDataCollector>::sql();
-						let sql = sql.iter().map(|(table, select, from)| format!(
-							#get_by_pk_sql,
-							select, table, from
-						)).collect::<::std::vec::Vec<_>>().join(" UNION ALL ");
-						let sql = std::format!("{} ORDER BY {};", sql, order_by.iter().map(|i| (i + 1).to_string()).collect::<::std::vec::Vec<_>>().join(","));
-						sql
-					};
-					static ref PARAM_COUNT: usize = <<#name as #crate_name::db_model::DbModel>::DataCollector as #crate_name::db_model::DbModelDataCollector>::sql().3.len();
+					static ref SQL: ::std::string::String = <#name as #crate_name::db_model::DbModel>::model_sql().select(::std::option::Option::Some(#get_by_pk_condition));
 				}
-				let params: ::std::vec::Vec<_> = (0..*PARAM_COUNT).map(|_| pk).collect();
-				let sql: &str = &*SQL;
-				let mut data = Self::vec_from_rows(connection.exec(sql, &params).await?);
+				let params: ::std::vec::Vec<_> = (0..<#name as #crate_name::db_model::DbModel>::model_sql().branch_count()).map(|_| pk).collect();
+				let sql: &str = &SQL;
+				let rows = connection.exec(sql, &params).await?;
+				let utc_offset = #crate_name::db_connection::QueryableConn::session_utc_offset(connection);
+				let mut data = #crate_name::db_date::with_session_utc_offset(utc_offset, || Self::vec_from_rows(rows))?;
 				let mut data = data.drain(..);
-				data.next().ok_or_else(|| #crate_name::db_connection::DbError::Other(::std::format!("Not found. SQL: {}\nPARAMS: {:#?}", sql, params).into()))
+				data.next().ok_or_else(|| #crate_name::db_connection::OrmError::NotFound {
+					table: #table,
+					pk: ::std::format!("{:?}", pk),
+				})
 			}
 			pub async fn exec_update(&self, connection: &mut impl #crate_name::db_connection::QueryableConn) -> ::std::result::Result<Self, #crate_name::db_connection::DbError> {
-				if let ::std::option::Option::Some(pk) = &self.#pk_name_ident {
-					let old_value = Self::get_by_pk(*pk, connection).await?;
-					let mut query = ::std::string::String::new();
-					<Self as #crate_name::db_model::DbModel>::prepare_update(::std::option::Option::None, self, &old_value, &mut query, 0);
-					if ::std::cfg!(debug_assertions) {
-						if let ::std::result::Result::Err(error) = connection.query_drop(&query).await {
-							println!("Error: {}\nIn: ```{}```", error, query);
-							return ::std::result::Result::Err(error);
-						}
-					} else {
-						connection.query_drop(query).await?;
-					}
-					::std::result::Result::Ok(old_value)
-				} else {
-					::std::result::Result::Err(#crate_name::db_connection::DbError::Other("Pk must be Some".into()))
+				if let ::std::option::Option::Some(mut transaction) = #crate_name::db_connection::QueryableConn::begin_atomic(connection).await? {
+					let result = #mod_name::exec_update(self, &mut transaction).await;
+					return transaction.finish(result).await;
 				}
+				#mod_name::exec_update(self, connection).await
 			}
 			pub async fn exec_delete(#pk_name_ident: #pk_inner_type, connection: &mut impl #crate_name::db_connection::QueryableConn) -> ::std::result::Result<Self, #crate_name::db_connection::DbError> {
-				let old_value = Self::get_by_pk(#pk_name_ident, connection).await?;
-				let mut query = ::std::string::String::new();
-				<Self as #crate_name::db_model::DbModel>::prepare_delete(::std::option::Option::None, &old_value, &mut query, 0);
-				if ::std::cfg!(debug_assertions) {
-					if let ::std::result::Result::Err(error) = connection.query_drop(&query).await {
-						println!("Error: {}\nIn: ```{}```", error, query);
-						return ::std::result::Result::Err(error);
-					}
-				} else {
-					connection.query_drop(query).await?;
+				if let ::std::option::Option::Some(mut transaction) = #crate_name::db_connection::QueryableConn::begin_atomic(connection).await? {
+					let result = #mod_name::exec_delete(#pk_name_ident, &mut transaction).await;
+					return transaction.finish(result).await;
 				}
-				Ok(old_value)
+				#mod_name::exec_delete(#pk_name_ident, connection).await
 			}
 			pub async fn exec_insert(&self, connection: &mut impl #crate_name::db_connection::QueryableConn) -> ::std::result::Result<#pk_inner_type, #crate_name::db_connection::DbError> {
-				if self.#pk_name_ident.is_none() {
-					let mut query = ::std::string::String::new();
-					<Self as #crate_name::db_model::DbModel>::prepare_insert(::std::option::Option::None, self, &mut query, 0);
-					query.push_str("SELECT @id_1;");
-					let mut res = if ::std::cfg!(debug_assertions) {
-						match connection.query_iter(&query).await {
-							::std::result::Result::Ok(res) => res,
-							::std::result::Result::Err(error) => {
-								println!("Error: {}\nIn: ```{}```", error, query);
-								return ::std::result::Result::Err(error);
-							}
-						}
-					} else {
-						connection.query_iter(query).await?
-					};
-					let mut last_row = ::std::option::Option::None;
-					while !res.is_empty() {
-						res.for_each(|row| last_row = Some(row)).await?;
-					}
-					let mut last_row = last_row.ok_or_else(|| #crate_name::db_connection::DbError::Other("Unknown error".into()))?;
-					last_row.take(0).ok_or_else(|| #crate_name::db_connection::DbError::Other("Unknown error".into()))
-				} else {
-					::std::result::Result::Err(#crate_name::db_connection::DbError::Other("Pk must be None".into()))
+				if let ::std::option::Option::Some(mut transaction) = #crate_name::db_connection::QueryableConn::begin_atomic(connection).await? {
+					let result = #mod_name::exec_insert(self, &mut transaction).await;
+					return transaction.finish(result).await;
 				}
+				#mod_name::exec_insert(self, connection).await
 			}
 		}
 		mod #mod_name {
@@ -280,16 +317,64 @@ fn db_model_macro(input: &syn::DeriveInput) -> Result<proc_macro2::TokenStream>
 			use super::*;
 			use #crate_name::mysql_async::Row;
 			
+			const MODEL: &str = ::std::stringify!(#name);
+			const TABLE: &str = #table;
+			
+			pub struct Columns {
+				#(#columns_fields,)*
+			}
+			
+			pub(super) async fn exec_update(data: &#name, connection: &mut impl #crate_name::db_connection::QueryableConn) -> ::std::result::Result<#name, #crate_name::db_connection::DbError> {
+				if let ::std::option::Option::Some(pk) = &data.#pk_name_ident {
+					let old_value = #name::get_by_pk(*pk, connection).await?;
+					let mut statements = ::std::vec::Vec::new();
+					<#name as #crate_name::db_model::DbModel>::prepare_update(::std::option::Option::None, data, &old_value, &mut statements, 0)?;
+					#crate_name::db_model::exec_write(connection, statements).await?;
+					::std::result::Result::Ok(old_value)
+				} else {
+					::std::result::Result::Err(#crate_name::db_connection::OrmError::PkMustBeSome)
+				}
+			}
+			
+			pub(super) async fn exec_delete(#pk_name_ident: #pk_inner_type, connection: &mut impl #crate_name::db_connection::QueryableConn) -> ::std::result::Result<#name, #crate_name::db_connection::DbError> {
+				let old_value = #name::get_by_pk(#pk_name_ident, connection).await?;
+				let mut statements = ::std::vec::Vec::new();
+				<#name as #crate_name::db_model::DbModel>::prepare_delete(::std::option::Option::None, &old_value, &mut statements, 0);
+				#crate_name::db_model::exec_write(connection, statements).await?;
+				::std::result::Result::Ok(old_value)
+			}
+			
+			pub(super) async fn exec_insert(data: &#name, connection: &mut impl #crate_name::db_connection::QueryableConn) -> ::std::result::Result<#pk_inner_type, #crate_name::db_connection::DbError> {
+				if data.#pk_name_ident.is_none() {
+					let mut statements = ::std::vec::Vec::new();
+					<#name as #crate_name::db_model::DbModel>::prepare_insert(::std::option::Option::None, data, &mut statements, 0)?;
+					let id = #crate_name::db_model::exec_write(connection, statements).await?
+						.ok_or(#crate_name::db_connection::OrmError::MissingInsertId)?;
+					<#pk_inner_type as #crate_name::mysql_async::prelude::FromValue>::from_value_opt(#crate_name::mysql_async::Value::from(id))
+						.map_err(|_| #crate_name::db_connection::OrmError::RowDecode {
+							model: MODEL,
+							table: TABLE,
+							field: #pk_name_str,
+							column: #pk_db_name.to_string(),
+							index: 0,
+							sql_type: "LAST_INSERT_ID()".to_string(),
+						})
+				} else {
+					::std::result::Result::Err(#crate_name::db_connection::OrmError::PkMustBeNone)
+				}
+			}
+			
 			struct PartialData {
 				#pk_name_ident: #pk_type,
 				#(#partial_data_fields,)*
 			}
 		
 			impl PartialData {
-				fn new(pk: #pk_type, offset: usize, row: &mut Row) -> Option<Self> {
+				#[allow(clippy::ptr_arg)]
+				fn new(pk: #pk_type, offset: usize, row: &mut Row, mode: #crate_name::db_model::DecodeMode, skipped: &mut ::std::vec::Vec<#crate_name::db_connection::DbError>) -> ::std::result::Result<Self, #crate_name::db_connection::DbError> {
 					let offset_sub = offset + <<#name as #crate_name::db_model::DbModel>::DataCollector as #crate_name::db_model::DbModelDataCollector>::SIZE;
 					#(#partial_data_init_collectors)*
-					Some(PartialData {
+					Ok(PartialData {
 						#pk_name_ident: pk,
 						#(#partial_data_init,)*
 					})
@@ -306,6 +391,9 @@ fn db_model_macro(input: &syn::DeriveInput) -> Result<proc_macro2::TokenStream>
 		
 			pub struct DataCollector {
 				offset: usize,
+				mode: #crate_name::db_model::DecodeMode,
+				skipped: #pk_type,
+				undecodable_pk: Option<#crate_name::mysql_async::Value>,
 				current: Option<PartialData>,
 				partial_result: Vec<#name>,
 			}
@@ -313,30 +401,59 @@ fn db_model_macro(input: &syn::DeriveInput) -> Result<proc_macro2::TokenStream>
 				type Item = #name;
 				const SIZE: usize = #db_model_col_count;
 				
-				fn sql() -> (&'static str, String, Vec<usize>, Vec<(&'static str, String, String)>) {
+				fn sql() -> #crate_name::db_model::DataCollectorSql {
 					#sql_fn
 				}
 				
-				fn new(offset: usize) -> Self {
+				fn new(offset: usize, mode: #crate_name::db_model::DecodeMode) -> Self {
 					DataCollector {
 						offset,
+						mode,
+						skipped: None,
+						undecodable_pk: None,
 						current: None,
 						partial_result: Vec::new(),
 					}
 				}
 		
-				fn push_next(&mut self, next_row: &mut Row) -> Option<()> {
-					let #pk_name_ident = next_row.take_opt(self.offset)?.ok();
+				fn push_next(&mut self, next_row: &mut Row, skipped: &mut ::std::vec::Vec<#crate_name::db_connection::DbError>) -> ::std::result::Result<(), #crate_name::db_connection::DbError> {
+					let mut raw_pk = None;
+					let #pk_name_ident: #pk_type = match #crate_name::db_model::take_pk(next_row, self.offset, MODEL, TABLE, #pk_name_str, &mut raw_pk) {
+						Ok(pk) => pk,
+						Err(_) if raw_pk.is_some() && raw_pk == self.undecodable_pk => return Ok(()),
+						Err(error) => {
+							self.undecodable_pk = raw_pk;
+							return self.mode.skip_or_fail(error, skipped);
+						}
+					};
+					if #pk_name_ident.is_none() || #pk_name_ident == self.skipped {
+						return Ok(());
+					}
 					if let Some(current) = &mut self.current {
 						if #pk_name_ident == current.#pk_name_ident {
 							#(#push_next_sub)*
-						} else {
-							self.partial_result.push(std::mem::replace(current, PartialData::new(#pk_name_ident, self.offset, next_row)?).build());
+							return Ok(());
+						}
+					}
+					match PartialData::new(#pk_name_ident, self.offset, next_row, self.mode, skipped) {
+						Ok(next) => {
+							if let Some(current) = self.current.replace(next) {
+								self.partial_result.push(current.build());
+							}
+							Ok(())
+						}
+						Err(error) => {
+							if let Some(current) = self.current.take() {
+								self.partial_result.push(current.build());
+							}
+							self.skipped = #pk_name_ident;
+							self.mode.skip_or_fail(error, skipped)
 						}
-					} else {
-						self.current = PartialData::new(#pk_name_ident, self.offset, next_row);
 					}
-					Some(())
+				}
+		
+				fn take_completed(&mut self) -> Vec<#name> {
+					std::mem::take(&mut self.partial_result)
 				}
 		
 				fn build(mut self) -> Vec<#name> {
@@ -349,6 +466,9 @@ fn db_model_macro(input: &syn::DeriveInput) -> Result<proc_macro2::TokenStream>
 			}
 		}
 		
+		#json_schema
+		#db_schema
+		#fe_export
 	};
 	
 	//println!("{}", res.to_string());
@@ -356,12 +476,24 @@ fn db_model_macro(input: &syn::DeriveInput) -> Result<proc_macro2::TokenStream>
 	Ok(res)
 }
 
-#[proc_macro_derive(DbModel, attributes(from, pk, relation, readonly, mysql_async_orm_crate_path))]
+#[proc_macro_derive(DbModel, attributes(from, pk, relation, readonly, ddl, convert, db_schema, json_schema, fe_export, mysql_async_orm_crate_path))]
 pub fn db_model(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
 	let input = syn::parse_macro_input!(input as syn::DeriveInput);
 	db_model_macro(&input).unwrap_or_else(syn::Error::into_compile_error).into()
 }
 
+/// Maps a fieldless enum to a MySQL `ENUM` (or to integer codes with `#[db_enum(integer)]`),
+/// so that it can be a `DbModel` field or, with at most 64 variants, the member type of a `DbSet`.
+#[proc_macro_derive(DbEnum, attributes(db_enum, mysql_async_orm_crate_path))]
+pub fn db_enum(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+	let input = syn::parse_macro_input!(input as syn::DeriveInput);
+	let attributes = db_model_macro::get_attributes(input.attrs.iter());
+	get_crate_name(&attributes)
+		.and_then(|crate_name| db_enum_macro::db_enum_macro(&crate_name, &input))
+		.unwrap_or_else(syn::Error::into_compile_error)
+		.into()
+}
+
 fn sql_order_by_macro(input: proc_macro::TokenStream) -> Result<proc_macro2::TokenStream> {
 	struct SqlOrderByInput(syn::Ident, #[allow(dead_code)] syn::Token![,], Array, #[allow(dead_code)] syn::Token![,], syn::Expr, #[allow(dead_code)] syn::Token![,], syn::Expr, #[allow(dead_code)] syn::Token![,], syn::LitStr, #[allow(dead_code)] Option<syn::Token![,]>);
 	impl syn::parse::Parse for SqlOrderByInput {
@@ -379,7 +511,7 @@ fn sql_order_by_macro(input: proc_macro::TokenStream) -> Result<proc_macro2::Tok
 	}
 	let SqlOrderByInput(order_by_var, _, cols, _, sql_prefix, _, sql_suffix, _, default_order_by_suffix, _) = syn::parse(input)?;
 	let default_order_by_suffix = default_order_by_suffix.value();
-	let default_order_by_suffix = if default_order_by_suffix != "" {
+	let default_order_by_suffix = if !default_order_by_suffix.is_empty() {
 		format!(" ORDER BY {default_order_by_suffix} ")
 	} else {
 		"".into()
@@ -395,13 +527,13 @@ fn sql_order_by_macro(input: proc_macro::TokenStream) -> Result<proc_macro2::Tok
 			#index_2 => Ok(concat!(#sql_prefix, #order_by_2, #sql_suffix)),
 		}
 	});
-	Ok((quote! {
+	Ok(quote! {
 		match #order_by_var {
 			0 => Ok(concat!(#sql_prefix, #default_order_by_suffix, #sql_suffix)),
 			#(#cols)*
 			_ => Err(()),
 		}
-	}).into())
+	})
 }
 
 #[proc_macro]
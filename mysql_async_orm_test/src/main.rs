@@ -79,5 +79,13 @@ async fn main() {
 	
 	
 	let articolo = Articolo::get_by_pk(1, &mut conn).await.unwrap();
-	println!("{:#?}", articolo)
+	println!("{:#?}", articolo);
+	println!("{} ({})", articolo.codice, articolo.unita_di_misura);
+	for componente in articolo.distinte_base.iter().flat_map(|distinta| &distinta.articoli) {
+		println!("{} {}: {} {} a {}", componente.codice, componente.descrizione, componente.qta, componente.unita_di_misura, componente.costo);
+	}
+	
+	let columns = Articolo::columns();
+	let articoli = Articolo::find(columns.codice.like("A%").and(columns.scorta_minima.gt(0.0)), &mut conn).await.unwrap();
+	println!("{:#?}", articoli)
 }
\ No newline at end of file
@@ -0,0 +1,53 @@
+#![cfg(feature = "chrono")]
+
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+use mysql_async_orm::{db_mock::MockConn, mysql_async::Value, DbModel};
+
+#[derive(DbModel, Debug, Clone, PartialEq)]
+#[from("turni")]
+pub struct Turno {
+	#[pk]
+	id: Option<u32>,
+	giorno: NaiveDate,
+	inizio: NaiveTime,
+	registrato: NaiveDateTime,
+	chiuso: Option<NaiveDateTime>,
+}
+
+fn turno() -> Turno {
+	let giorno = NaiveDate::from_ymd_opt(2024, 2, 29).unwrap();
+	Turno {
+		id: None,
+		giorno,
+		inizio: NaiveTime::from_hms_opt(6, 30, 0).unwrap(),
+		registrato: giorno.and_hms_micro_opt(5, 59, 58, 250_000).unwrap(),
+		chiuso: None,
+	}
+}
+
+#[tokio::test]
+async fn chrono_fields_decode() {
+	let mut conn = MockConn::new();
+	conn.expect_regex("^SELECT turni").returning(&["id", "giorno", "inizio", "registrato", "chiuso"], vec![vec![
+		1u32.into(),
+		Value::Date(2024, 2, 29, 0, 0, 0, 0),
+		Value::Time(false, 0, 6, 30, 0, 0),
+		Value::Date(2024, 2, 29, 5, 59, 58, 250_000),
+		Value::NULL,
+	]]);
+	assert_eq!(Turno::find_all(&mut conn).await.unwrap(), vec![Turno { id: Some(1), ..turno() }]);
+}
+
+#[tokio::test]
+async fn chrono_fields_are_written_as_dates() {
+	let mut conn = MockConn::recording();
+	assert_eq!(turno().exec_insert(&mut conn).await.unwrap(), 1);
+	let executed = conn.executed();
+	assert_eq!(executed[0].sql, "INSERT INTO turni (giorno,inizio,registrato,chiuso) VALUES (?,?,?,?)");
+	assert_eq!(executed[0].params, vec![
+		Value::Date(2024, 2, 29, 0, 0, 0, 0),
+		Value::Time(false, 0, 6, 30, 0, 0),
+		Value::Date(2024, 2, 29, 5, 59, 58, 250_000),
+		Value::NULL,
+	]);
+}
@@ -0,0 +1,97 @@
+use mysql_async_orm::{
+	db_connection::DbError,
+	db_mock::MockConn,
+	db_schema::DbSchema,
+	mysql_async::{FromValueError, Value},
+	DbModel,
+};
+
+mod si_no {
+	use mysql_async_orm::mysql_async::{FromValueError, Value};
+
+	pub fn from_value(value: Value) -> Result<bool, FromValueError> {
+		match &value {
+			Value::Bytes(b) if b == b"S" => Ok(true),
+			Value::Bytes(b) if b == b"N" => Ok(false),
+			_ => Err(FromValueError(value)),
+		}
+	}
+
+	pub fn to_value(value: &bool) -> Value {
+		Value::from(if *value { "S" } else { "N" })
+	}
+}
+
+mod codici {
+	use super::{FromValueError, Value};
+
+	pub fn from_value(value: Value) -> Result<Vec<String>, FromValueError> {
+		match &value {
+			Value::Bytes(b) => match std::str::from_utf8(b) {
+				Ok(text) => Ok(text.split(';').filter(|s| !s.is_empty()).map(str::to_string).collect()),
+				Err(_) => Err(FromValueError(value)),
+			},
+			_ => Err(FromValueError(value)),
+		}
+	}
+
+	pub fn to_value(value: &[String]) -> Value {
+		Value::from(value.join(";"))
+	}
+}
+
+#[derive(DbModel, Debug, Clone, PartialEq)]
+#[from("fornitori")]
+#[db_schema]
+pub struct Fornitore {
+	#[pk]
+	pub id: Option<u32>,
+	#[convert(with = si_no)]
+	#[ddl(sql_type = "CHAR(1)")]
+	pub attivo: bool,
+	#[convert(with = codici)]
+	pub codici: Vec<String>,
+}
+
+fn fornitore() -> Fornitore {
+	Fornitore { id: Some(1), attivo: true, codici: vec!["A".to_string(), "B".to_string()] }
+}
+
+#[tokio::test]
+async fn converted_columns_are_decoded_with_from_value() {
+	let mut conn = MockConn::new();
+	conn.expect_regex("^SELECT fornitori").returning(&["id", "attivo", "codici"], vec![vec![1u32.into(), "S".into(), "A;B".into()]]);
+	assert_eq!(Fornitore::find_all(&mut conn).await.unwrap(), vec![fornitore()]);
+}
+
+#[tokio::test]
+async fn a_failing_converter_reports_the_field() {
+	let mut conn = MockConn::new();
+	conn.expect_regex("^SELECT fornitori").returning(&["id", "attivo", "codici"], vec![vec![1u32.into(), "X".into(), "".into()]]);
+	match Fornitore::find_all(&mut conn).await {
+		Err(DbError::RowDecode { model, field, index, .. }) => assert_eq!((model, field, index), ("Fornitore", "attivo", 1)),
+		other => panic!("expected RowDecode, got {:?}", other),
+	}
+}
+
+#[tokio::test]
+async fn converted_columns_are_written_and_filtered_with_to_value() {
+	let mut conn = MockConn::recording();
+	Fornitore { id: None, ..fornitore() }.exec_insert(&mut conn).await.unwrap();
+	assert_eq!(conn.executed()[0].sql, "INSERT INTO fornitori (attivo,codici) VALUES (?,?)");
+	assert_eq!(conn.executed()[0].params, vec![Value::from("S"), Value::from("A;B")]);
+	let filter = Fornitore::columns().attivo.eq(false);
+	assert_eq!(filter.sql(), "fornitori.attivo = ?");
+	assert_eq!(filter.params(), [Value::from("N")]);
+}
+
+#[test]
+fn converted_columns_default_to_varchar() {
+	let mut tables = Vec::new();
+	Fornitore::table_defs(None, &mut tables);
+	assert_eq!(tables[0].create_sql(), "CREATE TABLE fornitori (
+	id INT UNSIGNED NOT NULL AUTO_INCREMENT PRIMARY KEY,
+	attivo CHAR(1) NOT NULL,
+	codici VARCHAR(255) NOT NULL
+);");
+}
@@ -0,0 +1,160 @@
+use mysql_async_orm::{
+	db_enum::{DbEnum, DbSet},
+	db_mock::MockConn,
+	db_schema::{ColumnDdl, SqlType},
+	fe_export::TsType,
+	json_schema::JsonSchemaType,
+	mysql_async::{from_value_opt, Value},
+	serde_json::json,
+	DbEnum, DbModel,
+};
+
+#[derive(DbEnum, Debug, Clone, Copy, PartialEq)]
+#[db_enum(rename_all = "snake_case")]
+pub enum Reparto {
+	Taglio,
+	PiegaturaLamiera,
+	#[db_enum(rename = "verniciatura a polvere")]
+	Verniciatura,
+}
+
+#[derive(DbEnum, Debug, Clone, Copy, PartialEq)]
+#[db_enum(integer)]
+pub enum Controllo {
+	Scartato = -1,
+	Nessuno,
+	Visivo,
+	Dimensionale = 5,
+}
+
+#[derive(DbModel, Debug, Clone, PartialEq)]
+#[from("articoli")]
+pub struct Articolo {
+	#[pk]
+	pub id: Option<u32>,
+	pub controllo: Controllo,
+	pub reparti: DbSet<Reparto>,
+}
+
+fn sql_type<T: SqlType>() -> String {
+	T::sql_type(&ColumnDdl::default())
+}
+
+#[test]
+fn string_enums_use_the_renamed_members() {
+	assert_eq!(Reparto::NAMES, ["taglio", "piegatura_lamiera", "verniciatura a polvere"]);
+	assert_eq!(sql_type::<Reparto>(), "ENUM('taglio','piegatura_lamiera','verniciatura a polvere')");
+	assert_eq!(Value::from(Reparto::PiegaturaLamiera), Value::from("piegatura_lamiera"));
+	assert_eq!(from_value_opt::<Reparto>(Value::from("verniciatura a polvere")).unwrap(), Reparto::Verniciatura);
+	assert!(from_value_opt::<Reparto>(Value::from("Taglio")).is_err());
+	assert!(from_value_opt::<Reparto>(Value::Int(0)).is_err());
+	assert_eq!(Reparto::json_schema(&ColumnDdl::default()), json!({
+		"type": "string",
+		"enum": ["taglio", "piegatura_lamiera", "verniciatura a polvere"],
+	}));
+	assert_eq!(Reparto::ts_type(), r#""taglio" | "piegatura_lamiera" | "verniciatura a polvere""#);
+}
+
+#[test]
+fn integer_enums_use_the_discriminants() {
+	assert_eq!(Controllo::NAMES, ["Scartato", "Nessuno", "Visivo", "Dimensionale"]);
+	assert_eq!(sql_type::<Controllo>(), "TINYINT");
+	assert_eq!(Value::from(Controllo::Scartato), Value::Int(-1));
+	assert_eq!(Value::from(Controllo::Visivo), Value::Int(1));
+	assert_eq!(from_value_opt::<Controllo>(Value::Int(5)).unwrap(), Controllo::Dimensionale);
+	assert_eq!(from_value_opt::<Controllo>(Value::UInt(0)).unwrap(), Controllo::Nessuno);
+	assert_eq!(from_value_opt::<Controllo>(Value::from("-1")).unwrap(), Controllo::Scartato);
+	assert!(from_value_opt::<Controllo>(Value::Int(2)).is_err());
+	assert!(from_value_opt::<Controllo>(Value::from("Visivo")).is_err());
+	assert_eq!(Controllo::json_schema(&ColumnDdl::default()), json!({ "type": "integer", "enum": [-1, 0, 1, 5] }));
+	assert_eq!(Controllo::ts_type(), "-1 | 0 | 1 | 5");
+}
+
+#[test]
+fn integer_enums_use_the_smallest_column() {
+	#[derive(DbEnum)]
+	#[db_enum(integer)]
+	enum U8 { A, B = 255 }
+	#[derive(DbEnum)]
+	#[db_enum(integer)]
+	enum I8 { A = -128, B = 127 }
+	#[derive(DbEnum)]
+	#[db_enum(integer)]
+	enum U16 { A, B = 256 }
+	#[derive(DbEnum)]
+	#[db_enum(integer)]
+	enum I16 { A = -129, B }
+	#[derive(DbEnum)]
+	#[db_enum(integer)]
+	#[repr(i64)]
+	enum U32 { A, B = 4294967295 }
+	#[derive(DbEnum)]
+	#[db_enum(integer)]
+	enum I32 { A = -2147483648, B = 32768 }
+	#[derive(DbEnum)]
+	#[db_enum(integer)]
+	#[repr(i64)]
+	enum I64 { A = -1, B = 2147483648 }
+	assert_eq!(sql_type::<U8>(), "TINYINT UNSIGNED");
+	assert_eq!(sql_type::<I8>(), "TINYINT");
+	assert_eq!(sql_type::<U16>(), "SMALLINT UNSIGNED");
+	assert_eq!(sql_type::<I16>(), "SMALLINT");
+	assert_eq!(sql_type::<U32>(), "INT UNSIGNED");
+	assert_eq!(sql_type::<I32>(), "INT");
+	assert_eq!(sql_type::<I64>(), "BIGINT");
+}
+
+#[test]
+fn sets_decode_text_and_bits() {
+	let set: DbSet<Reparto> = from_value_opt(Value::from("taglio,verniciatura a polvere")).unwrap();
+	assert_eq!(set.iter().collect::<Vec<_>>(), vec![Reparto::Taglio, Reparto::Verniciatura]);
+	assert_eq!(set.bits(), 0b101);
+	assert_eq!(from_value_opt::<DbSet<Reparto>>(Value::UInt(0b101)).unwrap(), set);
+	assert_eq!(from_value_opt::<DbSet<Reparto>>(Value::Int(0b101)).unwrap(), set);
+	assert!(from_value_opt::<DbSet<Reparto>>(Value::from("")).unwrap().is_empty());
+	assert_eq!(Value::from(set), Value::from("taglio,verniciatura a polvere"));
+	assert_eq!(sql_type::<DbSet<Reparto>>(), "SET('taglio','piegatura_lamiera','verniciatura a polvere')");
+}
+
+#[test]
+fn sets_reject_unknown_members() {
+	assert!(from_value_opt::<DbSet<Reparto>>(Value::from("taglio,saldatura")).is_err());
+	assert!(from_value_opt::<DbSet<Reparto>>(Value::UInt(0b1000)).is_err());
+	assert!(DbSet::<Reparto>::from_bits(0b1000).is_none());
+}
+
+#[test]
+fn set_members_are_inserted_once() {
+	let mut set = DbSet::new();
+	assert!(set.insert(Reparto::Verniciatura));
+	assert!(set.insert(Reparto::Taglio));
+	assert!(!set.insert(Reparto::Taglio));
+	assert_eq!(set.len(), 2);
+	assert!(set.contains(&Reparto::Taglio));
+	assert!(set.remove(&Reparto::Taglio));
+	assert!(!set.remove(&Reparto::Taglio));
+	assert_eq!(set.to_string(), "verniciatura a polvere");
+}
+
+#[tokio::test]
+async fn enum_fields_round_trip_through_the_model() {
+	let mut conn = MockConn::new();
+	conn.expect("SELECT articoli.id,articoli.controllo,articoli.reparti FROM articoli  WHERE (articoli.controllo = ?) AND (FIND_IN_SET(?, articoli.reparti)) ORDER BY 1;")
+		.with_params([Value::Int(5), Value::from("taglio")])
+		.returning(&["id", "controllo", "reparti"], vec![vec![1u32.into(), Value::Int(5), Value::from("taglio,piegatura_lamiera")]]);
+	let columns = Articolo::columns();
+	let found = Articolo::query()
+		.filter(columns.controllo.eq(Controllo::Dimensionale).and(columns.reparti.contains(Reparto::Taglio)))
+		.fetch(&mut conn)
+		.await
+		.unwrap();
+	let articolo = Articolo {
+		id: Some(1),
+		controllo: Controllo::Dimensionale,
+		reparti: [Reparto::Taglio, Reparto::PiegaturaLamiera].into_iter().collect(),
+	};
+	assert_eq!(found, vec![articolo.clone()]);
+	let mut conn = MockConn::recording();
+	Articolo { id: None, ..articolo }.exec_insert(&mut conn).await.unwrap();
+	assert_eq!(conn.executed()[0].params, vec![Value::Int(5), Value::from("taglio,piegatura_lamiera")]);
+}
@@ -0,0 +1,122 @@
+#![cfg(feature = "serde")]
+
+use std::collections::BTreeMap;
+
+use mysql_async_orm::{
+	db_connection::DbError,
+	db_json::DbJson,
+	db_mock::MockConn,
+	mysql_async::{from_value_opt, Value},
+	serde_json::{self, json},
+	DbModel,
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Parametri {
+	tolleranza: f64,
+	misure: Vec<u32>,
+}
+
+#[derive(DbModel, Debug, Clone, PartialEq)]
+#[from("articoli")]
+pub struct Articolo {
+	#[pk]
+	pub id: Option<u32>,
+	pub parametri: DbJson<Parametri>,
+	pub note: Option<DbJson<serde_json::Value>>,
+}
+
+#[derive(DbModel, Debug, Clone, PartialEq)]
+#[from("griglie")]
+pub struct Griglia {
+	#[pk]
+	pub id: Option<u32>,
+	pub celle: DbJson<BTreeMap<(u8, u8), u8>>,
+}
+
+fn parametri() -> Parametri {
+	Parametri { tolleranza: 0.5, misure: vec![7, 9] }
+}
+
+#[test]
+fn json_is_decoded_from_text_only() {
+	let decoded: DbJson<Parametri> = from_value_opt(Value::from(r#"{"tolleranza":0.5,"misure":[7,9]}"#)).unwrap();
+	assert_eq!(decoded.into_inner(), parametri());
+	assert!(from_value_opt::<DbJson<Parametri>>(Value::from(r#"{"tolleranza":"0.5"}"#)).is_err());
+	assert!(from_value_opt::<DbJson<Parametri>>(Value::from("{")).is_err());
+	assert!(from_value_opt::<DbJson<u32>>(Value::UInt(1)).is_err());
+	assert_eq!(from_value_opt::<Option<DbJson<u32>>>(Value::NULL).unwrap(), None);
+}
+
+#[tokio::test]
+async fn json_columns_are_loaded_and_written() {
+	let mut conn = MockConn::new();
+	conn.expect_regex("^SELECT articoli").returning(&["id", "parametri", "note"], vec![
+		vec![1u32.into(), Value::from(r#"{"tolleranza":0.5,"misure":[7,9]}"#), Value::from(r#"{"urgente":true}"#)],
+		vec![2u32.into(), Value::from(r#"{"tolleranza":1.0,"misure":[]}"#), Value::NULL],
+	]);
+	let found = Articolo::find_all(&mut conn).await.unwrap();
+	assert_eq!(found[0].parametri.misure, vec![7, 9]);
+	assert_eq!(found[0].note, Some(DbJson(json!({ "urgente": true }))));
+	assert_eq!(found[1].note, None);
+	let mut conn = MockConn::recording();
+	Articolo { id: None, ..found[0].clone() }.exec_insert(&mut conn).await.unwrap();
+	assert_eq!(conn.executed()[0].params, vec![
+		Value::from(r#"{"tolleranza":0.5,"misure":[7,9]}"#),
+		Value::from(r#"{"urgente":true}"#),
+	]);
+}
+
+#[tokio::test]
+async fn invalid_json_fails_the_row() {
+	let mut conn = MockConn::new();
+	conn.expect_regex("^SELECT articoli").returning(&["id", "parametri", "note"], vec![vec![1u32.into(), Value::from("[]"), Value::NULL]]);
+	match Articolo::find_all(&mut conn).await {
+		Err(DbError::RowDecode { field, .. }) => assert_eq!(field, "parametri"),
+		other => panic!("expected RowDecode, got {:?}", other),
+	}
+}
+
+#[tokio::test]
+async fn unserializable_json_fails_the_write_before_executing() {
+	let griglia = Griglia { id: None, celle: DbJson(BTreeMap::from([((0, 0), 1)])) };
+	let mut conn = MockConn::recording();
+	match griglia.exec_insert(&mut conn).await {
+		Err(DbError::Encode { model, table, field, .. }) => assert_eq!((model, table, field), ("Griglia", "griglie", "celle")),
+		other => panic!("expected Encode, got {:?}", other),
+	}
+	assert!(conn.executed().is_empty());
+}
+
+#[test]
+fn json_filters_bind_the_path_and_the_value() {
+	let parametri = Articolo::columns().parametri;
+	let filter = parametri.path("$.tolleranza").le(&0.5).unwrap();
+	assert_eq!(filter.sql(), "JSON_EXTRACT(articoli.parametri, ?) <= CAST(? AS JSON)");
+	assert_eq!(filter.params(), [Value::from("$.tolleranza"), Value::from("0.5")]);
+	let filter = parametri.path("$.misure").contains(&7).unwrap();
+	assert_eq!(filter.sql(), "JSON_CONTAINS(articoli.parametri, CAST(? AS JSON), ?)");
+	assert_eq!(filter.params(), [Value::from("7"), Value::from("$.misure")]);
+	let filter = parametri.path("$.note").exists();
+	assert_eq!(filter.sql(), "JSON_CONTAINS_PATH(articoli.parametri, 'one', ?)");
+	assert_eq!(filter.params(), [Value::from("$.note")]);
+	let filter = parametri.json_contains(&json!({ "misure": [9] })).unwrap();
+	assert_eq!(filter.sql(), "JSON_CONTAINS(articoli.parametri, CAST(? AS JSON))");
+	assert_eq!(filter.params(), [Value::from(r#"{"misure":[9]}"#)]);
+	assert_eq!(Articolo::columns().note.is_null().sql(), "articoli.note IS NULL");
+	let key: BTreeMap<(u8, u8), u8> = BTreeMap::from([((0, 0), 1)]);
+	assert!(parametri.path("$.misure").eq(&key).is_err());
+}
+
+#[tokio::test]
+async fn json_filters_run_through_the_query() {
+	let parametri = Articolo::columns().parametri;
+	let mut conn = MockConn::new();
+	conn.expect("SELECT articoli.id,articoli.parametri,articoli.note FROM articoli  WHERE JSON_EXTRACT(articoli.parametri, ?) > CAST(? AS JSON) ORDER BY 1;")
+		.with_params([Value::from("$.tolleranza"), Value::from("0.1")])
+		.returning(&["id", "parametri", "note"], Vec::<Vec<Value>>::new());
+	let found = Articolo::query().filter(parametri.path("$.tolleranza").gt(&0.1).unwrap()).fetch(&mut conn).await.unwrap();
+	assert!(found.is_empty());
+	assert_eq!(conn.pending(), 0);
+}
@@ -0,0 +1,51 @@
+#![cfg(feature = "rust_decimal")]
+
+use std::str::FromStr;
+
+use mysql_async_orm::{
+	db_mock::MockConn,
+	db_schema::DbSchema,
+	mysql_async::Value,
+	rust_decimal::Decimal,
+	serde_json::json,
+	DbModel,
+};
+
+#[derive(DbModel, Debug, Clone, PartialEq)]
+#[from("listini")]
+#[db_schema]
+#[json_schema]
+pub struct Listino {
+	#[pk]
+	pub id: Option<u32>,
+	#[ddl(precision = 12, scale = 4)]
+	pub imponibile: Decimal,
+	pub sconto: Option<Decimal>,
+}
+
+#[test]
+fn decimal_columns_take_the_ddl_digits() {
+	let mut tables = Vec::new();
+	Listino::table_defs(None, &mut tables);
+	assert_eq!(tables[0].create_sql(), "CREATE TABLE listini (
+	id INT UNSIGNED NOT NULL AUTO_INCREMENT PRIMARY KEY,
+	imponibile DECIMAL(12,4) NOT NULL,
+	sconto DECIMAL(10,0) NULL
+);");
+	let properties = &Listino::json_schema()["$defs"]["Listino"]["properties"];
+	assert_eq!(properties["imponibile"], json!({ "type": "string", "pattern": r"^-?\d{1,8}(\.\d{1,4})?$" }));
+}
+
+#[tokio::test]
+async fn decimals_round_trip_exactly() {
+	let imponibile = "12345678.0001";
+	let mut conn = MockConn::new();
+	conn.expect_regex("^SELECT listini")
+		.returning(&["id", "imponibile", "sconto"], vec![vec![1u32.into(), Value::from(imponibile), Value::NULL]]);
+	let found = Listino::find_all(&mut conn).await.unwrap();
+	assert_eq!(found, vec![Listino { id: Some(1), imponibile: Decimal::from_str(imponibile).unwrap(), sconto: None }]);
+	assert_eq!(found[0].imponibile.to_string(), imponibile);
+	let mut conn = MockConn::recording();
+	Listino { id: None, sconto: Some(Decimal::new(1, 1)), ..found[0].clone() }.exec_insert(&mut conn).await.unwrap();
+	assert_eq!(conn.executed()[0].params, vec![Value::from(imponibile), Value::from("0.1")]);
+}
@@ -0,0 +1,101 @@
+use mysql_async_orm::{
+	db_date::DBDate,
+	db_enum::DbSet,
+	fe_export::{TsExport, TsExporter},
+	DbEnum, DbModel,
+};
+
+#[derive(DbEnum, Debug, Clone, Copy, PartialEq)]
+#[db_enum(rename_all = "lowercase")]
+pub enum Stato {
+	Aperto,
+	Chiuso,
+}
+
+#[derive(DbEnum, Debug, Clone, Copy, PartialEq)]
+#[db_enum(integer)]
+pub enum Priorita {
+	Bassa = 1,
+	Alta = 5,
+}
+
+#[derive(DbModel, Debug, Clone)]
+#[from("ordini")]
+#[fe_export]
+pub struct Ordine {
+	#[pk]
+	pub id: Option<u32>,
+	pub codice: String,
+	pub data: DBDate,
+	pub consegna: Option<DBDate>,
+	pub stato: Stato,
+	pub priorita: Option<Priorita>,
+	pub etichette: DbSet<Stato>,
+	#[readonly]
+	pub totale: f64,
+	#[relation("id_ordine")]
+	pub righe: Vec<Riga>,
+}
+
+#[derive(DbModel, Debug, Clone)]
+#[from("righe")]
+#[fe_export]
+pub struct Riga {
+	#[pk]
+	pub id: Option<u32>,
+	pub qta: i32,
+	pub evasa: bool,
+	#[relation("id_riga")]
+	pub lotti: Vec<Lotto>,
+}
+
+#[derive(DbModel, Debug, Clone)]
+#[from("lotti")]
+#[fe_export]
+pub struct Lotto {
+	#[pk]
+	pub id: Option<u32>,
+	pub codice: Option<String>,
+}
+
+#[test]
+fn interfaces_render_optional_fields_enums_and_relations() {
+	assert_eq!(Ordine::NAME, "Ordine");
+	assert_eq!(Ordine::ts_interface(), r#"export interface Ordine {
+	id: number | null;
+	codice: string;
+	data: string;
+	consegna: string | null;
+	stato: "aperto" | "chiuso";
+	priorita: 1 | 5 | null;
+	etichette: ("aperto" | "chiuso")[];
+	readonly totale: number;
+	righe: Riga[];
+}
+"#);
+}
+
+#[test]
+fn the_exporter_follows_relations_once() {
+	let source = TsExporter::new().export::<Ordine>().export::<Riga>().source();
+	assert_eq!(source, format!(
+		"// Generated by mysql_async_orm from the #[fe_export] models, do not edit.\n\n{}\n{}\n{}",
+		Ordine::ts_interface(),
+		Riga::ts_interface(),
+		Lotto::ts_interface(),
+	));
+	assert_eq!(Lotto::ts_interface(), "export interface Lotto {\n\tid: number | null;\n\tcodice: string | null;\n}\n");
+}
+
+#[test]
+fn the_file_is_only_written_when_it_changes() {
+	let path = std::env::temp_dir().join(format!("mysql_async_orm_fe_export_{}.ts", std::process::id()));
+	let mut exporter = TsExporter::new();
+	exporter.export::<Lotto>();
+	assert!(exporter.write(&path).unwrap());
+	assert!(!exporter.write(&path).unwrap());
+	exporter.export::<Riga>();
+	assert!(exporter.write(&path).unwrap());
+	assert_eq!(std::fs::read_to_string(&path).unwrap(), exporter.source());
+	std::fs::remove_file(path).unwrap();
+}
@@ -0,0 +1,105 @@
+use mysql_async_orm::{db_date::DBDate, serde_json::json, DbModel};
+
+#[derive(DbModel, Debug)]
+#[from("ordini")]
+#[json_schema]
+pub struct Ordine {
+	#[pk]
+	pub id: Option<u32>,
+	#[ddl(length = 20)]
+	pub codice: String,
+	pub data: DBDate,
+	pub nota: Option<String>,
+	#[readonly]
+	pub totale: f64,
+	#[relation("id_ordine")]
+	pub righe: Vec<Riga>,
+}
+
+#[derive(DbModel, Debug)]
+#[from("righe")]
+#[json_schema]
+pub struct Riga {
+	#[pk]
+	pub id: Option<u32>,
+	pub qta: i16,
+}
+
+#[test]
+fn schema_has_the_model_and_its_relations() {
+	assert_eq!(Ordine::json_schema(), json!({
+		"$schema": "https://json-schema.org/draft/2020-12/schema",
+		"$ref": "#/$defs/Ordine",
+		"$defs": {
+			"Ordine": {
+				"title": "Ordine",
+				"type": "object",
+				"properties": {
+					"id": { "type": ["integer", "null"], "minimum": 0, "maximum": u32::MAX },
+					"codice": { "type": "string", "maxLength": 20 },
+					"data": { "type": "string", "pattern": r"^\d{4}-\d{2}-\d{2}$" },
+					"nota": { "type": ["string", "null"] },
+					"totale": { "type": "number", "readOnly": true },
+					"righe": { "type": "array", "items": { "$ref": "#/$defs/Riga" } },
+				},
+				"required": ["codice", "data"],
+				"additionalProperties": false,
+			},
+			"Riga": {
+				"title": "Riga",
+				"type": "object",
+				"properties": {
+					"id": { "type": ["integer", "null"], "minimum": 0, "maximum": u32::MAX },
+					"qta": { "type": "integer", "minimum": i16::MIN, "maximum": i16::MAX },
+				},
+				"required": ["qta"],
+				"additionalProperties": false,
+			},
+		},
+	}));
+}
+
+#[test]
+fn relations_are_not_required() {
+	let schema = mysql_async_orm::json_schema::schema_for::<Ordine>();
+	let required = schema["$defs"]["Ordine"]["required"].as_array().unwrap();
+	assert!(!required.contains(&json!("righe")));
+}
+
+#[cfg(feature = "serde")]
+mod serde_default {
+	use mysql_async_orm::{serde_json::json, DbModel};
+
+	#[derive(DbModel, Debug, serde::Deserialize)]
+	#[from("note")]
+	#[json_schema]
+	pub struct Nota {
+		#[pk]
+		pub id: Option<u32>,
+		pub testo: String,
+		#[serde(default)]
+		pub priorita: u8,
+		#[serde(rename = "chiusa", default = "aperta")]
+		pub chiusa: bool,
+	}
+
+	fn aperta() -> bool {
+		false
+	}
+
+	#[derive(DbModel, Debug, Default, serde::Deserialize)]
+	#[from("etichette")]
+	#[json_schema]
+	#[serde(default)]
+	pub struct Etichetta {
+		#[pk]
+		pub id: Option<u32>,
+		pub nome: String,
+	}
+
+	#[test]
+	fn serde_default_fields_are_not_required() {
+		assert_eq!(Nota::json_schema()["$defs"]["Nota"]["required"], json!(["testo"]));
+		assert_eq!(Etichetta::json_schema()["$defs"]["Etichetta"]["required"], json!([]));
+	}
+}
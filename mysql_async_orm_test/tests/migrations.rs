@@ -0,0 +1,98 @@
+use mysql_async_orm::{
+	db_connection::DbError,
+	db_mock::MockConn,
+	migrations::{Migration, MigrationError, MigrationStatus, Migrator, MIGRATIONS_TABLE},
+	mysql_async::Value,
+};
+
+fn migrator() -> Migrator {
+	Migrator::new()
+		.with_migration(Migration::new(1, "init", "CREATE TABLE clienti (id INT)").with_down("DROP TABLE clienti"))
+		.with_migration(Migration::new(2, "seed", "INSERT INTO clienti VALUES (1)").with_down("DELETE FROM clienti"))
+}
+
+fn expect_lock(conn: &mut MockConn, locked: i64) {
+	conn.expect("SELECT GET_LOCK(?, ?)")
+		.with_params([Value::from(MIGRATIONS_TABLE), Value::from(60u32)])
+		.returning(&["locked"], vec![vec![locked.into()]]);
+}
+
+fn expect_unlock(conn: &mut MockConn) {
+	conn.expect("SELECT RELEASE_LOCK(?)").with_params([MIGRATIONS_TABLE]);
+}
+
+fn expect_applied(conn: &mut MockConn, applied: &[&Migration]) {
+	conn.expect_regex(r"^CREATE TABLE IF NOT EXISTS _orm_migrations \(");
+	conn.expect("SELECT version, name, checksum FROM _orm_migrations ORDER BY version").returning(
+		&["version", "name", "checksum"],
+		applied.iter().map(|m| vec![m.version.into(), m.name.as_str().into(), m.checksum().into()]),
+	);
+}
+
+#[tokio::test]
+async fn migrate_applies_the_pending_migrations_under_the_lock() {
+	let migrator = migrator();
+	let mut conn = MockConn::new();
+	expect_lock(&mut conn, 1);
+	expect_applied(&mut conn, &[&migrator.migrations()[0]]);
+	conn.expect("INSERT INTO clienti VALUES (1)");
+	conn.expect("INSERT INTO _orm_migrations (version, name, checksum) VALUES (?, ?, ?)")
+		.with_params([Value::from(2u64), Value::from("seed"), Value::from(migrator.migrations()[1].checksum())]);
+	expect_unlock(&mut conn);
+	assert_eq!(migrator.migrate(&mut conn).await.unwrap(), vec![2]);
+	assert_eq!(conn.pending(), 0);
+}
+
+#[tokio::test]
+async fn migrate_fails_when_the_lock_is_taken() {
+	let mut conn = MockConn::new();
+	expect_lock(&mut conn, 0);
+	let error = migrator().migrate(&mut conn).await.unwrap_err();
+	assert!(matches!(error, DbError::Migration(MigrationError::Locked)));
+	assert_eq!(conn.executed().len(), 1);
+}
+
+#[tokio::test]
+async fn a_failing_script_releases_the_lock() {
+	let mut conn = MockConn::new();
+	expect_lock(&mut conn, 1);
+	expect_applied(&mut conn, &[]);
+	conn.expect("CREATE TABLE clienti (id INT)").fails(1050, "Table 'clienti' already exists");
+	expect_unlock(&mut conn);
+	let error = migrator().migrate(&mut conn).await.unwrap_err();
+	assert!(matches!(error, DbError::Driver(_)));
+	assert_eq!(conn.pending(), 0);
+}
+
+#[tokio::test]
+async fn rollback_runs_the_last_down_script() {
+	let migrator = migrator();
+	let mut conn = MockConn::new();
+	expect_lock(&mut conn, 1);
+	expect_applied(&mut conn, &[&migrator.migrations()[0], &migrator.migrations()[1]]);
+	conn.expect("DELETE FROM clienti");
+	conn.expect("DELETE FROM _orm_migrations WHERE version = ?").with_params([2u64]);
+	expect_unlock(&mut conn);
+	assert_eq!(migrator.rollback(&mut conn, 1).await.unwrap(), vec![2]);
+	assert_eq!(conn.pending(), 0);
+}
+
+#[tokio::test]
+async fn status_lists_the_applied_migrations() {
+	let migrator = migrator();
+	let mut conn = MockConn::new();
+	expect_applied(&mut conn, &[&migrator.migrations()[0]]);
+	assert_eq!(migrator.status(&mut conn).await.unwrap(), vec![
+		MigrationStatus { version: 1, name: "init".to_string(), applied: true },
+		MigrationStatus { version: 2, name: "seed".to_string(), applied: false },
+	]);
+}
+
+#[tokio::test]
+async fn modified_migrations_are_refused() {
+	let changed = Migration::new(1, "init", "CREATE TABLE clienti (id BIGINT)");
+	let mut conn = MockConn::new();
+	expect_applied(&mut conn, &[&changed]);
+	let error = migrator().status(&mut conn).await.unwrap_err();
+	assert!(matches!(error, DbError::Migration(MigrationError::ChecksumMismatch { version: 1, .. })));
+}
@@ -0,0 +1,390 @@
+use std::sync::{Arc, Mutex};
+
+use mysql_async_orm::{
+	db_connection::{DbError, QueryEvent, QueryHook, QueryableConn},
+	db_date::DBTimestamp,
+	db_mock::MockConn,
+	db_model::{exec_write, DbModel, WriteParam, WriteStatement},
+	db_query::Cursor,
+	mysql_async::{params, Value},
+	DbModel,
+};
+
+#[derive(DbModel, Debug, Clone, PartialEq)]
+#[from("clienti")]
+pub struct Cliente {
+	#[pk]
+	id: Option<u32>,
+	nome: String,
+	nota: Option<String>,
+}
+
+#[derive(DbModel, Debug, Clone, PartialEq)]
+#[from("articoli")]
+pub struct Articolo {
+	#[pk]
+	id: Option<u32>,
+	codice: String,
+	#[relation("id_articolo")]
+	distinte: Vec<Distinta>,
+}
+
+#[derive(DbModel, Debug, Clone, PartialEq)]
+#[from("distinte")]
+pub struct Distinta {
+	#[pk]
+	id: Option<u32>,
+	nome: String,
+	#[relation("id_distinta")]
+	componenti: Vec<Componente>,
+}
+
+#[derive(DbModel, Debug, Clone, PartialEq)]
+#[from("componenti")]
+pub struct Componente {
+	#[pk]
+	id: Option<u32>,
+	qta: f64,
+}
+
+#[derive(DbModel, Debug, Clone, PartialEq)]
+#[from("eventi")]
+pub struct Evento {
+	#[pk]
+	id: Option<u32>,
+	creato: DBTimestamp,
+}
+
+const SELECT_CLIENTI: &str = "SELECT clienti.id,clienti.nome,clienti.nota FROM clienti ";
+
+fn cliente(id: u32, nome: &str) -> Vec<Value> {
+	vec![id.into(), nome.into(), Value::NULL]
+}
+
+fn clienti(ids: &[u32]) -> Vec<Vec<Value>> {
+	ids.iter().map(|&id| cliente(id, &format!("c{}", id))).collect()
+}
+
+#[derive(Clone, Default)]
+struct SkippedRows(Arc<Mutex<Vec<String>>>);
+
+impl QueryHook for SkippedRows {
+	fn on_query(&self, _: &QueryEvent<'_>) {}
+
+	fn on_skipped_row(&self, error: &DbError) {
+		self.0.lock().unwrap().push(error.to_string());
+	}
+}
+
+/// Method, SQL, parameter count, affected rows and whether it failed.
+type Event = (&'static str, String, usize, Option<u64>, bool);
+
+#[derive(Default, Clone)]
+struct Events(Arc<Mutex<Vec<Event>>>);
+
+impl QueryHook for Events {
+	fn on_query(&self, event: &QueryEvent<'_>) {
+		self.0.lock().unwrap().push((event.method, event.sql.to_string(), event.params, event.affected_rows, event.error.is_some()));
+	}
+}
+
+#[tokio::test]
+async fn statements_are_reported_to_the_query_hooks() {
+	let events = Events::default();
+	let mut conn = MockConn::new();
+	conn.add_query_hook(events.clone());
+	conn.expect("UPDATE clienti SET nome=? WHERE id=?").affected_rows(1);
+	conn.expect("SELECT nome FROM clienti").returning(&["nome"], vec![vec!["a".into()]]).affected_rows(1);
+	conn.expect("DELETE FROM clienti").fails(1451, "Cannot delete or update a parent row");
+	conn.exec_drop("UPDATE clienti SET nome=? WHERE id=?", ("a", 1u32)).await.unwrap();
+	let _: Vec<String> = conn.query("SELECT nome FROM clienti").await.unwrap();
+	conn.query_drop("DELETE FROM clienti").await.unwrap_err();
+	assert_eq!(*events.0.lock().unwrap(), vec![
+		("exec_drop", "UPDATE clienti SET nome=? WHERE id=?".to_string(), 2, Some(1), false),
+		("query", "SELECT nome FROM clienti".to_string(), 0, None, false),
+		("query_drop", "DELETE FROM clienti".to_string(), 0, None, true),
+	]);
+}
+
+#[tokio::test]
+async fn filters_bind_their_values() {
+	let columns = Cliente::columns();
+	let mut conn = MockConn::new();
+	conn.expect(format!("{} WHERE ((clienti.nome = ?) OR (clienti.nome LIKE ?)) AND (clienti.nota IS NOT NULL) ORDER BY 1;", SELECT_CLIENTI))
+		.with_params(["a", "b%"])
+		.returning(&["id", "nome", "nota"], vec![vec![1u32.into(), "a".into(), "x".into()]]);
+	conn.expect(format!("{} WHERE (clienti.id IN (?,?)) AND (NOT (clienti.nota IS NULL)) ORDER BY 1;", SELECT_CLIENTI))
+		.with_params([1u32, 2]);
+	conn.expect(format!("{} WHERE FALSE ORDER BY 1;", SELECT_CLIENTI));
+	let found = Cliente::query()
+		.filter(columns.nome.eq("a").or(columns.nome.like("b%")))
+		.filter(columns.nota.ne(None::<String>))
+		.fetch(&mut conn)
+		.await
+		.unwrap();
+	assert_eq!(found, vec![Cliente { id: Some(1), nome: "a".to_string(), nota: Some("x".to_string()) }]);
+	Cliente::find(columns.id.in_list([1u32, 2]).and(!columns.nota.is_null()), &mut conn).await.unwrap();
+	Cliente::find(columns.id.in_list(Vec::<u32>::new()), &mut conn).await.unwrap();
+	assert_eq!(conn.pending(), 0);
+}
+
+#[tokio::test]
+async fn get_by_pks_splits_the_placeholders() {
+	let pks: Vec<u32> = (1..=65536).collect();
+	let mut conn = MockConn::new();
+	conn.expect_regex(r"^SELECT clienti\.id.* WHERE clienti\.id IN \(\?(,\?){65534}\) ORDER BY 1;$").returning(&["id", "nome", "nota"], clienti(&[1, 2]));
+	conn.expect(format!("{} WHERE clienti.id IN (?) ORDER BY 1;", SELECT_CLIENTI))
+		.with_params([65536u32])
+		.returning(&["id", "nome", "nota"], clienti(&[65536]));
+	let found = Cliente::get_by_pks(&pks, &mut conn).await.unwrap();
+	assert_eq!(found.iter().map(|c| c.id.unwrap()).collect::<Vec<_>>(), vec![1, 2, 65536]);
+	assert_eq!(conn.executed()[0].params.len(), 65535);
+	assert_eq!(conn.pending(), 0);
+}
+
+#[tokio::test]
+async fn page_counts_and_loads_the_pks() {
+	let mut conn = MockConn::new();
+	conn.expect("SELECT COUNT(DISTINCT clienti.id) FROM clienti  WHERE clienti.nome LIKE ?")
+		.with_params(["c%"])
+		.returning(&["count"], vec![vec![3u64.into()]]);
+	conn.expect("SELECT DISTINCT clienti.id FROM clienti  WHERE clienti.nome LIKE ? ORDER BY clienti.id LIMIT ? OFFSET ?")
+		.with_params([Value::from("c%"), Value::from(2u64), Value::from(0u64)])
+		.returning(&["id"], vec![vec![1u32.into()], vec![2u32.into()]]);
+	conn.expect(format!("{} WHERE clienti.id IN (?,?) ORDER BY 1;", SELECT_CLIENTI))
+		.with_params([1u32, 2])
+		.returning(&["id", "nome", "nota"], clienti(&[1, 2]));
+	let page = Cliente::query().filter(Cliente::columns().nome.like("c%")).page(0, 2, &mut conn).await.unwrap();
+	assert_eq!(page.items.len(), 2);
+	assert_eq!(page.total, Some(3));
+	assert_eq!(page.next_cursor, Some(2));
+	assert_eq!(conn.pending(), 0);
+}
+
+#[tokio::test]
+async fn get_by_pks_orders_the_chunks_by_pk() {
+	let pks: Vec<u32> = (1..=65536).rev().collect();
+	let mut conn = MockConn::new();
+	conn.expect_regex(r"^SELECT clienti\.id.* WHERE clienti\.id IN \(\?(,\?){65534}\) ORDER BY 1;$").returning(&["id", "nome", "nota"], clienti(&[3, 65536]));
+	conn.expect(format!("{} WHERE clienti.id IN (?) ORDER BY 1;", SELECT_CLIENTI))
+		.with_params([1u32])
+		.returning(&["id", "nome", "nota"], clienti(&[1]));
+	let found = Cliente::get_by_pks(&pks, &mut conn).await.unwrap();
+	assert_eq!(found.iter().map(|c| c.id.unwrap()).collect::<Vec<_>>(), vec![1, 3, 65536]);
+}
+
+#[tokio::test]
+async fn page_splits_the_pk_placeholders() {
+	let mut conn = MockConn::new();
+	conn.expect("SELECT COUNT(DISTINCT clienti.id) FROM clienti ").returning(&["count"], vec![vec![65536u64.into()]]);
+	conn.expect("SELECT DISTINCT clienti.id FROM clienti  ORDER BY clienti.id LIMIT ? OFFSET ?")
+		.returning(&["id"], (1..=65536u32).map(|pk| vec![pk.into()]));
+	conn.expect_regex(r"WHERE clienti\.id IN \(\?(,\?){65534}\) ORDER BY 1;$").returning(&["id", "nome", "nota"], clienti(&[1]));
+	conn.expect(format!("{} WHERE clienti.id IN (?) ORDER BY 1;", SELECT_CLIENTI))
+		.with_params([65536u32])
+		.returning(&["id", "nome", "nota"], clienti(&[65536]));
+	let page = Cliente::page(0, 65536, &mut conn).await.unwrap();
+	assert_eq!(page.items.iter().map(|c| c.id.unwrap()).collect::<Vec<_>>(), vec![1, 65536]);
+	assert_eq!(page.next_cursor, None);
+	assert_eq!(conn.pending(), 0);
+}
+
+#[tokio::test]
+async fn page_after_does_not_overflow_the_limit() {
+	let mut conn = MockConn::new();
+	conn.expect("SELECT DISTINCT clienti.nome, clienti.id FROM clienti  ORDER BY clienti.nome, clienti.id LIMIT ?")
+		.with_params([u64::MAX])
+		.returning(&["nome", "id"], vec![]);
+	let page = Cliente::page_after(Cliente::columns().nome, None, u64::MAX, &mut conn).await.unwrap();
+	assert!(page.items.is_empty());
+	assert_eq!(page.next_cursor, None);
+}
+
+#[tokio::test]
+async fn page_after_continues_from_the_cursor() {
+	let nome = Cliente::columns().nome;
+	let mut conn = MockConn::new();
+	conn.expect("SELECT DISTINCT clienti.nome, clienti.id FROM clienti  WHERE (clienti.nome > ?) OR ((clienti.nome = ?) AND (clienti.id > ?)) ORDER BY clienti.nome, clienti.id LIMIT ?")
+		.with_params([Value::from("c4"), Value::from("c4"), Value::from(4u32), Value::from(3u64)])
+		.returning(&["nome", "id"], vec![
+			vec!["c5".into(), 9u32.into()],
+			vec!["c6".into(), 5u32.into()],
+			vec!["c7".into(), 6u32.into()],
+		]);
+	conn.expect_regex(r"WHERE clienti\.id IN \(\?,\?\) ORDER BY 1;$")
+		.with_params([9u32, 5])
+		.returning(&["id", "nome", "nota"], vec![cliente(5, "c6"), cliente(9, "c5")]);
+	let after = Cursor { value: "c4".to_string(), pk: 4 };
+	let page = Cliente::page_after(nome, Some(after), 2, &mut conn).await.unwrap();
+	assert_eq!(page.items.iter().map(|c| c.id.unwrap()).collect::<Vec<_>>(), vec![9, 5]);
+	assert_eq!(page.total, None);
+	assert_eq!(page.next_cursor, Some(Cursor { value: "c6".to_string(), pk: 5 }));
+	assert_eq!(conn.pending(), 0);
+}
+
+fn articolo() -> Articolo {
+	Articolo {
+		id: None,
+		codice: "A1".to_string(),
+		distinte: vec![Distinta {
+			id: None,
+			nome: "base".to_string(),
+			componenti: vec![Componente { id: None, qta: 1.5 }, Componente { id: None, qta: 2.0 }],
+		}],
+	}
+}
+
+#[tokio::test]
+async fn insert_binds_the_parent_insert_ids() {
+	let mut conn = MockConn::new();
+	conn.expect("INSERT INTO articoli (codice) VALUES (?)").with_params(["A1"]).last_insert_id(10);
+	conn.expect("INSERT INTO distinte (id_articolo,nome) VALUES (?,?)").with_params([Value::from(10u64), Value::from("base")]).last_insert_id(20);
+	conn.expect("INSERT INTO componenti (id_distinta,qta) VALUES (?,?)").with_params([Value::from(20u64), Value::from(1.5)]).last_insert_id(30);
+	conn.expect("INSERT INTO componenti (id_distinta,qta) VALUES (?,?)").with_params([Value::from(20u64), Value::from(2.0)]).last_insert_id(31);
+	assert_eq!(articolo().exec_insert(&mut conn).await.unwrap(), 10);
+	assert_eq!(conn.pending(), 0);
+}
+
+#[tokio::test]
+async fn update_inserts_new_children_under_the_existing_parent() {
+	let old = Articolo {
+		id: Some(1),
+		codice: "A1".to_string(),
+		distinte: vec![Distinta { id: Some(2), nome: "vecchia".to_string(), componenti: vec![] }],
+	};
+	let new = Articolo { id: Some(1), ..articolo() };
+	let mut statements = Vec::new();
+	Articolo::prepare_update(None, &new, &old, &mut statements, 0).unwrap();
+	let parent = WriteParam::Value(Value::from(1u32));
+	assert_eq!(statements, vec![
+		WriteStatement {
+			sql: "UPDATE articoli SET codice=? WHERE id=?".to_string(),
+			params: vec![WriteParam::Value("A1".into()), parent.clone()],
+			insert_id: None,
+		},
+		WriteStatement {
+			sql: "INSERT INTO distinte (id_articolo,nome) VALUES (?,?)".to_string(),
+			params: vec![parent, WriteParam::Value("base".into())],
+			insert_id: Some(2),
+		},
+		WriteStatement {
+			sql: "INSERT INTO componenti (id_distinta,qta) VALUES (?,?)".to_string(),
+			params: vec![WriteParam::InsertId(2), WriteParam::Value(1.5.into())],
+			insert_id: Some(3),
+		},
+		WriteStatement {
+			sql: "INSERT INTO componenti (id_distinta,qta) VALUES (?,?)".to_string(),
+			params: vec![WriteParam::InsertId(2), WriteParam::Value(2.0.into())],
+			insert_id: Some(3),
+		},
+		WriteStatement {
+			sql: "DELETE FROM distinte WHERE id_articolo=? AND id=?".to_string(),
+			params: vec![WriteParam::Value(Value::from(1u32)), WriteParam::Value(Value::from(2u32))],
+			insert_id: None,
+		},
+	]);
+	let mut conn = MockConn::recording();
+	exec_write(&mut conn, statements).await.unwrap();
+	assert_eq!(conn.executed()[2].params[0], Value::from(1u64));
+	assert_eq!(conn.executed()[3].params[0], Value::from(1u64));
+}
+
+#[tokio::test]
+async fn recording_gives_insert_ids_to_inserts_only() {
+	let mut conn = MockConn::recording();
+	conn.query_drop("UPDATE clienti SET nome='x'").await.unwrap();
+	assert_eq!(conn.last_insert_id(), None);
+	conn.query_drop(" insert INTO clienti (nome) VALUES ('a')").await.unwrap();
+	assert_eq!(conn.last_insert_id(), Some(1));
+	conn.query_drop("DELETE FROM clienti").await.unwrap();
+	assert_eq!(conn.last_insert_id(), None);
+	conn.query_drop("REPLACE INTO clienti (nome) VALUES ('b')").await.unwrap();
+	assert_eq!(conn.last_insert_id(), Some(2));
+}
+
+#[tokio::test]
+async fn named_params_are_recorded_by_name() {
+	let mut conn = MockConn::recording();
+	conn.exec_drop("UPDATE clienti SET nome=:nome WHERE id=:id", params! { "nome" => "a", "id" => 1u32 }).await.unwrap();
+	assert_eq!(conn.executed()[0].params, vec![Value::from(1u32), Value::from("a")]);
+}
+
+#[tokio::test]
+async fn missing_insert_id_fails_the_write() {
+	let statements = vec![WriteStatement {
+		sql: "INSERT INTO componenti (id_distinta,qta) VALUES (?,?)".to_string(),
+		params: vec![WriteParam::InsertId(1), WriteParam::Value(1.5.into())],
+		insert_id: None,
+	}];
+	let mut conn = MockConn::recording();
+	assert!(matches!(exec_write(&mut conn, statements).await, Err(DbError::MissingInsertId)));
+	assert!(conn.executed().is_empty());
+}
+
+#[tokio::test]
+async fn strict_decoding_fails_on_a_bad_row() {
+	let mut conn = MockConn::new();
+	conn.expect_regex("^SELECT clienti").returning(&["id", "nome", "nota"], vec![cliente(1, "a"), vec![2u32.into(), Value::NULL, Value::NULL]]);
+	match Cliente::find_all(&mut conn).await {
+		Err(DbError::RowDecode { model, table, field, index, .. }) => {
+			assert_eq!((model, table, field, index), ("Cliente", "clienti", "nome", 1));
+		}
+		other => panic!("expected RowDecode, got {:?}", other),
+	}
+}
+
+#[tokio::test]
+async fn lenient_decoding_skips_and_reports_bad_roots() {
+	let skipped = SkippedRows::default();
+	let mut conn = MockConn::new();
+	conn.add_query_hook(skipped.clone());
+	conn.expect_regex("^SELECT articoli").returning(&["id", "codice", "distinta_id", "nome", "componente_id", "qta"], vec![
+		vec![1u32.into(), Value::NULL, 2u32.into(), "a".into(), 3u32.into(), 1.5.into()],
+		vec![1u32.into(), Value::NULL, 2u32.into(), "a".into(), 4u32.into(), 2.5.into()],
+		vec![5u32.into(), "A5".into(), 6u32.into(), "b".into(), 7u32.into(), 1.0.into()],
+	]);
+	let found = Articolo::query().lenient().fetch(&mut conn).await.unwrap();
+	assert_eq!(found, vec![Articolo {
+		id: Some(5),
+		codice: "A5".to_string(),
+		distinte: vec![Distinta { id: Some(6), nome: "b".to_string(), componenti: vec![Componente { id: Some(7), qta: 1.0 }] }],
+	}]);
+	let skipped = skipped.0.lock().unwrap();
+	assert_eq!(skipped.len(), 1);
+	assert!(skipped[0].contains("Articolo.codice"), "{}", skipped[0]);
+}
+
+#[tokio::test]
+async fn an_undecodable_root_pk_is_reported_once() {
+	let skipped = SkippedRows::default();
+	let mut conn = MockConn::new();
+	conn.add_query_hook(skipped.clone());
+	conn.expect_regex("^SELECT articoli").returning(&["id", "codice", "distinta_id", "nome", "componente_id", "qta"], vec![
+		vec![Value::Int(-1), "A0".into(), 2u32.into(), "a".into(), 3u32.into(), 1.5.into()],
+		vec![Value::Int(-1), "A0".into(), 2u32.into(), "a".into(), 4u32.into(), 2.5.into()],
+		vec![Value::Int(-2), "A1".into(), 8u32.into(), "c".into(), 9u32.into(), 1.5.into()],
+		vec![5u32.into(), "A5".into(), 6u32.into(), "b".into(), 7u32.into(), 1.0.into()],
+	]);
+	let found = Articolo::query().lenient().fetch(&mut conn).await.unwrap();
+	assert_eq!(found.iter().map(|articolo| articolo.id).collect::<Vec<_>>(), vec![Some(5)]);
+	let skipped = skipped.0.lock().unwrap();
+	assert_eq!(skipped.len(), 2);
+	assert!(skipped.iter().all(|error| error.contains("Articolo.id")), "{:?}", skipped);
+}
+
+#[tokio::test]
+async fn timestamps_need_the_session_time_zone() {
+	let row = || vec![vec![Value::from(1u32), Value::Date(2024, 1, 2, 4, 4, 5, 0)]];
+	let mut conn = MockConn::new();
+	conn.expect_regex("^SELECT eventi").returning(&["id", "creato"], row());
+	conn.expect_regex("^SELECT eventi").returning(&["id", "creato"], row());
+	match Evento::find_all(&mut conn).await {
+		Err(DbError::MissingTimeZone { model, table, field }) => assert_eq!((model, table, field), ("Evento", "eventi", "creato")),
+		other => panic!("expected MissingTimeZone, got {:?}", other),
+	}
+	assert!(matches!(Evento::query().lenient().fetch(&mut conn).await, Err(DbError::MissingTimeZone { .. })));
+	conn.set_time_zone(60);
+	conn.expect_regex("^SELECT eventi").returning(&["id", "creato"], row());
+	let found = Evento::find_all(&mut conn).await.unwrap();
+	assert_eq!(found[0].creato, "2024-01-02T03:04:05Z".parse().unwrap());
+}
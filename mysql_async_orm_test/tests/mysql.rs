@@ -0,0 +1,20 @@
+//! Tests against a real server, run with `MYSQL_URL=mysql://... cargo test -- --ignored`.
+
+use mysql_async_orm::db_connection::DbConnectionPool;
+
+fn url() -> String {
+	std::env::var("MYSQL_URL").expect("MYSQL_URL must point to a test database")
+}
+
+#[tokio::test]
+#[ignore]
+async fn time_zone_survives_a_pooled_reconnect() {
+	let pool = DbConnectionPool::with_time_zone(format!("{}?pool_min=1&pool_max=1", url()).as_str(), 90).unwrap();
+	for _ in 0..3 {
+		let mut conn = pool.get_conn().await.unwrap();
+		let time_zone: Option<String> = conn.query_first("SELECT @@session.time_zone").await.unwrap();
+		assert_eq!(time_zone.as_deref(), Some("+01:30"));
+		conn.query_drop("SET time_zone = '+00:00'").await.unwrap();
+	}
+	pool.disconnect().await.unwrap();
+}
@@ -0,0 +1,106 @@
+use mysql_async_orm::{
+	db_mock::MockConn,
+	db_schema::ModelRegistry,
+	mysql_async::Value,
+	DbModel,
+};
+
+#[derive(DbModel, Debug)]
+#[from("ordini")]
+#[db_schema]
+pub struct Ordine {
+	#[pk]
+	id: Option<u32>,
+	codice: String,
+	#[relation("id_ordine")]
+	righe: Vec<Riga>,
+}
+
+#[derive(DbModel, Debug)]
+#[from("righe")]
+#[db_schema]
+pub struct Riga {
+	#[pk]
+	id: Option<u32>,
+	qta: i32,
+	nota: Option<String>,
+}
+
+fn registry() -> ModelRegistry {
+	let mut registry = ModelRegistry::new();
+	registry.register::<Riga>().register::<Ordine>();
+	registry
+}
+
+fn column(table: &str, column: &str, data_type: &str, column_type: &str, nullable: bool) -> Vec<Value> {
+	vec![table.into(), column.into(), data_type.into(), column_type.into(), if nullable { "YES" } else { "NO" }.into()]
+}
+
+fn expect_schema(conn: &mut MockConn, columns: Vec<Vec<Value>>, indexes: Vec<Vec<Value>>) {
+	conn.expect_regex(r"information_schema\.COLUMNS")
+		.with_params(["ordini", "righe"])
+		.returning(&["TABLE_NAME", "COLUMN_NAME", "DATA_TYPE", "COLUMN_TYPE", "IS_NULLABLE"], columns);
+	conn.expect_regex(r"information_schema\.STATISTICS")
+		.with_params(["ordini", "righe"])
+		.returning(&["TABLE_NAME", "COLUMN_NAME"], indexes);
+	conn.expect_regex(r"information_schema\.KEY_COLUMN_USAGE")
+		.with_params(["ordini", "righe"])
+		.returning(&["TABLE_NAME", "COLUMN_NAME"], vec![]);
+}
+
+#[test]
+fn table_defs_put_referenced_tables_first() {
+	let names: Vec<_> = registry().table_defs().into_iter().map(|t| t.table).collect();
+	assert_eq!(names, ["ordini", "righe"]);
+}
+
+#[tokio::test]
+async fn missing_tables_are_created_and_dropped_in_reverse() {
+	let mut conn = MockConn::new();
+	expect_schema(&mut conn, vec![], vec![]);
+	let diff = registry().diff_schema(&mut conn).await.unwrap();
+	assert_eq!(diff.up.len(), 2);
+	assert!(diff.up[0].starts_with("CREATE TABLE ordini ("));
+	assert!(diff.up[1].starts_with("CREATE TABLE righe ("));
+	assert_eq!(diff.down, ["DROP TABLE righe;", "DROP TABLE ordini;"]);
+	assert_eq!(conn.pending(), 0);
+}
+
+#[tokio::test]
+async fn existing_tables_are_altered() {
+	let mut conn = MockConn::new();
+	expect_schema(
+		&mut conn,
+		vec![
+			column("ordini", "id", "int", "int unsigned", false),
+			column("ordini", "codice", "text", "text", false),
+			column("ordini", "legacy", "int", "int", true),
+			column("righe", "id", "int", "int unsigned", false),
+			column("righe", "qta", "varchar", "varchar(10)", false),
+			column("righe", "nota", "varchar", "varchar(40)", false),
+		],
+		vec![vec!["ordini".into(), "id".into()], vec!["righe".into(), "id".into()]],
+	);
+	let diff = registry().diff_schema(&mut conn).await.unwrap();
+	assert_eq!(
+		diff.up,
+		[
+			"-- ALTER TABLE ordini DROP COLUMN legacy;",
+			"ALTER TABLE righe MODIFY COLUMN qta INT NOT NULL;",
+			"ALTER TABLE righe MODIFY COLUMN nota varchar(40) NULL;",
+			"ALTER TABLE righe ADD COLUMN id_ordine INT UNSIGNED NOT NULL;",
+			"ALTER TABLE righe ADD INDEX righe_id_ordine_idx (id_ordine);",
+			"ALTER TABLE righe ADD CONSTRAINT righe_id_ordine_fk FOREIGN KEY (id_ordine) REFERENCES ordini(id);",
+		]
+	);
+	assert_eq!(
+		diff.down,
+		[
+			"ALTER TABLE righe DROP FOREIGN KEY righe_id_ordine_fk;",
+			"ALTER TABLE righe DROP INDEX righe_id_ordine_idx;",
+			"ALTER TABLE righe DROP COLUMN id_ordine;",
+			"ALTER TABLE righe MODIFY COLUMN nota varchar(40) NOT NULL;",
+			"ALTER TABLE righe MODIFY COLUMN qta varchar(10) NOT NULL;",
+		]
+	);
+}
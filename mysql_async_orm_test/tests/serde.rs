@@ -0,0 +1,80 @@
+#![cfg(feature = "serde")]
+
+use mysql_async_orm::{
+	db_date::DBDate,
+	db_enum::DbSet,
+	db_json::DbJson,
+	serde_json::{self, json},
+	DbEnum, DbModel,
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(DbEnum, Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[db_enum(rename_all = "lowercase")]
+pub enum Reparto {
+	Taglio,
+	Saldatura,
+	Verniciatura,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Parametri {
+	tolleranza: f64,
+}
+
+#[derive(DbModel, Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[from("articoli")]
+pub struct Articolo {
+	#[pk]
+	pub id: Option<u32>,
+	pub codice: String,
+	pub introdotto: DBDate,
+	pub reparti: DbSet<Reparto>,
+	pub parametri: DbJson<Parametri>,
+	pub note: Option<DbJson<serde_json::Value>>,
+	#[relation("id_articolo")]
+	pub distinte: Vec<Distinta>,
+}
+
+#[derive(DbModel, Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[from("distinte")]
+pub struct Distinta {
+	#[pk]
+	pub id: Option<u32>,
+	pub nome: String,
+}
+
+fn articolo() -> Articolo {
+	Articolo {
+		id: Some(1),
+		codice: "A1".to_string(),
+		introdotto: DBDate::new(2024, 1, 31).unwrap(),
+		reparti: [Reparto::Verniciatura, Reparto::Taglio].into_iter().collect(),
+		parametri: DbJson(Parametri { tolleranza: 0.5 }),
+		note: None,
+		distinte: vec![Distinta { id: None, nome: "base".to_string() }],
+	}
+}
+
+#[test]
+fn models_serialize_nested_and_round_trip() {
+	let value = serde_json::to_value(articolo()).unwrap();
+	assert_eq!(value, json!({
+		"id": 1,
+		"codice": "A1",
+		"introdotto": "2024-01-31",
+		"reparti": ["taglio", "verniciatura"],
+		"parametri": { "tolleranza": 0.5 },
+		"note": null,
+		"distinte": [{ "id": null, "nome": "base" }],
+	}));
+	assert_eq!(serde_json::from_value::<Articolo>(value).unwrap(), articolo());
+}
+
+#[test]
+fn unknown_set_members_fail_to_deserialize() {
+	let mut value = serde_json::to_value(articolo()).unwrap();
+	value["reparti"] = json!(["taglio", "fresatura"]);
+	let error = serde_json::from_value::<Articolo>(value).unwrap_err();
+	assert!(error.to_string().contains("unknown member \"fresatura\""), "{}", error);
+}